@@ -4,6 +4,7 @@ use orientdb_client::aio::OrientDB;
 use orientdb_client::derive::FromResult;
 use orientdb_client::OrientResult;
 
+#[allow(dead_code)]
 #[derive(FromResult, Debug)]
 struct User {
     name: String,
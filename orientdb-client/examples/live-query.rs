@@ -0,0 +1,23 @@
+use orientdb_client::{OrientDB, OrientResult};
+use std::thread;
+
+fn main() -> OrientResult<()> {
+    let client = OrientDB::connect(("localhost", 2424))?;
+    let session = client.session("demodb", "admin", "admin")?;
+
+    let (unsubscriber, results) = session.live_query("select from V").run()?;
+
+    let handle = thread::spawn(move || {
+        for event in results {
+            println!("Event {:?}", event);
+        }
+    });
+
+    let _: Vec<_> = session.command("insert into V set id = 1").run()?.collect();
+
+    unsubscriber.unsubscribe()?;
+
+    handle.join().expect("Failed to join the live query thread");
+
+    Ok(())
+}
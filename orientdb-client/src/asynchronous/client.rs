@@ -7,6 +7,7 @@ use crate::common::protocol::messages::request::{
     Close, Connect, CreateDB, DropDB, ExistDB, MsgHeader, Open,
 };
 use crate::common::protocol::messages::response;
+use crate::common::session::{Credentials, Reopen, ReopenPolicy};
 use crate::common::types::result::OResult;
 use crate::common::ConnectionOptions;
 use crate::{DatabaseType, OrientResult};
@@ -25,15 +26,31 @@ pub struct OrientDB {
 impl OrientDB {
     pub async fn connect<T: Into<ConnectionOptions>>(options: T) -> OrientResult<OrientDB> {
         let opts = options.into();
-        let addr: SocketAddr = format!("{}:{}", opts.host, opts.port)
-            .to_socket_addrs()?
-            .next()
-            .expect("Cannot parse socket address");
 
-        let cluster = Cluster::builder().add_server(addr).build().await?;
+        let mut builder = Cluster::builder()
+            .strategy(opts.strategy.clone())
+            .pool_timeout(opts.pool_timeout)
+            .timeouts(opts.timeouts())
+            .discover(opts.discover);
+
+        for (host, port) in opts.addresses() {
+            let addr: SocketAddr = format!("{}:{}", host, port)
+                .to_socket_addrs()?
+                .next()
+                .expect("Cannot parse socket address");
+            builder = builder.add_server(host, addr);
+        }
+
+        #[cfg(feature = "tls")]
+        if let Some(tls) = &opts.tls {
+            builder = builder.tls(tls.connector()?);
+        }
+
+        let cluster = builder.build().await?;
 
         let internal = OrientDBClientInternal {
             cluster: Arc::new(cluster),
+            reopen: opts.reopen.clone(),
         };
 
         Ok(OrientDB { internal })
@@ -43,6 +60,7 @@ impl OrientDB {
 #[derive(Clone, Debug)]
 pub struct OrientDBClientInternal {
     cluster: Arc<Cluster>,
+    reopen: Arc<dyn ReopenPolicy>,
 }
 
 impl Deref for OrientDB {
@@ -67,9 +85,7 @@ impl OrientDBClientInternal {
         min_size: Option<u32>,
         max_size: Option<u32>,
     ) -> OrientResult<SessionPool> {
-        let server = self.cluster.select();
-        SessionPoolManager::new(self.clone(), server, db_name, user, password)
-            .managed(min_size, max_size)
+        SessionPoolManager::new(self.clone(), db_name, user, password).managed(min_size, max_size)
     }
     pub async fn session(
         &self,
@@ -98,17 +114,22 @@ impl OrientDBClientInternal {
         password: &str,
         pooled: bool,
     ) -> OrientResult<OSession> {
-        let mut conn = server.connection().await?;
+        let (server, mut response) = self
+            .cluster
+            .send(&server, Open::new(db_name, user, password).into())
+            .await?;
+        let response: response::Open = response.payload();
 
-        let response: response::Open = conn
-            .send(Open::new(db_name, user, password).into())
-            .await?
-            .payload();
+        // Discovering the other members is best effort, the session is usable anyway
+        let _ = self
+            .cluster
+            .subscribe(&server, response.session_id, response.token.clone())
+            .await;
 
         Ok(OSession::new(
             -1,
-            response.session_id,
-            response.token,
+            Credentials::new(response.session_id, response.token),
+            Reopen::new(db_name, user, password, self.reopen.clone()),
             self.cluster.clone(),
             server.clone(),
             pooled,
@@ -215,7 +236,7 @@ impl OrientDBClientInternal {
         user: &str,
         password: &str,
         query: &str,
-    ) -> OrientResult<ServerStatement> {
+    ) -> OrientResult<ServerStatement<'_>> {
         Ok(ServerStatement::new(
             self,
             user.to_string(),
@@ -228,15 +249,28 @@ impl OrientDBClientInternal {
         &self,
         stmt: ServerStatement<'_>,
     ) -> OrientResult<impl Stream<Item = OrientResult<OResult>>> {
-        let user = stmt.user.clone();
-        let pwd = stmt.password.clone();
-        self.run_as_admin(&user, &pwd, move |session, mut conn| async move {
-            let response: response::ServerQuery = conn
-                .send(stmt.into_query(session.session_id, session.token).into())
-                .await?
-                .payload();
-            Ok((conn, ServerResultSet::new(response)))
-        })
-        .await
+        let (mut conn, server) = self.cluster.connection().await?;
+        let admin: response::Connect = conn
+            .send(Connect::new(&stmt.user, &stmt.password).into())
+            .await?
+            .payload();
+        let timeout = stmt.timeout;
+        let query = stmt.into_query(admin.session_id, admin.token.clone());
+        let page_size = query.page_size;
+        match conn.send_with_timeout(query.into(), timeout).await {
+            Ok(mut response) => Ok(ServerResultSet::new(
+                server,
+                response.payload(),
+                admin.session_id,
+                admin.token,
+                page_size,
+                timeout,
+            )),
+            Err(e) => {
+                conn.send_and_forget(Close::new(admin.session_id, admin.token).into())
+                    .await?;
+                Err(e)
+            }
+        }
     }
 }
@@ -1,8 +1,11 @@
 use super::network::cluster::Server;
+use super::network::conn::Connection;
+use super::session;
 
 #[cfg(feature = "async-std-runtime")]
 mod async_std_use {
     pub use async_std::sync::Mutex;
+    pub use async_std::task;
 }
 #[cfg(feature = "async-std-runtime")]
 use async_std_use::*;
@@ -11,92 +14,264 @@ use async_std_use::*;
 mod tokio_use {
     // pub use tokio::sync::mpsc::Sender;
     pub use tokio::sync::Mutex;
+    pub use tokio::task;
 }
 
-use futures::channel::mpsc::Sender;
-use futures::sink::SinkExt;
+use futures::stream::Stream;
 
 #[cfg(feature = "tokio-runtime")]
 pub use tokio_use::*;
 
 use std::collections::HashMap;
+use std::io;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::task::{Context, Poll};
 
-use crate::common::protocol::messages::request::UnsubscribeLiveQuery;
+use crate::common::live::{LiveQueue, DEFAULT_BUFFER};
+use crate::common::protocol::messages::request::{LiveQuery, UnsubscribeLiveQuery};
+use crate::common::protocol::messages::response;
 use crate::common::protocol::messages::response::LiveQueryResult;
-use crate::types::LiveResult;
+use crate::common::session::{Credentials, Reopen, SessionCredentials};
+use crate::types::{LiveEvent, OverflowPolicy};
 
-use crate::OrientResult;
+use crate::{OrientError, OrientResult};
 
-pub struct Unsubscriber {
-    monitor_id: i32,
-    session_id: i32,
-    token: Option<Vec<u8>>,
+// A live query and the connection it is currently registered on, shared by
+// the stream, the unsubscriber and the task that resubscribes it.
+// The requests are sent with the current credentials of the session.
+pub(crate) struct Subscription {
+    query: LiveQuery,
     server: Arc<Server>,
+    credentials: Arc<SessionCredentials>,
+    reopen: Reopen,
+    current: Mutex<Option<(i32, Connection)>>,
+    active: AtomicBool,
 }
 
-impl Unsubscriber {
-    pub fn new(
-        monitor_id: i32,
-        session_id: i32,
-        token: Option<Vec<u8>>,
+impl Subscription {
+    pub(crate) fn new(
+        query: LiveQuery,
         server: Arc<Server>,
+        credentials: Arc<SessionCredentials>,
+        reopen: Reopen,
     ) -> Self {
-        Unsubscriber {
-            monitor_id,
-            session_id,
-            token,
+        Subscription {
+            query,
             server,
+            credentials,
+            reopen,
+            current: Mutex::new(None),
+            active: AtomicBool::new(true),
         }
     }
 
-    pub async fn unsubscribe(self) -> OrientResult<()> {
-        let mut conn = self.server.connection().await?;
+    /// Run the live query on a fresh connection, its events are pushed to `queue`.
+    /// When the session expired it is opened again and the live query sent once more
+    pub(crate) async fn subscribe(&self, queue: Arc<LiveQueue>) -> OrientResult<()> {
+        let credentials = self.credentials.get();
+        match self
+            .subscribe_with(queue.clone(), credentials.clone())
+            .await
+        {
+            Err(e) if self.reopen.allows(&e) => {
+                let fresh =
+                    session::reopen(&self.server, &self.reopen, &self.credentials, &credentials)
+                        .await?;
+                self.subscribe_with(queue, fresh).await
+            }
+            result => result,
+        }
+    }
 
-        conn.send_and_forget(
-            UnsubscribeLiveQuery::new(self.session_id, self.token, self.monitor_id).into(),
-        )
-        .await?;
+    async fn subscribe_with(
+        &self,
+        queue: Arc<LiveQueue>,
+        credentials: Credentials,
+    ) -> OrientResult<()> {
+        let Credentials { session_id, token } = credentials;
+        let mut query = self.query.clone();
+        query.session_id = session_id;
+        query.token = token;
+        let mut conn = self.server.dedicated_connection().await?;
+        let mut response = conn.send(query.into()).await?;
+        self.credentials.renew(session_id, &response.header);
+        let q: response::LiveQuery = response.payload();
+        conn.register_handler(q.monitor_id, queue).await?;
+        *self.current.lock().await = Some((q.monitor_id, conn));
+        Ok(())
+    }
 
+    async fn unsubscribe(&self) -> OrientResult<()> {
+        if !self.active.swap(false, Ordering::SeqCst) {
+            return Ok(());
+        }
+        // The connection is kept until the server ends the live query
+        if let Some((monitor_id, conn)) = self.current.lock().await.as_mut() {
+            let Credentials { session_id, token } = self.credentials.get();
+            conn.send_and_forget(UnsubscribeLiveQuery::new(session_id, token, *monitor_id).into())
+                .await?;
+        }
         Ok(())
     }
+
+    fn is_active(&self) -> bool {
+        self.active.load(Ordering::SeqCst)
+    }
+}
+
+/// Forward the events of `inner` to `outer` on a task, running the live
+/// query again on a fresh connection every time its connection fails
+pub(crate) fn resubscribe(
+    subscription: Arc<Subscription>,
+    inner: Arc<LiveQueue>,
+    outer: Arc<LiveQueue>,
+) {
+    task::spawn(async move {
+        forward(subscription, inner, &outer).await;
+        outer.close();
+    });
+}
+
+async fn forward(subscription: Arc<Subscription>, mut inner: Arc<LiveQueue>, outer: &LiveQueue) {
+    loop {
+        let mut failed = false;
+        while let Some(item) = inner.recv().await {
+            match item {
+                Err(e) if e.is_connection_error() => failed = true,
+                item => {
+                    if !outer.push(item).await {
+                        inner.disconnect();
+                        return;
+                    }
+                }
+            }
+        }
+        if !failed || !subscription.is_active() {
+            return;
+        }
+        let next = Arc::new(LiveQueue::new(DEFAULT_BUFFER, OverflowPolicy::Block));
+        if let Err(e) = subscription.subscribe(next.clone()).await {
+            outer.fail(e);
+            return;
+        }
+        inner = next;
+    }
+}
+
+pub struct Unsubscriber {
+    subscription: Arc<Subscription>,
+}
+
+impl Unsubscriber {
+    pub(crate) fn new(subscription: Arc<Subscription>) -> Self {
+        Unsubscriber { subscription }
+    }
+
+    pub async fn unsubscribe(self) -> OrientResult<()> {
+        self.subscription.unsubscribe().await
+    }
+}
+
+/// Events of a live query. Dropping the stream unsubscribes the live query.
+pub struct LiveStream {
+    queue: Arc<LiveQueue>,
+    subscription: Arc<Subscription>,
+}
+
+impl LiveStream {
+    pub(crate) fn new(queue: Arc<LiveQueue>, subscription: Arc<Subscription>) -> Self {
+        LiveStream {
+            queue,
+            subscription,
+        }
+    }
+
+    /// Number of events dropped because the buffer of the stream was full
+    pub fn dropped(&self) -> u64 {
+        self.queue.dropped()
+    }
+}
+
+impl Stream for LiveStream {
+    type Item = OrientResult<LiveEvent>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.queue.poll_recv(cx)
+    }
+}
+
+impl Drop for LiveStream {
+    fn drop(&mut self) {
+        self.queue.disconnect();
+        if !self.subscription.is_active() {
+            return;
+        }
+        let subscription = self.subscription.clone();
+        #[cfg(feature = "tokio-runtime")]
+        if tokio::runtime::Handle::try_current().is_err() {
+            return;
+        }
+        task::spawn(async move {
+            let _ = subscription.unsubscribe().await;
+        });
+    }
 }
 
 pub struct LiveQueryManager {
-    live_queries: Mutex<HashMap<i32, Sender<OrientResult<LiveResult>>>>,
+    live_queries: Mutex<HashMap<i32, Arc<LiveQueue>>>,
 }
 
 impl LiveQueryManager {
-    pub async fn register_handler(
+    pub(crate) async fn register_handler(
         &self,
         monitor_id: i32,
-        sender: Sender<OrientResult<LiveResult>>,
+        queue: Arc<LiveQueue>,
     ) -> OrientResult<()> {
         let mut guard = self.live_queries.lock().await;
-        guard.insert(monitor_id, sender);
+        guard.insert(monitor_id, queue);
         Ok(())
     }
 
-    pub async fn fire_event(&self, mut evt: LiveQueryResult) -> OrientResult<()> {
-        let mut guard = self.live_queries.lock().await;
+    pub async fn fire_event(&self, evt: LiveQueryResult) -> OrientResult<()> {
+        // The lock is not held while pushing, a full buffer only waits
+        // for its own subscriber
+        let handler = {
+            let mut guard = self.live_queries.lock().await;
+            if evt.ended {
+                guard.remove(&evt.monitor_id)
+            } else {
+                guard.get(&evt.monitor_id).cloned()
+            }
+        };
 
-        if evt.ended {
-            if let Some(mut handler) = guard.remove(&evt.monitor_id) {
-                while let Some(e) = evt.events.pop() {
-                    handler.send(Ok(e)).await?;
+        if let Some(handler) = handler {
+            let monitor_id = evt.monitor_id;
+            let ended = evt.ended;
+            for event in evt.into_events() {
+                if !handler.push(Ok(event)).await {
+                    // Nobody is listening anymore
+                    self.live_queries.lock().await.remove(&monitor_id);
+                    break;
                 }
-                drop(handler);
             }
-        } else {
-            if let Some(handler) = guard.get_mut(&evt.monitor_id) {
-                while let Some(e) = evt.events.pop() {
-                    handler.send(Ok(e)).await?;
-                }
+            if ended {
+                handler.close();
             }
         }
 
         Ok(())
     }
+
+    /// Tell every live query that its connection failed
+    pub async fn fail(&self, error: &io::Error) {
+        let handlers: Vec<_> = self.live_queries.lock().await.drain().collect();
+        for (_, handler) in handlers {
+            let e = io::Error::new(error.kind(), error.to_string());
+            handler.fail(OrientError::Io(e));
+        }
+    }
 }
 impl Default for LiveQueryManager {
     fn default() -> Self {
@@ -105,3 +280,85 @@ impl Default for LiveQueryManager {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::LiveQueryManager;
+    use crate::common::live::LiveQueue;
+    use crate::common::protocol::messages::response::LiveQueryResult;
+    use crate::types::{LiveResult, OResult, OverflowPolicy};
+    use crate::OrientError;
+    use futures::executor::block_on;
+    use std::io;
+    use std::sync::Arc;
+
+    #[test]
+    fn fail_live_queries() {
+        block_on(async {
+            let manager = LiveQueryManager::default();
+            let queue = Arc::new(LiveQueue::new(10, OverflowPolicy::Block));
+            manager.register_handler(1, queue.clone()).await.unwrap();
+
+            let error = io::Error::new(io::ErrorKind::UnexpectedEof, "closed");
+            manager.fail(&error).await;
+
+            assert!(matches!(queue.recv().await, Some(Err(OrientError::Io(_)))));
+            assert!(queue.recv().await.is_none());
+        });
+    }
+
+    #[test]
+    fn fire_event_on_full_buffer() {
+        block_on(async {
+            let manager = LiveQueryManager::default();
+            let queue = Arc::new(LiveQueue::new(1, OverflowPolicy::DropNewest));
+            manager.register_handler(1, queue.clone()).await.unwrap();
+
+            let events = (0..3)
+                .map(|_| LiveResult::Created(OResult::empty()))
+                .collect();
+            manager
+                .fire_event(LiveQueryResult::new(1, true, events))
+                .await
+                .unwrap();
+
+            assert_eq!(queue.dropped(), 2);
+            assert!(matches!(queue.recv().await, Some(Ok(_))));
+            assert!(queue.recv().await.is_none());
+        });
+    }
+
+    #[test]
+    fn fire_events_in_order() {
+        block_on(async {
+            let manager = LiveQueryManager::default();
+            let queue = Arc::new(LiveQueue::new(10, OverflowPolicy::Block));
+            manager.register_handler(3, queue.clone()).await.unwrap();
+
+            let events = vec![
+                LiveResult::Created(OResult::empty()),
+                LiveResult::Updated((OResult::empty(), OResult::empty())),
+                LiveResult::Deleted(OResult::empty()),
+            ];
+            manager
+                .fire_event(LiveQueryResult::new(3, true, events))
+                .await
+                .unwrap();
+
+            let mut received = vec![];
+            while let Some(event) = queue.recv().await {
+                let event = event.unwrap();
+                assert_eq!(3, event.monitor_id());
+                received.push(event.into_result());
+            }
+            assert!(matches!(
+                received.as_slice(),
+                [
+                    LiveResult::Created(_),
+                    LiveResult::Updated(_),
+                    LiveResult::Deleted(_)
+                ]
+            ));
+        });
+    }
+}
@@ -1,10 +1,10 @@
-use super::live::Unsubscriber;
+use super::live::{LiveStream, Unsubscriber};
 use super::session::OSession;
+use crate::common::live::{LiveQueue, DEFAULT_BUFFER};
 use crate::common::protocol::messages::request::LiveQuery;
 use crate::common::types::value::{IntoOValue, OValue};
-use crate::types::LiveResult;
+use crate::types::OverflowPolicy;
 use crate::OrientResult;
-use futures::Stream;
 use std::collections::HashMap;
 
 pub struct LiveStatement<'a> {
@@ -12,6 +12,9 @@ pub struct LiveStatement<'a> {
     stm: String,
     params: HashMap<String, OValue>,
     named: bool,
+    resilient: bool,
+    buffer: usize,
+    overflow: OverflowPolicy,
 }
 
 impl<'a> LiveStatement<'a> {
@@ -21,6 +24,9 @@ impl<'a> LiveStatement<'a> {
             stm,
             params: HashMap::new(),
             named: true,
+            resilient: false,
+            buffer: DEFAULT_BUFFER,
+            overflow: OverflowPolicy::default(),
         }
     }
 
@@ -36,25 +42,44 @@ impl<'a> LiveStatement<'a> {
     pub fn named(mut self, params: &[(&str, &dyn IntoOValue)]) -> Self {
         self.params = params
             .iter()
-            .map(|&(k, ref v)| (String::from(k), v.into_ovalue()))
+            .map(|(k, v)| (String::from(*k), v.into_ovalue()))
             .collect();
 
         self.named = true;
         self
     }
 
-    pub async fn run(
-        self,
-    ) -> OrientResult<(Unsubscriber, impl Stream<Item = OrientResult<LiveResult>>)> {
-        self.session.live_run(self.into()).await
+    /// Run the live query again on a fresh connection when its connection fails,
+    /// instead of ending the stream with the error
+    pub fn resilient(mut self, resilient: bool) -> Self {
+        self.resilient = resilient;
+        self
+    }
+
+    /// Number of events buffered for the stream, 10 by default
+    pub fn buffer(mut self, buffer: usize) -> Self {
+        self.buffer = buffer;
+        self
+    }
+
+    /// What to do with events when the buffer is full, `OverflowPolicy::Block` by default
+    pub fn overflow(mut self, overflow: OverflowPolicy) -> Self {
+        self.overflow = overflow;
+        self
+    }
+
+    pub async fn run(self) -> OrientResult<(Unsubscriber, LiveStream)> {
+        let queue = LiveQueue::new(self.buffer, self.overflow);
+        let resilient = self.resilient;
+        self.session.live_run(self.into(), queue, resilient).await
     }
 }
 
 impl<'a> From<LiveStatement<'a>> for LiveQuery {
     fn from(x: LiveStatement) -> LiveQuery {
         LiveQuery::new(
-            x.session.session_id,
-            x.session.token.clone(),
+            x.session.session_id(),
+            x.session.token(),
             x.stm,
             x.params,
             x.named,
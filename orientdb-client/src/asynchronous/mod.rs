@@ -2,9 +2,11 @@ pub mod client;
 pub mod live;
 pub mod live_statement;
 pub mod network;
+pub mod push;
 pub mod server_statement;
 pub mod session;
 pub mod statement;
+pub mod transaction;
 pub mod types;
 pub use client::OrientDB;
 pub use session::OSession;
@@ -1,16 +1,55 @@
 use super::conn::Connection;
 
+use crate::common::cluster::{Endpoint, FirstAvailable, SelectionStrategy, Timeouts, Transport};
+use crate::common::protocol::messages::request::SubscribeDistributedConfig;
+use crate::common::protocol::messages::{Request, Response};
+#[cfg(feature = "tls")]
+use crate::common::tls::TlsConnector;
 use crate::{OrientError, OrientResult};
 use async_trait::async_trait;
+use futures::channel::mpsc::unbounded;
+use futures::channel::oneshot;
+use futures::future::{select, Either};
+use futures::stream::StreamExt;
 use mobc::{Connection as PooledConnection, Manager, Pool};
 use std::net::SocketAddr;
-use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, RwLock, Weak};
+use std::time::Duration;
+
+#[cfg(feature = "async-std-runtime")]
+use async_std::task;
+
+#[cfg(feature = "tokio-runtime")]
+use tokio::task;
+
+// Resolve a host of the distributed configuration without blocking the runtime
+#[cfg(feature = "async-std-runtime")]
+async fn resolve(host: &str) -> Option<SocketAddr> {
+    use async_std::net::ToSocketAddrs;
+    host.to_socket_addrs().await.ok()?.next()
+}
+
+#[cfg(feature = "tokio-runtime")]
+async fn resolve(host: &str) -> Option<SocketAddr> {
+    tokio::net::lookup_host(host).await.ok()?.next()
+}
 
 pub type AsyncConnection = PooledConnection<ServerConnectionManager>;
 
 #[derive(Debug)]
 pub struct Cluster {
-    servers: Vec<Arc<Server>>,
+    servers: RwLock<Vec<Arc<Server>>>,
+    // The servers given by the user, kept to reconnect when the discovered ones go away
+    seeds: Vec<SocketAddr>,
+    strategy: Arc<dyn SelectionStrategy>,
+    pool_max: u32,
+    pool_timeout: Option<Duration>,
+    transport: Transport,
+    discover: bool,
+    subscribed: AtomicBool,
+    // Dropped with the cluster to stop listening to pushes
+    subscription: Mutex<Option<oneshot::Sender<()>>>,
 }
 
 impl Cluster {
@@ -19,35 +58,232 @@ impl Cluster {
     }
 
     pub(crate) async fn connection(&self) -> OrientResult<(AsyncConnection, Arc<Server>)> {
-        let conn = self.servers[0]
-            .connection()
-            .await
-            .map_err(OrientError::from)?;
-        Ok((conn, self.servers[0].clone()))
+        let server = self.select();
+        match server.connection().await {
+            Ok(conn) => Ok((conn, server)),
+            Err(e) => {
+                for other in self.failover(&server) {
+                    if let Ok(conn) = other.connection().await {
+                        return Ok((conn, other.clone()));
+                    }
+                }
+                Err(e)
+            }
+        }
     }
 
     pub(crate) fn select(&self) -> Arc<Server> {
-        self.servers[0].clone()
+        let servers = self.servers();
+        let healthy: Vec<bool> = servers.iter().map(|s| s.is_healthy()).collect();
+        // When every server is marked as failed give all of them another chance
+        let idx = self
+            .strategy
+            .select(&healthy)
+            .or_else(|| self.strategy.select(&vec![true; healthy.len()]))
+            .unwrap_or(0);
+        servers[idx].clone()
+    }
+
+    /// Send a request to `server`. Idempotent requests that do not belong to a session
+    /// are sent again to the other servers when `server` cannot be reached, the other
+    /// servers do not know the sessions opened on `server`.
+    /// Returns the server that answered along with the response
+    pub(crate) async fn send(
+        &self,
+        server: &Arc<Server>,
+        request: Request,
+    ) -> OrientResult<(Arc<Server>, Response)> {
+        self.send_with_timeout(server, request, None).await
+    }
+
+    /// Like `send`, waiting at most `timeout` for each response
+    /// instead of the request timeout of the connections
+    pub(crate) async fn send_with_timeout(
+        &self,
+        server: &Arc<Server>,
+        request: Request,
+        timeout: Option<Duration>,
+    ) -> OrientResult<(Arc<Server>, Response)> {
+        let retry = if request.is_idempotent() && request.is_sessionless() {
+            Some(request.clone())
+        } else {
+            None
+        };
+        let mut error = match server.send(request, timeout).await {
+            Err(e) if retry.is_some() && e.is_connection_error() => e,
+            result => return result.map(|response| (server.clone(), response)),
+        };
+        if let Some(request) = retry {
+            for other in self.failover(server) {
+                match other.send(request.clone(), timeout).await {
+                    Err(e) if e.is_connection_error() => error = e,
+                    result => return result.map(|response| (other.clone(), response)),
+                }
+            }
+        }
+        Err(error)
+    }
+
+    fn failover(&self, failed: &Arc<Server>) -> Vec<Arc<Server>> {
+        self.servers()
+            .into_iter()
+            .filter(|s| !Arc::ptr_eq(s, failed) && s.is_healthy())
+            .collect()
+    }
+
+    fn servers(&self) -> Vec<Arc<Server>> {
+        self.servers
+            .read()
+            .expect("Cluster servers lock poisoned")
+            .clone()
+    }
+
+    /// Subscribe to the distributed configuration pushed by `server`
+    /// using the credentials of an open session.
+    ///
+    /// Only one subscription is kept for the cluster, it is made again
+    /// on a later session when the connection used for it is lost
+    pub(crate) async fn subscribe(
+        self: &Arc<Self>,
+        server: &Arc<Server>,
+        session_id: i32,
+        token: Option<Vec<u8>>,
+    ) -> OrientResult<()> {
+        if !self.discover || self.subscribed.swap(true, Ordering::SeqCst) {
+            return Ok(());
+        }
+        let result = self.subscribe_with(server, session_id, token).await;
+        if result.is_err() {
+            self.subscribed.store(false, Ordering::SeqCst);
+        }
+        result
+    }
+
+    async fn subscribe_with(
+        self: &Arc<Self>,
+        server: &Arc<Server>,
+        session_id: i32,
+        token: Option<Vec<u8>>,
+    ) -> OrientResult<()> {
+        // Pushes are received on a dedicated connection that is not
+        // returned to the pool
+        let mut conn = Connection::connect_to(&server.endpoint).await?;
+        let (sender, mut receiver) = unbounded();
+        conn.register_config_handler(sender).await;
+        conn.send(SubscribeDistributedConfig::new(session_id, token).into())
+            .await?;
+
+        let (stop, mut stopped) = oneshot::channel();
+        *self
+            .subscription
+            .lock()
+            .expect("Cluster subscription lock poisoned") = Some(stop);
+
+        let cluster = Arc::downgrade(self);
+        task::spawn(async move {
+            loop {
+                let config = match select(receiver.next(), stopped).await {
+                    Either::Left((Some(config), pending)) => {
+                        stopped = pending;
+                        config
+                    }
+                    _ => break,
+                };
+                match Weak::upgrade(&cluster) {
+                    Some(cluster) => cluster.update(&config.hosts).await,
+                    None => break,
+                }
+            }
+            if let Some(cluster) = Weak::upgrade(&cluster) {
+                cluster.subscribed.store(false, Ordering::SeqCst);
+            }
+            let _ = conn.close().await;
+        });
+        Ok(())
+    }
+
+    /// Replace the servers with the members of the distributed configuration,
+    /// followed by the seed servers that are not members.
+    /// Known servers keep their pool, members that cannot be resolved are skipped
+    pub(crate) async fn update(&self, hosts: &[String]) {
+        let mut endpoints: Vec<Endpoint> = Vec::new();
+        for host in hosts {
+            let address = match resolve(host).await {
+                Some(address) => address,
+                None => continue,
+            };
+            let name = host
+                .rsplit_once(':')
+                .map_or(host.as_str(), |(name, _)| name);
+            if let Ok(endpoint) = self.transport.endpoint(name, address) {
+                endpoints.push(endpoint);
+            }
+        }
+        if endpoints.is_empty() {
+            return;
+        }
+        let mut servers = self.servers.write().expect("Cluster servers lock poisoned");
+        let mut updated: Vec<Arc<Server>> = endpoints
+            .into_iter()
+            .map(|endpoint| {
+                servers
+                    .iter()
+                    .find(|s| s.endpoint.address == endpoint.address)
+                    .cloned()
+                    .unwrap_or_else(|| {
+                        Arc::new(Server::new(endpoint, self.pool_max, self.pool_timeout))
+                    })
+            })
+            .collect();
+        let seeds: Vec<Arc<Server>> = servers
+            .iter()
+            .filter(|s| self.seeds.contains(&s.endpoint.address))
+            .filter(|s| {
+                !updated
+                    .iter()
+                    .any(|u| u.endpoint.address == s.endpoint.address)
+            })
+            .cloned()
+            .collect();
+        updated.extend(seeds);
+        *servers = updated;
     }
 }
 
 pub struct ClusterBuilder {
     pool_max: u32,
-    servers: Vec<SocketAddr>,
+    pool_timeout: Option<Duration>,
+    servers: Vec<(String, SocketAddr)>,
+    strategy: Arc<dyn SelectionStrategy>,
+    transport: Transport,
+    discover: bool,
 }
 
 impl ClusterBuilder {
     pub async fn build(self) -> OrientResult<Cluster> {
         let pool_max = self.pool_max;
+        let pool_timeout = self.pool_timeout;
 
-        let mut servers = vec![];
-
-        for server in self.servers {
-            let s = Server::connect(server, pool_max).await?;
-            servers.push(Arc::new(s));
-        }
+        let servers = self
+            .servers
+            .iter()
+            .map(|(host, address)| {
+                let endpoint = self.transport.endpoint(host, *address)?;
+                Ok(Arc::new(Server::new(endpoint, pool_max, pool_timeout)))
+            })
+            .collect::<OrientResult<_>>()?;
 
-        Ok(Cluster { servers })
+        Ok(Cluster {
+            servers: RwLock::new(servers),
+            seeds: self.servers.iter().map(|(_, address)| *address).collect(),
+            strategy: self.strategy,
+            pool_max,
+            pool_timeout,
+            transport: self.transport,
+            discover: self.discover,
+            subscribed: AtomicBool::new(false),
+            subscription: Mutex::new(None),
+        })
     }
 
     pub fn pool_max(mut self, pool_max: u32) -> Self {
@@ -55,8 +291,36 @@ impl ClusterBuilder {
         self
     }
 
-    pub fn add_server<T: Into<SocketAddr>>(mut self, address: T) -> Self {
-        self.servers.push(address.into());
+    /// Time to wait for a pooled connection, the default of `mobc` when `None`
+    pub(crate) fn pool_timeout(mut self, pool_timeout: Option<Duration>) -> Self {
+        self.pool_timeout = pool_timeout;
+        self
+    }
+
+    pub(crate) fn timeouts(mut self, timeouts: Timeouts) -> Self {
+        self.transport.timeouts = timeouts;
+        self
+    }
+
+    /// Add the server `host` resolved to `address`
+    pub fn add_server<T: Into<SocketAddr>>(mut self, host: &str, address: T) -> Self {
+        self.servers.push((String::from(host), address.into()));
+        self
+    }
+
+    pub fn strategy(mut self, strategy: Arc<dyn SelectionStrategy>) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    pub fn discover(mut self, discover: bool) -> Self {
+        self.discover = discover;
+        self
+    }
+
+    #[cfg(feature = "tls")]
+    pub(crate) fn tls(mut self, connector: TlsConnector) -> Self {
+        self.transport.tls = Some(connector);
         self
     }
 }
@@ -65,42 +329,91 @@ impl Default for ClusterBuilder {
     fn default() -> ClusterBuilder {
         ClusterBuilder {
             pool_max: 20,
+            pool_timeout: None,
             servers: vec![],
+            strategy: Arc::new(FirstAvailable),
+            transport: Transport::default(),
+            discover: true,
         }
     }
 }
 
 pub struct Server {
+    endpoint: Endpoint,
     pool: Pool<ServerConnectionManager>,
+    // Cleared when a connection fails, set again by the pool when a connection is
+    // established or passes its check
+    healthy: Arc<AtomicBool>,
 }
 
 impl std::fmt::Debug for Server {
     fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
-        fmt.debug_struct("Server").finish()
+        fmt.debug_struct("Server")
+            .field("address", &self.endpoint.address)
+            .finish()
     }
 }
 
 impl Server {
-    async fn connect(address: SocketAddr, pool_max: u32) -> OrientResult<Server> {
-        let manager = ServerConnectionManager { address };
-        let pool = Pool::builder()
+    fn new(endpoint: Endpoint, pool_max: u32, pool_timeout: Option<Duration>) -> Server {
+        let healthy = Arc::new(AtomicBool::new(true));
+        let manager = ServerConnectionManager {
+            endpoint: endpoint.clone(),
+            healthy: healthy.clone(),
+        };
+        let mut builder = Pool::builder()
             .max_open(pool_max as u64)
-            .max_idle(pool_max as u64)
-            .build(manager);
+            .max_idle(pool_max as u64);
+        if pool_timeout.is_some() {
+            builder = builder.get_timeout(pool_timeout);
+        }
+        let pool = builder.build(manager);
+
+        Server {
+            endpoint,
+            pool,
+            healthy,
+        }
+    }
 
-        Ok(Server { pool })
+    pub(crate) fn is_healthy(&self) -> bool {
+        self.healthy.load(Ordering::SeqCst)
     }
 
     pub(crate) async fn connection(
         &self,
     ) -> OrientResult<PooledConnection<ServerConnectionManager>> {
-        self.pool.get().await.map_err(OrientError::from)
+        let conn = self.pool.get().await.map_err(OrientError::from);
+        if conn.is_err() {
+            self.healthy.store(false, Ordering::SeqCst);
+        }
+        conn
+    }
+
+    /// A connection outside of the pool, owned by the caller
+    pub(crate) async fn dedicated_connection(&self) -> OrientResult<Connection> {
+        Connection::connect_to(&self.endpoint).await
+    }
+
+    async fn send(&self, request: Request, timeout: Option<Duration>) -> OrientResult<Response> {
+        let result = self
+            .connection()
+            .await?
+            .send_with_timeout(request, timeout)
+            .await;
+        if let Err(e) = &result {
+            if e.is_connection_error() {
+                self.healthy.store(false, Ordering::SeqCst);
+            }
+        }
+        result
     }
 }
 
 #[derive(Debug)]
 pub struct ServerConnectionManager {
-    address: SocketAddr,
+    endpoint: Endpoint,
+    healthy: Arc<AtomicBool>,
 }
 
 #[async_trait]
@@ -109,10 +422,18 @@ impl Manager for ServerConnectionManager {
     type Error = OrientError;
 
     async fn connect(&self) -> Result<Connection, OrientError> {
-        Connection::connect(&self.address).await
+        let conn = Connection::connect_to(&self.endpoint).await;
+        self.healthy.store(conn.is_ok(), Ordering::SeqCst);
+        conn
     }
 
     async fn check(&self, conn: Self::Connection) -> Result<Self::Connection, Self::Error> {
+        conn.check()?;
+        self.healthy.store(true, Ordering::SeqCst);
         Ok(conn)
     }
+
+    fn validate(&self, conn: &mut Self::Connection) -> bool {
+        !conn.is_broken()
+    }
 }
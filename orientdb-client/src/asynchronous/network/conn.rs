@@ -3,27 +3,33 @@ use futures::io::{AsyncRead, AsyncWrite, AsyncWriteExt};
 use futures::sink::SinkExt;
 use futures::stream::StreamExt;
 use std::collections::VecDeque;
+use std::io;
 use std::net::Shutdown;
 use std::net::SocketAddr;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::time::Duration;
 
 use crate::common::protocol::messages::request::HandShake;
 use crate::common::protocol::messages::{
-    response::LiveQueryResult, response::Status, Request, Response,
+    response::DistributedConfig, response::LiveQueryResult, response::Status, Request, Response,
 };
 use crate::sync::protocol::WiredProtocol;
 use crate::{OrientError, OrientResult};
 
 use super::super::live::LiveQueryManager;
-use super::decoder::decode;
+use super::decoder::{decode, decode_header};
 use super::reader;
+use super::timeout::timeout;
+#[cfg(feature = "tls")]
+use crate::asynchronous::network::stream::split_tls;
 use crate::asynchronous::network::stream::{split, ShutdownStream};
-use crate::types::LiveResult;
+use crate::common::cluster::Endpoint;
+use crate::common::live::LiveQueue;
+use crate::types::PushEvent;
 
 #[cfg(feature = "async-std-runtime")]
 mod async_std_use {
-    pub use async_std::io::BufReader;
     pub use async_std::net::TcpStream;
     pub use async_std::sync::Mutex;
     pub use async_std::task;
@@ -33,19 +39,24 @@ use async_std_use::*;
 
 #[cfg(feature = "tokio-runtime")]
 mod tokio_use {
-    pub use tokio::io::BufReader;
     pub use tokio::net::TcpStream;
     pub use tokio::sync::Mutex;
     pub use tokio::task;
 }
 
-use futures::channel::mpsc::{channel, Receiver, Sender};
+use futures::channel::mpsc::{channel, Receiver, SendError, Sender, UnboundedSender};
+use futures::channel::oneshot;
+use futures::future::{select, Either};
 
 #[cfg(feature = "tokio-runtime")]
 pub use tokio_use::*;
 
 pub type ChannelMsg = Cmd;
 pub type ResponseChannel = Sender<OrientResult<Response>>;
+// The response channel along with the kind of push subscribed by the request
+type PendingResponse = (ResponseChannel, Option<i8>);
+type ConfigHandler = Arc<Mutex<Option<UnboundedSender<DistributedConfig>>>>;
+type PushHandler = Arc<Mutex<Option<UnboundedSender<OrientResult<PushEvent>>>>>;
 
 #[derive(Debug)]
 pub enum Cmd {
@@ -56,7 +67,12 @@ pub enum Cmd {
 
 pub struct Connection {
     sender: Sender<ChannelMsg>,
+    request_timeout: Option<Duration>,
+    // Set by the reader and writer tasks when the stream cannot be used anymore
+    broken: Arc<AtomicBool>,
     live_query_manager: Arc<LiveQueryManager>,
+    config_handler: ConfigHandler,
+    push_handler: PushHandler,
 }
 
 impl std::fmt::Debug for Connection {
@@ -81,50 +97,83 @@ where
 fn sender_loop<T>(
     mut stream: T,
     mut channel: Receiver<ChannelMsg>,
-    queue: Arc<Mutex<VecDeque<ResponseChannel>>>,
+    queue: Arc<Mutex<VecDeque<PendingResponse>>>,
     mut protocol: WiredProtocol,
     shutdown_flag: Arc<AtomicBool>,
+    io_timeout: Option<Duration>,
+    mut reader_stopped: oneshot::Receiver<()>,
 ) where
     T: AsyncWrite + Send + ShutdownStream + Unpin + 'static,
 {
     task::spawn(async move {
         loop {
-            match channel.next().await {
+            let msg = match select(channel.next(), &mut reader_stopped).await {
+                Either::Left((msg, _)) => msg,
+                // Nothing reads the responses anymore, close the socket
+                Either::Right(_) => {
+                    break_stream(&stream, &shutdown_flag);
+                    break;
+                }
+            };
+            match msg {
                 Some(msg) => match msg {
+                    Cmd::Msg(mut m) if shutdown_flag.load(Ordering::SeqCst) => {
+                        // Nothing will read the response of a broken connection
+                        let _ = m.0.send(Err(broken())).await;
+                    }
                     Cmd::Msg(mut m) => {
+                        let subscription = m.1.subscription();
                         let mut guard = queue.lock().await;
 
-                        match encode_and_write(&mut stream, &mut protocol, m.1).await {
-                            Ok(()) => {
-                                guard.push_back(m.0);
+                        let write = encode_and_write(&mut stream, &mut protocol, m.1);
+                        match timeout(io_timeout, "writing the request", write).await {
+                            Ok(Ok(())) => {
+                                guard.push_back((m.0, subscription));
                                 drop(guard);
                             }
-                            Err(e) => {
+                            Ok(Err(e)) => {
                                 drop(guard);
+                                if let OrientError::Io(_) = e {
+                                    break_stream(&stream, &shutdown_flag);
+                                }
                                 match m.0.send(Err(e)).await {
                                     Ok(_) => {}
                                     Err(_e) => {}
                                 };
                             }
+                            Err(e) => {
+                                drop(guard);
+                                break_stream(&stream, &shutdown_flag);
+                                let _ = m.0.send(Err(e)).await;
+                            }
                         }
                     }
                     Cmd::MsgNoResponse(mut m) => {
-                        match encode_and_write(&mut stream, &mut protocol, m.1).await {
-                            Ok(()) => match m.0.send(Ok(())).await {
-                                Ok(_e) => {}
-                                Err(_e) => {}
-                            },
-                            Err(e) => match m.0.send(Err(e)).await {
+                        let write = encode_and_write(&mut stream, &mut protocol, m.1);
+                        match timeout(io_timeout, "writing the request", write).await {
+                            Ok(Ok(())) => match m.0.send(Ok(())).await {
                                 Ok(_e) => {}
                                 Err(_e) => {}
                             },
+                            Ok(Err(e)) => {
+                                if let OrientError::Io(_) = e {
+                                    break_stream(&stream, &shutdown_flag);
+                                }
+                                match m.0.send(Err(e)).await {
+                                    Ok(_e) => {}
+                                    Err(_e) => {}
+                                }
+                            }
+                            Err(e) => {
+                                break_stream(&stream, &shutdown_flag);
+                                let _ = m.0.send(Err(e)).await;
+                            }
                         }
                     }
                     Cmd::Shutdown => {
                         shutdown_flag.store(true, Ordering::SeqCst);
-                        stream
-                            .shutdown(Shutdown::Both)
-                            .expect("Failed to shutdown the socket");
+                        // the reader may have closed the socket already
+                        let _ = stream.shutdown(Shutdown::Both);
                     }
                 },
                 None => {
@@ -141,32 +190,87 @@ fn sender_loop<T>(
     });
 }
 
+/// Read the responses and pushes until the stream fails. Dropping `stopped`
+/// when the loop ends lets the writer task close the socket
+#[allow(clippy::too_many_arguments)]
 fn responder_loop<T>(
     mut stream: T,
-    queue: Arc<Mutex<VecDeque<ResponseChannel>>>,
+    queue: Arc<Mutex<VecDeque<PendingResponse>>>,
     protocol: WiredProtocol,
     live_manager: Arc<LiveQueryManager>,
+    config_handler: ConfigHandler,
+    push_handler: PushHandler,
     shutdown_flag: Arc<AtomicBool>,
+    stopped: oneshot::Sender<()>,
 ) where
     T: AsyncRead + Send + Unpin + 'static,
 {
     task::spawn(async move {
+        let _stopped = stopped;
         loop {
-            let response = decode(protocol.version, &mut stream).await;
+            let response = match decode_header(protocol.version, &mut stream).await {
+                Ok(header) => {
+                    let subscription = if header.status == Status::OK && header.op == 100 {
+                        queue.lock().await.front().and_then(|pending| pending.1)
+                    } else {
+                        None
+                    };
+                    decode(protocol.version, header, subscription, &mut stream).await
+                }
+                Err(e) => Err(e),
+            };
 
-            if let Err(e) = &response {
-                match e {
-                    OrientError::Protocol(_p) => {
-                        if shutdown_flag.load(Ordering::SeqCst) {
-                            break;
-                        }
-                    }
-                    _ => {}
+            if response.is_err() && shutdown_flag.load(Ordering::SeqCst) {
+                break;
+            }
+
+            // Errors reported by the server are read whole, any other error
+            // leaves the stream broken or the rest of a message unread
+            let failure = match &response {
+                Ok(_)
+                | Err(OrientError::Request(_))
+                | Err(OrientError::ConcurrentModification(_)) => None,
+                Err(OrientError::Io(e)) => Some(io_error(e)),
+                Err(_) => Some(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "The connection is broken by a response that could not be decoded",
+                )),
+            };
+            if let Some(e) = failure {
+                // No more pushes nor responses will come from a broken connection
+                shutdown_flag.store(true, Ordering::SeqCst);
+                config_handler.lock().await.take();
+                if let Some(handler) = push_handler.lock().await.take() {
+                    let _ = handler.unbounded_send(Err(OrientError::Io(io_error(&e))));
+                }
+                live_manager.fail(&e).await;
+                let mut guard = queue.lock().await;
+                // the first request gets the error of its response
+                if let Some((mut s, _)) = guard.pop_front() {
+                    let _ = s.send(response).await;
                 }
+                while let Some((mut s, _)) = guard.pop_front() {
+                    let _ = s.send(Err(OrientError::Io(io_error(&e)))).await;
+                }
+                break;
             }
 
             let result = match response {
                 Ok(mut r) => match r.header.status {
+                    Status::PUSH if r.header.op == 80 => {
+                        let config: DistributedConfig = r.payload();
+                        if let Some(handler) = config_handler.lock().await.as_ref() {
+                            let _ = handler.unbounded_send(config);
+                        }
+                        None
+                    }
+                    Status::PUSH if r.header.op != 81 => {
+                        let event: PushEvent = r.payload();
+                        if let Some(handler) = push_handler.lock().await.as_ref() {
+                            let _ = handler.unbounded_send(Ok(event));
+                        }
+                        None
+                    }
                     Status::PUSH => {
                         let live_result: LiveQueryResult = r.payload::<LiveQueryResult>();
                         match live_manager.fire_event(live_result).await {
@@ -183,27 +287,75 @@ fn responder_loop<T>(
             if let Some(response) = result {
                 let mut guard = queue.lock().await;
 
-                match guard.pop_front() {
-                    Some(mut s) => {
-                        drop(guard);
-                        match s.send(response).await {
-                            Ok(_m) => {}
-                            Err(_e) => {}
-                        }
+                if let Some((mut s, _)) = guard.pop_front() {
+                    drop(guard);
+                    match s.send(response).await {
+                        Ok(_m) => {}
+                        Err(_e) => {}
                     }
-                    None => {}
                 }
             }
         }
     });
 }
 
+// A write that failed or was cut by a timeout may leave a partial message
+// on the socket, nothing else can be sent on the connection
+fn break_stream<T: ShutdownStream>(stream: &T, shutdown_flag: &AtomicBool) {
+    shutdown_flag.store(true, Ordering::SeqCst);
+    let _ = stream.shutdown(Shutdown::Both);
+}
+
+fn io_error(e: &io::Error) -> io::Error {
+    io::Error::new(e.kind(), e.to_string())
+}
+
+fn broken() -> OrientError {
+    OrientError::Io(io::Error::new(
+        io::ErrorKind::NotConnected,
+        "The connection is broken",
+    ))
+}
+
 impl Connection {
-    pub async fn connect(addr: &SocketAddr) -> OrientResult<Self> {
-        let stream = TcpStream::connect(addr).await?;
+    pub async fn connect(address: &SocketAddr) -> OrientResult<Self> {
+        Connection::connect_to(&Endpoint::plain(*address)).await
+    }
+
+    pub(crate) async fn connect_to(endpoint: &Endpoint) -> OrientResult<Self> {
+        let timeouts = endpoint.timeouts;
+        let what = format!("connecting to {}", endpoint.address);
+
+        let mut conn = timeout(timeouts.connect, &what, Connection::open(endpoint)).await??;
+        conn.request_timeout = timeouts.request;
+
+        Ok(conn)
+    }
+
+    async fn open(endpoint: &Endpoint) -> OrientResult<Self> {
+        let io_timeout = endpoint.timeouts.io;
+        let stream = TcpStream::connect(&endpoint.address).await?;
+
+        #[cfg(feature = "tls")]
+        if let Some((connector, name)) = &endpoint.tls {
+            let (reader, writer) = split_tls(stream, connector, name.clone()).await?;
+            return Connection::start(reader, writer, io_timeout).await;
+        }
+
+        let (reader, writer) = split(stream);
 
-        let (mut reader, writer) = split(stream);
+        Connection::start(reader, writer, io_timeout).await
+    }
 
+    async fn start<R, W>(
+        mut reader: R,
+        writer: W,
+        io_timeout: Option<Duration>,
+    ) -> OrientResult<Self>
+    where
+        R: AsyncRead + Send + Unpin + 'static,
+        W: AsyncWrite + Send + ShutdownStream + Unpin + 'static,
+    {
         let p = reader::read_i16(&mut reader).await?;
 
         let protocol = WiredProtocol::from_version(p)?;
@@ -212,26 +364,47 @@ impl Connection {
 
         let live_query_manager = Arc::new(LiveQueryManager::default());
 
+        let config_handler = Arc::new(Mutex::new(None));
+
+        let push_handler = Arc::new(Mutex::new(None));
+
+        let shutdown_flag = Arc::new(AtomicBool::new(false));
+
         let conn = Connection {
             sender,
+            request_timeout: None,
+            broken: shutdown_flag.clone(),
             live_query_manager: live_query_manager.clone(),
+            config_handler: config_handler.clone(),
+            push_handler: push_handler.clone(),
         };
 
-        let shutdown_flag = Arc::new(AtomicBool::new(false));
-
         let queue = Arc::new(Mutex::new(VecDeque::new()));
 
         let p_version = protocol.version;
 
+        let (stopped, reader_stopped) = oneshot::channel();
+
         sender_loop(
             writer,
             receiver,
             queue.clone(),
             protocol.clone(),
             shutdown_flag.clone(),
+            io_timeout,
+            reader_stopped,
         );
 
-        responder_loop(reader, queue, protocol, live_query_manager, shutdown_flag);
+        responder_loop(
+            reader,
+            queue,
+            protocol,
+            live_query_manager,
+            config_handler,
+            push_handler,
+            shutdown_flag,
+            stopped,
+        );
 
         conn.handshake(p_version).await
     }
@@ -248,40 +421,158 @@ impl Connection {
 
     pub(crate) async fn send_and_forget(&mut self, request: Request) -> OrientResult<()> {
         let (sender, mut receiver) = channel(1);
-
-        self.sender
+        let sent = self
+            .sender
             .send(Cmd::MsgNoResponse((sender, request)))
-            .await?;
-
-        let result = receiver
-            .next()
-            .await
-            .expect("It should contain the response");
-
-        return result;
+            .await;
+        answer(sent, receiver.next().await)
     }
 
-    pub async fn register_handler(
+    pub(crate) async fn register_handler(
         &self,
         monitor_id: i32,
-        sender: Sender<OrientResult<LiveResult>>,
+        queue: Arc<LiveQueue>,
     ) -> OrientResult<()> {
         self.live_query_manager
-            .register_handler(monitor_id, sender)
+            .register_handler(monitor_id, queue)
             .await
     }
 
+    /// Forward the distributed configuration pushed by the server to `sender`
+    pub async fn register_config_handler(&self, sender: UnboundedSender<DistributedConfig>) {
+        *self.config_handler.lock().await = Some(sender);
+    }
+
+    /// Forward the pushes other than live queries and distributed configuration to `sender`
+    pub(crate) async fn register_push_handler(
+        &self,
+        sender: UnboundedSender<OrientResult<PushEvent>>,
+    ) {
+        *self.push_handler.lock().await = Some(sender);
+    }
+
     pub async fn send(&mut self, request: Request) -> OrientResult<Response> {
+        self.send_with_timeout(request, None).await
+    }
+
+    /// Send a request waiting at most `limit` for its response,
+    /// the request timeout of the connection when `None`
+    pub(crate) async fn send_with_timeout(
+        &mut self,
+        request: Request,
+        limit: Option<Duration>,
+    ) -> OrientResult<Response> {
+        let limit = limit.or(self.request_timeout);
         let (sender, mut receiver) = channel(1);
-        self.sender.send(Cmd::Msg((sender, request))).await?;
-        receiver
-            .next()
-            .await
-            .expect("It should contain the response")
+        let response = async {
+            let sent = self.sender.send(Cmd::Msg((sender, request))).await;
+            answer(sent, receiver.next().await)
+        };
+        let result = timeout(limit, "waiting for the response", response).await;
+        if result.is_err() {
+            // As on the sync connection, a connection whose request timed out is not
+            // trusted anymore and is evicted from the pools. The request keeps its slot
+            // in the queue until then, so that the next responses still match their requests
+            self.broken.store(true, Ordering::SeqCst);
+        }
+        result?
+    }
+
+    /// Whether the reader or writer task stopped, the connection cannot be used anymore
+    pub(crate) fn is_broken(&self) -> bool {
+        self.broken.load(Ordering::SeqCst) || self.sender.is_closed()
+    }
+
+    /// Check without a round trip that the connection can still be used
+    pub(crate) fn check(&self) -> OrientResult<()> {
+        if self.is_broken() {
+            return Err(broken());
+        }
+        Ok(())
     }
 
     pub async fn close(mut self) -> OrientResult<()> {
-        &self.sender.send(Cmd::Shutdown).await;
+        let _ = self.sender.send(Cmd::Shutdown).await;
         Ok(())
     }
 }
+
+// The flush of a sent command fails when the writer task stops right after
+// taking it, its answer is still delivered. The writer task drops the channel
+// without answering when it stops before
+fn answer<T>(sent: Result<(), SendError>, answer: Option<OrientResult<T>>) -> OrientResult<T> {
+    match answer {
+        Some(answer) => answer,
+        None => Err(sent.err().map_or_else(broken, OrientError::from)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Connection;
+    use crate::common::cluster::{Endpoint, Timeouts};
+    use crate::common::protocol::messages::request::Connect;
+    use crate::OrientError;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::thread;
+    use std::time::Duration;
+
+    // A server that accepts the handshake and never answers
+    fn silent_server() -> Endpoint {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        thread::spawn(move || {
+            let (mut socket, _) = listener.accept().unwrap();
+            socket.write_all(&37i16.to_be_bytes()).unwrap();
+            let mut buf = [0; 1024];
+            while matches!(socket.read(&mut buf), Ok(read) if read > 0) {}
+        });
+        Endpoint::plain(address)
+    }
+
+    #[cfg_attr(feature = "async-std-runtime", async_std::test)]
+    #[cfg_attr(feature = "tokio-runtime", tokio::test)]
+    async fn request_timeout_breaks_connection() {
+        let mut endpoint = silent_server();
+        endpoint.timeouts = Timeouts {
+            request: Some(Duration::from_millis(100)),
+            ..Default::default()
+        };
+        let mut conn = Connection::connect_to(&endpoint).await.unwrap();
+        assert!(conn.check().is_ok());
+
+        let result = conn.send(Connect::new("root", "root").into()).await;
+        assert!(matches!(result, Err(OrientError::Timeout(_))));
+        assert!(conn.is_broken());
+        assert!(conn.check().is_err());
+    }
+
+    #[cfg_attr(feature = "async-std-runtime", async_std::test)]
+    #[cfg_attr(feature = "tokio-runtime", tokio::test)]
+    async fn undecodable_response_closes_connection() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let endpoint = Endpoint::plain(listener.local_addr().unwrap());
+        let (connected, on_connect) = std::sync::mpsc::channel();
+        let (closed, on_close) = std::sync::mpsc::channel();
+        thread::spawn(move || {
+            let (mut socket, _) = listener.accept().unwrap();
+            socket.write_all(&37i16.to_be_bytes()).unwrap();
+            on_connect.recv().unwrap();
+            // a push of an unknown type
+            socket.write_all(&[3, 99]).unwrap();
+            let mut buf = [0; 1024];
+            while matches!(socket.read(&mut buf), Ok(read) if read > 0) {}
+            let _ = closed.send(());
+        });
+        let mut conn = Connection::connect_to(&endpoint).await.unwrap();
+        connected.send(()).unwrap();
+
+        // the push may be read before the request is queued
+        let result = conn.send(Connect::new("root", "root").into()).await;
+        assert!(result.is_err());
+        assert!(conn.is_broken());
+        assert!(conn.check().is_err());
+        assert!(on_close.recv_timeout(Duration::from_secs(5)).is_ok());
+    }
+}
@@ -1,26 +1,53 @@
 use super::reader;
 use crate::common::protocol::deserializer::DocumentDeserializer;
-use crate::common::protocol::messages::response::Response;
 use crate::common::protocol::messages::response::Status;
 use crate::common::protocol::messages::response::{
-    Connect, CreateDB, DropDB, ExistDB, Header, LiveQuery, LiveQueryResult, Open, Query,
-    QueryClose, ServerQuery,
+    BeginTransaction, CommitTransaction, Connect, CreateDB, CreateRecord, DeleteRecord,
+    DistributedConfig, DropDB, ExistDB, Header, LiveQuery, LiveQueryResult, LoadRecord, Open,
+    Query, QueryClose, RollbackTransaction, SBTreeEntriesMajor, SBTreeFirstKey, ServerQuery,
+    SubscribeDistributedConfig, SubscribePush, UnsubscribeLiveQuery, UpdateRecord,
 };
+use crate::common::protocol::messages::response::{Response, ResponseType};
+use crate::common::protocol::v37::{decode_bag_entries, decode_bag_key};
 use crate::common::types::error::{OError, RequestError};
-use crate::common::types::OResult;
+use crate::common::types::push::{PushEvent, StorageConfiguration};
+use crate::common::types::{ODocument, OResult};
 use crate::sync::protocol::v37::Protocol37 as P37Sync;
 use crate::types::LiveResult;
 use crate::{OrientError, OrientResult};
 use async_trait::async_trait;
 use futures::io::AsyncRead;
 use std::collections::{HashMap, VecDeque};
+use std::convert::TryFrom;
 
-pub async fn decode<T>(version: i16, buf: &mut T) -> OrientResult<Response>
+pub async fn decode_header<T>(version: i16, buf: &mut T) -> OrientResult<Header>
 where
     T: AsyncRead + Unpin + Send,
 {
     if version >= 37 {
-        return decode_with::<Protocol37, T>(buf).await;
+        return Protocol37::decode_header(buf).await;
+    }
+    Err(OrientError::Protocol(format!(
+        "Protocol {} not supported",
+        version
+    )))
+}
+
+/// Decode the payload of a response given its header.
+///
+/// `subscription` is the kind of push subscribed by the pending request,
+/// as the responses to the subscribe message have no other way to be told apart
+pub async fn decode<T>(
+    version: i16,
+    header: Header,
+    subscription: Option<i8>,
+    buf: &mut T,
+) -> OrientResult<Response>
+where
+    T: AsyncRead + Unpin + Send,
+{
+    if version >= 37 {
+        return decode_with::<Protocol37, T>(header, subscription, buf).await;
     }
     Err(OrientError::Protocol(format!(
         "Protocol {} not supported",
@@ -39,12 +66,14 @@ impl VersionedDecoder for Protocol37 {
         let status = reader::read_i8(buf).await?;
 
         if status == 3 {
+            // pushes carry the push type in place of the request
+            let op = reader::read_i8(buf).await?;
             Ok(Header {
                 status: Status::from(status),
                 client_id: None,
                 session_id: -1,
                 token: None,
-                op: 100,
+                op,
             })
         } else {
             let session_id = reader::read_i32(buf).await?;
@@ -107,9 +136,6 @@ impl VersionedDecoder for Protocol37 {
     where
         T: AsyncRead + Unpin + Send,
     {
-        // handle only live query for now
-        let _push_type = reader::read_i8(buf).await?;
-
         let monitor_id = reader::read_i32(buf).await?;
 
         let status = reader::read_i8(buf).await?;
@@ -138,7 +164,12 @@ impl VersionedDecoder for Protocol37 {
                     let result = read_result(buf).await?;
                     LiveResult::Deleted(result)
                 }
-                _ => panic!("Event not supported"),
+                _ => {
+                    return Err(OrientError::Decoder(format!(
+                        "Unsupported live query event {}",
+                        e_type
+                    )))
+                }
             };
 
             events.push(event);
@@ -146,6 +177,36 @@ impl VersionedDecoder for Protocol37 {
 
         Ok(LiveQueryResult::new(monitor_id, status == 2, events))
     }
+    async fn decode_distributed_config<T>(buf: &mut T) -> OrientResult<DistributedConfig>
+    where
+        T: AsyncRead + Unpin + Send,
+    {
+        let size = reader::read_i32(buf).await?;
+        let mut hosts = vec![];
+        for _ in 0..size {
+            hosts.push(reader::read_string(buf).await?);
+        }
+        Ok(DistributedConfig::new(hosts))
+    }
+    async fn decode_push_event<T>(op: i8, buf: &mut T) -> OrientResult<PushEvent>
+    where
+        T: AsyncRead + Unpin + Send,
+    {
+        match op {
+            82 => Ok(PushEvent::StorageConfiguration(Box::new(
+                read_storage_configuration(buf).await?,
+            ))),
+            83 => Ok(PushEvent::Schema(P37Sync::decode_document(
+                &reader::read_bytes(buf).await?,
+            )?)),
+            84 => Ok(PushEvent::IndexManager(P37Sync::decode_document(
+                &reader::read_bytes(buf).await?,
+            )?)),
+            85 => Ok(PushEvent::Functions),
+            86 => Ok(PushEvent::Sequences),
+            _ => Err(OrientError::Decoder(format!("Unsupported push {}", op))),
+        }
+    }
     async fn decode_query<T>(buf: &mut T) -> OrientResult<Query>
     where
         T: AsyncRead + Unpin + Send,
@@ -220,6 +281,97 @@ impl VersionedDecoder for Protocol37 {
         let exist = reader::read_bool(buf).await?;
         Ok(ExistDB::new(exist))
     }
+    async fn decode_load_record<T>(buf: &mut T) -> OrientResult<LoadRecord>
+    where
+        T: AsyncRead + Unpin + Send,
+    {
+        // Nothing follows the status of a missing record
+        if reader::read_i8(buf).await? == 0 {
+            return Ok(LoadRecord::new(None));
+        }
+        let r_type = reader::read_i8(buf).await?;
+        let version = reader::read_i32(buf).await?;
+        let content = reader::read_bytes(buf).await?;
+        // records prefetched by the fetch plan are not cached, skip them
+        while reader::read_i8(buf).await? == 2 {
+            match reader::read_i16(buf).await? {
+                // null record
+                -2 => {}
+                // record id only
+                -3 => {
+                    let _identity = reader::read_identity(buf).await?;
+                }
+                _ => {
+                    let _r_type = reader::read_i8(buf).await?;
+                    let _identity = reader::read_identity(buf).await?;
+                    let _version = reader::read_i32(buf).await?;
+                    let _content = reader::read_bytes(buf).await?;
+                }
+            }
+        }
+        Ok(LoadRecord::new(Some(read_document(
+            r_type, version, &content,
+        )?)))
+    }
+    async fn decode_create_record<T>(buf: &mut T) -> OrientResult<CreateRecord>
+    where
+        T: AsyncRead + Unpin + Send,
+    {
+        let identity = reader::read_identity(buf).await?;
+        let version = reader::read_i32(buf).await?;
+        read_collection_changes(buf).await?;
+        Ok(CreateRecord::new(identity, version))
+    }
+    async fn decode_update_record<T>(buf: &mut T) -> OrientResult<UpdateRecord>
+    where
+        T: AsyncRead + Unpin + Send,
+    {
+        let version = reader::read_i32(buf).await?;
+        read_collection_changes(buf).await?;
+        Ok(UpdateRecord::new(version))
+    }
+    async fn decode_delete_record<T>(buf: &mut T) -> OrientResult<DeleteRecord>
+    where
+        T: AsyncRead + Unpin + Send,
+    {
+        let deleted = reader::read_bool(buf).await?;
+        Ok(DeleteRecord::new(deleted))
+    }
+    async fn decode_begin_transaction<T>(buf: &mut T) -> OrientResult<BeginTransaction>
+    where
+        T: AsyncRead + Unpin + Send,
+    {
+        let tx_id = reader::read_i32(buf).await?;
+        let size = reader::read_i32(buf).await?;
+        let mut updated_ids = vec![];
+        for _ in 0..size {
+            let current = reader::read_identity(buf).await?;
+            let updated = reader::read_identity(buf).await?;
+            updated_ids.push((current, updated));
+        }
+        Ok(BeginTransaction::new(tx_id, updated_ids))
+    }
+    async fn decode_commit_transaction<T>(buf: &mut T) -> OrientResult<CommitTransaction>
+    where
+        T: AsyncRead + Unpin + Send,
+    {
+        let size = reader::read_i32(buf).await?;
+        let mut created = vec![];
+        for _ in 0..size {
+            let current = reader::read_identity(buf).await?;
+            let created_rid = reader::read_identity(buf).await?;
+            created.push((current, created_rid));
+        }
+        let size = reader::read_i32(buf).await?;
+        let mut updated = vec![];
+        for _ in 0..size {
+            let rid = reader::read_identity(buf).await?;
+            let version = reader::read_i32(buf).await?;
+            updated.push((rid, version));
+        }
+        read_collection_changes(buf).await?;
+        Ok(CommitTransaction::new(created, updated))
+    }
 }
 
 #[async_trait]
@@ -252,6 +404,14 @@ pub trait VersionedDecoder {
     where
         T: AsyncRead + Unpin + Send;
 
+    async fn decode_distributed_config<T>(buf: &mut T) -> OrientResult<DistributedConfig>
+    where
+        T: AsyncRead + Unpin + Send;
+
+    async fn decode_push_event<T>(op: i8, buf: &mut T) -> OrientResult<PushEvent>
+    where
+        T: AsyncRead + Unpin + Send;
+
     async fn decode_connect<T>(buf: &mut T) -> OrientResult<Connect>
     where
         T: AsyncRead + Unpin + Send;
@@ -260,6 +420,76 @@ pub trait VersionedDecoder {
     where
         T: AsyncRead + Unpin + Send;
 
+    async fn decode_load_record<T>(buf: &mut T) -> OrientResult<LoadRecord>
+    where
+        T: AsyncRead + Unpin + Send;
+
+    async fn decode_create_record<T>(buf: &mut T) -> OrientResult<CreateRecord>
+    where
+        T: AsyncRead + Unpin + Send;
+
+    async fn decode_sbtree_first_key<T>(buf: &mut T) -> OrientResult<SBTreeFirstKey>
+    where
+        T: AsyncRead + Unpin + Send,
+    {
+        let stream = reader::read_bytes(buf).await?;
+        Ok(SBTreeFirstKey::new(decode_bag_key(&stream)?))
+    }
+
+    async fn decode_sbtree_entries_major<T>(buf: &mut T) -> OrientResult<SBTreeEntriesMajor>
+    where
+        T: AsyncRead + Unpin + Send,
+    {
+        let stream = reader::read_bytes(buf).await?;
+        Ok(SBTreeEntriesMajor::new(decode_bag_entries(&stream)?))
+    }
+
+    async fn decode_update_record<T>(buf: &mut T) -> OrientResult<UpdateRecord>
+    where
+        T: AsyncRead + Unpin + Send;
+
+    async fn decode_delete_record<T>(buf: &mut T) -> OrientResult<DeleteRecord>
+    where
+        T: AsyncRead + Unpin + Send;
+
+    async fn decode_begin_transaction<T>(buf: &mut T) -> OrientResult<BeginTransaction>
+    where
+        T: AsyncRead + Unpin + Send;
+
+    async fn decode_commit_transaction<T>(buf: &mut T) -> OrientResult<CommitTransaction>
+    where
+        T: AsyncRead + Unpin + Send;
+
+    async fn decode_rollback_transaction<T>(_buf: &mut T) -> OrientResult<RollbackTransaction>
+    where
+        T: AsyncRead + Unpin + Send,
+    {
+        Ok(RollbackTransaction {})
+    }
+
+    async fn decode_subscribe_distributed_config<T>(
+        _buf: &mut T,
+    ) -> OrientResult<SubscribeDistributedConfig>
+    where
+        T: AsyncRead + Unpin + Send,
+    {
+        Ok(SubscribeDistributedConfig {})
+    }
+
+    async fn decode_subscribe_push<T>(_buf: &mut T) -> OrientResult<SubscribePush>
+    where
+        T: AsyncRead + Unpin + Send,
+    {
+        Ok(SubscribePush {})
+    }
+
+    async fn decode_unsubscribe_live_query<T>(_buf: &mut T) -> OrientResult<UnsubscribeLiveQuery>
+    where
+        T: AsyncRead + Unpin + Send,
+    {
+        Ok(UnsubscribeLiveQuery {})
+    }
+
     async fn decode_drop_db<T>(_buf: &mut T) -> OrientResult<DropDB>
     where
         T: AsyncRead + Unpin + Send,
@@ -280,25 +510,63 @@ pub trait VersionedDecoder {
     }
 }
 
-pub async fn decode_with<T: VersionedDecoder, R>(buf: &mut R) -> OrientResult<Response>
+pub async fn decode_with<T: VersionedDecoder, R>(
+    header: Header,
+    subscription: Option<i8>,
+    buf: &mut R,
+) -> OrientResult<Response>
 where
     R: AsyncRead + Unpin + Send,
 {
-    let header = T::decode_header(buf).await?;
+    let mut counted = reader::Counted::new(buf);
+    let payload = decode_payload::<T, _>(&header, subscription, &mut counted)
+        .await
+        .map_err(|e| match e {
+            OrientError::Decoder(message) => OrientError::Decoder(format!(
+                "Error decoding response after {} bytes: {}",
+                counted.count(),
+                message
+            )),
+            e => e,
+        })?;
+    Ok(Response::new(header, payload))
+}
 
+async fn decode_payload<T: VersionedDecoder, R>(
+    header: &Header,
+    subscription: Option<i8>,
+    buf: &mut R,
+) -> OrientResult<ResponseType>
+where
+    R: AsyncRead + Unpin + Send,
+{
     let payload = match header.status {
-        Status::ERROR => return Err(OrientError::Request(T::decode_errors(buf).await?)),
+        Status::ERROR => return Err(T::decode_errors(buf).await?.into()),
         Status::OK => match header.op {
             2 => T::decode_connect(buf).await?.into(),
             3 => T::decode_open(buf).await?.into(),
             4 => T::decode_create_db(buf).await?.into(),
             6 => T::decode_exist(buf).await?.into(),
             7 => T::decode_drop_db(buf).await?.into(),
+            30 => T::decode_load_record(buf).await?.into(),
+            31 => T::decode_create_record(buf).await?.into(),
+            32 => T::decode_update_record(buf).await?.into(),
+            33 => T::decode_delete_record(buf).await?.into(),
             50 => T::decode_server_query(buf).await?.into(),
+            60 => T::decode_commit_transaction(buf).await?.into(),
+            73 => T::decode_begin_transaction(buf).await?.into(),
+            74 => T::decode_rollback_transaction(buf).await?.into(),
+            112 => T::decode_sbtree_first_key(buf).await?.into(),
+            113 => T::decode_sbtree_entries_major(buf).await?.into(),
             45 => T::decode_query(buf).await?.into(),
             46 => T::decode_query_close(buf).await?.into(),
             47 => T::decode_query(buf).await?.into(),
-            100 => T::decode_live_query(buf).await?.into(),
+            100 => match subscription {
+                Some(1) => T::decode_subscribe_distributed_config(buf).await?.into(),
+                Some(2) | None => T::decode_live_query(buf).await?.into(),
+                Some(_) => T::decode_subscribe_push(buf).await?.into(),
+            },
+            101 => T::decode_unsubscribe_live_query(buf).await?.into(),
             _ => {
                 return Err(OrientError::Protocol(format!(
                     "Request {:?} not supported",
@@ -306,9 +574,79 @@ where
                 )))
             }
         },
-        Status::PUSH => T::decode_live_query_result(buf).await?.into(),
+        Status::PUSH => match header.op {
+            80 => T::decode_distributed_config(buf).await?.into(),
+            81 => T::decode_live_query_result(buf).await?.into(),
+            82..=86 => T::decode_push_event(header.op, buf).await?.into(),
+            _ => {
+                return Err(OrientError::Protocol(format!(
+                    "Push {:?} not supported",
+                    header
+                )))
+            }
+        },
     };
-    Ok(Response::new(header, payload))
+    Ok(payload)
+}
+
+async fn read_storage_configuration<T>(buf: &mut T) -> OrientResult<StorageConfiguration>
+where
+    T: AsyncRead + Unpin + Send,
+{
+    let date_format = reader::read_string(buf).await?;
+    let date_time_format = reader::read_string(buf).await?;
+    let name = reader::read_string(buf).await?;
+    let version = reader::read_i32(buf).await?;
+    let directory = reader::read_string(buf).await?;
+    let mut properties = HashMap::new();
+    for _ in 0..reader::read_i32(buf).await? {
+        let key = reader::read_string(buf).await?;
+        let value = reader::read_string(buf).await?;
+        properties.insert(key, value);
+    }
+    let schema_record_id = reader::read_identity(buf).await?;
+    let index_manager_record_id = reader::read_identity(buf).await?;
+    let cluster_selection = reader::read_string(buf).await?;
+    let conflict_strategy = reader::read_string(buf).await?;
+    let validation_enabled = reader::read_bool(buf).await?;
+    let locale_language = reader::read_string(buf).await?;
+    let minimum_clusters = reader::read_i32(buf).await?;
+    let strict_sql = reader::read_bool(buf).await?;
+    let charset = reader::read_string(buf).await?;
+    let time_zone = reader::read_string(buf).await?;
+    let locale_country = reader::read_string(buf).await?;
+    let record_serializer = reader::read_string(buf).await?;
+    let record_serializer_version = reader::read_i32(buf).await?;
+    let binary_format_version = reader::read_i32(buf).await?;
+    let mut clusters = vec![];
+    for _ in 0..reader::read_i32(buf).await? {
+        let id = reader::read_i32(buf).await?;
+        let name = reader::read_string(buf).await?;
+        clusters.push((id, name));
+    }
+    Ok(StorageConfiguration {
+        date_format,
+        date_time_format,
+        name,
+        version,
+        directory,
+        properties,
+        schema_record_id,
+        index_manager_record_id,
+        cluster_selection,
+        conflict_strategy,
+        validation_enabled,
+        locale_language,
+        minimum_clusters,
+        strict_sql,
+        charset,
+        time_zone,
+        locale_country,
+        record_serializer,
+        record_serializer_version,
+        binary_format_version,
+        clusters,
+    })
 }
 
 async fn read_result<T>(buf: &mut T) -> OrientResult<OResult>
@@ -322,7 +660,7 @@ where
             let projection = P37Sync::decode_projection(&buffer)?;
             Ok(OResult::from(projection))
         }
-        1 | 2 | 3 => {
+        1..=3 => {
             let _val = reader::read_i16(buf).await?;
             let _d_type = reader::read_i8(buf).await?;
             let identity = reader::read_identity(buf).await?;
@@ -334,10 +672,41 @@ where
             document.set_record_id(identity);
             document.set_version(version);
 
-            Ok(OResult::from((r_type, document)))
+            OResult::try_from((r_type, document))
         }
-        _ => panic!("Unsupported result type {}", r_type),
+        _ => Err(OrientError::Decoder(format!(
+            "Unsupported result type {}",
+            r_type
+        ))),
+    }
+}
+
+fn read_document(r_type: i8, version: i32, content: &[u8]) -> OrientResult<ODocument> {
+    if r_type != b'd' as i8 {
+        return Err(OrientError::Decoder(format!(
+            "Unsupported record type {}",
+            r_type
+        )));
+    }
+    let mut document = P37Sync::decode_document(content)?;
+    document.set_version(version);
+    Ok(document)
+}
+
+// Changes to tree ridbags are not tracked on the client, just consume them
+async fn read_collection_changes<T>(buf: &mut T) -> OrientResult<()>
+where
+    T: AsyncRead + Unpin + Send,
+{
+    let size = reader::read_i32(buf).await?;
+    for _ in 0..size {
+        let _uuid_most = reader::read_i64(buf).await?;
+        let _uuid_least = reader::read_i64(buf).await?;
+        let _file_id = reader::read_i64(buf).await?;
+        let _page_index = reader::read_i64(buf).await?;
+        let _page_offset = reader::read_i32(buf).await?;
     }
+    Ok(())
 }
 
 async fn read_result_set<T>(buf: &mut T) -> OrientResult<VecDeque<OResult>>
@@ -363,3 +732,127 @@ where
     for _ in 0..size {}
     Ok(stats)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{decode, decode_header};
+    use crate::common::protocol::messages::response::{
+        DistributedConfig, LiveQuery, Status, SubscribeDistributedConfig, SubscribePush,
+    };
+    use crate::types::rid::ORecordID;
+    use crate::types::PushEvent;
+    use futures::executor::block_on;
+
+    fn put_string(buf: &mut Vec<u8>, value: &str) {
+        buf.extend_from_slice(&(value.len() as i32).to_be_bytes());
+        buf.extend_from_slice(value.as_bytes());
+    }
+
+    #[test]
+    fn decode_distributed_config_push() {
+        let mut buf = vec![3, 80];
+        buf.extend_from_slice(&2i32.to_be_bytes());
+        put_string(&mut buf, "10.0.0.1:2424");
+        put_string(&mut buf, "10.0.0.2:2425");
+        let mut input = buf.as_slice();
+
+        let mut response = block_on(async {
+            let header = decode_header(37, &mut input).await?;
+            decode(37, header, None, &mut input).await
+        })
+        .unwrap();
+
+        assert_eq!(Status::PUSH, response.header.status);
+        let config: DistributedConfig = response.payload();
+        assert_eq!(vec!["10.0.0.1:2424", "10.0.0.2:2425"], config.hosts);
+        assert!(input.is_empty());
+    }
+
+    #[test]
+    fn decode_subscribe_responses() {
+        let mut buf = vec![0];
+        buf.extend_from_slice(&1i32.to_be_bytes());
+        buf.extend_from_slice(&(-1i32).to_be_bytes());
+        buf.push(100);
+        let header = buf.clone();
+        buf.extend_from_slice(&7i32.to_be_bytes());
+
+        let mut input = header.as_slice();
+        let mut response = block_on(async {
+            let header = decode_header(37, &mut input).await?;
+            decode(37, header, Some(1), &mut input).await
+        })
+        .unwrap();
+        let _: SubscribeDistributedConfig = response.payload();
+        assert!(input.is_empty());
+
+        let mut input = buf.as_slice();
+        let mut response = block_on(async {
+            let header = decode_header(37, &mut input).await?;
+            decode(37, header, Some(2), &mut input).await
+        })
+        .unwrap();
+        let live: LiveQuery = response.payload();
+        assert_eq!(7, live.monitor_id);
+
+        let mut input = header.as_slice();
+        let mut response = block_on(async {
+            let header = decode_header(37, &mut input).await?;
+            decode(37, header, Some(4), &mut input).await
+        })
+        .unwrap();
+        let _: SubscribePush = response.payload();
+        assert!(input.is_empty());
+    }
+
+    #[test]
+    fn decode_storage_configuration_push() {
+        let mut buf = vec![3, 82];
+        put_string(&mut buf, "yyyy-MM-dd");
+        put_string(&mut buf, "yyyy-MM-dd HH:mm:ss");
+        put_string(&mut buf, "demodb");
+        buf.extend_from_slice(&23i32.to_be_bytes());
+        put_string(&mut buf, "databases/demodb");
+        buf.extend_from_slice(&1i32.to_be_bytes());
+        put_string(&mut buf, "key");
+        put_string(&mut buf, "value");
+        for position in [0i64, 1] {
+            buf.extend_from_slice(&0i16.to_be_bytes());
+            buf.extend_from_slice(&position.to_be_bytes());
+        }
+        put_string(&mut buf, "round-robin");
+        put_string(&mut buf, "version");
+        buf.push(1);
+        put_string(&mut buf, "en");
+        buf.extend_from_slice(&8i32.to_be_bytes());
+        buf.push(0);
+        put_string(&mut buf, "UTF-8");
+        put_string(&mut buf, "UTC");
+        put_string(&mut buf, "US");
+        put_string(&mut buf, "ORecordSerializerBinary");
+        buf.extend_from_slice(&0i32.to_be_bytes());
+        buf.extend_from_slice(&13i32.to_be_bytes());
+        buf.extend_from_slice(&1i32.to_be_bytes());
+        buf.extend_from_slice(&9i32.to_be_bytes());
+        put_string(&mut buf, "v");
+        let mut input = buf.as_slice();
+
+        let mut response = block_on(async {
+            let header = decode_header(37, &mut input).await?;
+            decode(37, header, None, &mut input).await
+        })
+        .unwrap();
+
+        let config = match response.payload() {
+            PushEvent::StorageConfiguration(config) => config,
+            event => panic!("unexpected push {:?}", event),
+        };
+        assert_eq!("demodb", config.name);
+        assert_eq!(Some(&String::from("value")), config.properties.get("key"));
+        assert_eq!(ORecordID::new(0, 1), config.index_manager_record_id);
+        assert!(config.validation_enabled);
+        assert!(!config.strict_sql);
+        assert_eq!(vec![(9, String::from("v"))], config.clusters);
+        assert!(input.is_empty());
+    }
+}
@@ -4,3 +4,4 @@ pub use conn::Connection;
 pub mod cluster;
 mod reader;
 mod stream;
+mod timeout;
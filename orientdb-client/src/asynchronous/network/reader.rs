@@ -1,15 +1,17 @@
 use crate::common::types::rid::ORecordID;
-use crate::OrientResult;
+use crate::{OrientError, OrientResult};
 use byteorder::{BigEndian, ReadBytesExt};
 use futures::io::{AsyncRead, AsyncReadExt};
 use std::io::Cursor;
+use std::pin::Pin;
+use std::task::{Context, Poll};
 
 pub async fn read_i8<T>(buf: &mut T) -> OrientResult<i8>
 where
     T: AsyncRead + Unpin,
 {
     let mut buffer = vec![0u8; 1];
-    buf.read(&mut buffer).await?;
+    buf.read_exact(&mut buffer).await?;
     let mut cursor = Cursor::new(buffer);
     let res = cursor.read_i8()?;
     Ok(res)
@@ -20,7 +22,7 @@ where
     T: AsyncRead + Unpin,
 {
     let mut buffer = vec![0u8; 4];
-    buf.read(&mut buffer).await?;
+    buf.read_exact(&mut buffer).await?;
     let mut cursor = Cursor::new(buffer);
     let res = cursor.read_i32::<BigEndian>()?;
     Ok(res)
@@ -31,7 +33,7 @@ where
     T: AsyncRead + Unpin,
 {
     let mut buffer = vec![0u8; 8];
-    buf.read(&mut buffer).await?;
+    buf.read_exact(&mut buffer).await?;
     let mut cursor = Cursor::new(buffer);
     let res = cursor.read_i64::<BigEndian>()?;
     Ok(res)
@@ -51,7 +53,7 @@ where
     T: AsyncRead + Unpin,
 {
     let mut buffer = vec![0u8; 2];
-    buf.read(&mut buffer).await?;
+    buf.read_exact(&mut buffer).await?;
     let mut cursor = Cursor::new(buffer);
     let res = cursor.read_i16::<BigEndian>()?;
     Ok(res)
@@ -65,7 +67,12 @@ where
     let val = match e {
         0 => false,
         1 => true,
-        _ => panic!("Cannot convert value to bool"),
+        _ => {
+            return Err(OrientError::Decoder(format!(
+                "Cannot convert value {} to bool",
+                e
+            )))
+        }
     };
     Ok(val)
 }
@@ -80,7 +87,7 @@ where
         return Ok(None);
     } else {
         buff = vec![0; size as usize];
-        buf.read(&mut buff).await?;
+        buf.read_exact(&mut buff).await?;
     }
     Ok(Some(buff))
 }
@@ -95,7 +102,7 @@ where
         buff = vec![];
     } else {
         buff = vec![0; size as usize];
-        buf.read(&mut buff).await?;
+        buf.read_exact(&mut buff).await?;
     }
     Ok(buff)
 }
@@ -108,3 +115,33 @@ where
     let res = String::from_utf8(bytes)?;
     Ok(res)
 }
+
+/// Counts the bytes read, to tell where a response failed to decode
+pub struct Counted<'a, R> {
+    inner: &'a mut R,
+    count: u64,
+}
+
+impl<'a, R: AsyncRead + Unpin> Counted<'a, R> {
+    pub fn new(inner: &'a mut R) -> Counted<'a, R> {
+        Counted { inner, count: 0 }
+    }
+
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+}
+
+impl<R: AsyncRead + Unpin> AsyncRead for Counted<'_, R> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<std::io::Result<usize>> {
+        let poll = Pin::new(&mut *self.inner).poll_read(cx, buf);
+        if let Poll::Ready(Ok(read)) = poll {
+            self.count += read as u64;
+        }
+        poll
+    }
+}
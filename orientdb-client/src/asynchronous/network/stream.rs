@@ -10,10 +10,7 @@ use crate::OrientResult;
 
 #[cfg(feature = "async-std-runtime")]
 mod async_std_use {
-    pub use async_std::io::BufReader;
     pub use async_std::net::TcpStream;
-    pub use async_std::sync::Mutex;
-    pub use async_std::task;
     pub use std::sync::Arc;
 }
 #[cfg(feature = "async-std-runtime")]
@@ -21,10 +18,8 @@ use async_std_use::*;
 
 #[cfg(feature = "tokio-runtime")]
 mod tokio_use {
-    pub use tokio::io::{AsyncRead, AsyncWrite, AsyncWriteExt, BufReader, ReadHalf, WriteHalf};
+    pub use tokio::io::{AsyncRead, AsyncWrite, ReadHalf, WriteHalf};
     pub use tokio::net::TcpStream;
-    pub use tokio::sync::Mutex;
-    pub use tokio::task;
 }
 
 #[cfg(feature = "tokio-runtime")]
@@ -142,6 +137,136 @@ impl ShutdownStream for StreamWriter {
     }
 }
 
+// TLS impl, the handshake runs on the whole stream before it is split
+
+#[cfg(feature = "tls")]
+mod tls_use {
+    pub(crate) use crate::common::tls::TlsConnector;
+    pub use futures::io::AsyncReadExt;
+    pub use futures_rustls::client::TlsStream;
+    pub use rustls::pki_types::ServerName;
+}
+#[cfg(feature = "tls")]
+use tls_use::*;
+
+#[cfg(all(feature = "tls", feature = "async-std-runtime"))]
+type Socket = TcpStream;
+
+#[cfg(all(feature = "tls", feature = "tokio-runtime"))]
+type Socket = TokioSocket;
+
+#[cfg(feature = "tls")]
+pub(crate) type TlsReader = io::ReadHalf<TlsStream<Socket>>;
+
+#[cfg(feature = "tls")]
+pub(crate) struct TlsWriter {
+    inner: io::WriteHalf<TlsStream<Socket>>,
+    // Shut down directly, the session may be held by the reader
+    #[cfg(feature = "async-std-runtime")]
+    socket: TcpStream,
+}
+
+#[cfg(feature = "tls")]
+pub(crate) async fn split_tls(
+    stream: TcpStream,
+    connector: &TlsConnector,
+    name: ServerName<'static>,
+) -> OrientResult<(TlsReader, TlsWriter)> {
+    #[cfg(feature = "async-std-runtime")]
+    let socket = stream.clone();
+    #[cfg(feature = "tokio-runtime")]
+    let stream = TokioSocket(stream);
+
+    let stream = futures_rustls::TlsConnector::from(connector.config.clone())
+        .connect(name, stream)
+        .await?;
+    let (reader, inner) = stream.split();
+
+    Ok((
+        reader,
+        TlsWriter {
+            inner,
+            #[cfg(feature = "async-std-runtime")]
+            socket,
+        },
+    ))
+}
+
+#[cfg(feature = "tls")]
+impl io::AsyncWrite for TlsWriter {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.inner).poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_close(cx)
+    }
+}
+
+#[cfg(all(feature = "tls", feature = "async-std-runtime"))]
+impl ShutdownStream for TlsWriter {
+    fn shutdown(&self, mode: Shutdown) -> OrientResult<()> {
+        self.socket.shutdown(mode)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(all(feature = "tls", feature = "tokio-runtime"))]
+impl ShutdownStream for TlsWriter {
+    fn shutdown(&self, _mode: Shutdown) -> OrientResult<()> {
+        Ok(())
+    }
+}
+
+// The tokio socket seen through the futures io traits used by futures-rustls
+#[cfg(all(feature = "tls", feature = "tokio-runtime"))]
+pub(crate) struct TokioSocket(TcpStream);
+
+#[cfg(all(feature = "tls", feature = "tokio-runtime"))]
+impl io::AsyncRead for TokioSocket {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        use futures::ready;
+        use tokio::io::ReadBuf;
+        let mut read_buf = ReadBuf::new(buf);
+
+        ready!(Pin::new(&mut self.0).poll_read(cx, &mut read_buf))?;
+
+        Poll::Ready(Ok(read_buf.filled().len()))
+    }
+}
+
+#[cfg(all(feature = "tls", feature = "tokio-runtime"))]
+impl io::AsyncWrite for TokioSocket {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.0).poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.0).poll_flush(cx)
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.0).poll_shutdown(cx)
+    }
+}
+
 pub(crate) trait ShutdownStream {
     fn shutdown(&self, mode: Shutdown) -> OrientResult<()>;
 }
@@ -0,0 +1,26 @@
+use crate::{OrientError, OrientResult};
+use std::future::Future;
+use std::time::Duration;
+
+/// Wait for `future` at most `limit`, `what` tells what timed out in the error
+pub(crate) async fn timeout<F>(
+    limit: Option<Duration>,
+    what: &str,
+    future: F,
+) -> OrientResult<F::Output>
+where
+    F: Future,
+{
+    let limit = match limit {
+        Some(limit) => limit,
+        None => return Ok(future.await),
+    };
+
+    #[cfg(feature = "async-std-runtime")]
+    let output = async_std::future::timeout(limit, future).await.ok();
+
+    #[cfg(feature = "tokio-runtime")]
+    let output = tokio::time::timeout(limit, future).await.ok();
+
+    output.ok_or_else(|| OrientError::timeout(what, limit))
+}
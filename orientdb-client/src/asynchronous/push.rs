@@ -0,0 +1,35 @@
+use super::network::conn::Connection;
+
+use crate::types::PushEvent;
+use crate::OrientResult;
+use futures::channel::mpsc::UnboundedReceiver;
+use futures::stream::Stream;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// Changes pushed by the server after a subscription. Dropping the stream
+/// closes its connection, which ends the subscription.
+pub struct PushStream {
+    receiver: UnboundedReceiver<OrientResult<PushEvent>>,
+    _conn: Connection,
+}
+
+impl PushStream {
+    pub(crate) fn new(
+        receiver: UnboundedReceiver<OrientResult<PushEvent>>,
+        conn: Connection,
+    ) -> Self {
+        PushStream {
+            receiver,
+            _conn: conn,
+        }
+    }
+}
+
+impl Stream for PushStream {
+    type Item = OrientResult<PushEvent>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Pin::new(&mut self.receiver).poll_next(cx)
+    }
+}
@@ -7,6 +7,7 @@ use crate::types::result::FromResult;
 use crate::OrientResult;
 use futures::Stream;
 use std::collections::HashMap;
+use std::time::Duration;
 
 #[cfg(feature = "sugar")]
 use futures::StreamExt;
@@ -21,6 +22,7 @@ pub struct ServerStatement<'a> {
     page_size: i32,
     mode: i8,
     named: bool,
+    pub(crate) timeout: Option<Duration>,
 }
 
 impl<'a> ServerStatement<'a> {
@@ -40,6 +42,7 @@ impl<'a> ServerStatement<'a> {
             mode: 1,
             language: String::from("sql"),
             page_size: 150,
+            timeout: None,
         }
     }
 
@@ -55,15 +58,27 @@ impl<'a> ServerStatement<'a> {
     pub fn named(mut self, params: &[(&str, &dyn IntoOValue)]) -> Self {
         self.params = params
             .iter()
-            .map(|&(k, ref v)| (String::from(k), v.into_ovalue()))
+            .map(|(k, v)| (String::from(*k), v.into_ovalue()))
             .collect();
 
         self.named = true;
         self
     }
 
+    pub fn page_size(mut self, page_size: i32) -> Self {
+        self.page_size = page_size;
+        self
+    }
+
+    /// Wait at most `timeout` for each response of the statement,
+    /// instead of the request timeout of the connection options
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
     pub async fn run(self) -> OrientResult<impl Stream<Item = OrientResult<OResult>>> {
-        self.client.run(self.into()).await
+        self.client.run(self).await
     }
 
     #[cfg(feature = "sugar")]
@@ -73,7 +88,7 @@ impl<'a> ServerStatement<'a> {
     {
         let mut stream = self
             .client
-            .run(self.into())
+            .run(self)
             .await?
             .map(|r| r.and_then(T::from_result));
 
@@ -90,7 +105,7 @@ impl<'a> ServerStatement<'a> {
     {
         let mut stream = self
             .client
-            .run(self.into())
+            .run(self)
             .await?
             .map(|r| r.and_then(T::from_result));
 
@@ -109,7 +124,7 @@ impl<'a> ServerStatement<'a> {
     {
         Ok(self
             .client
-            .run(self.into())
+            .run(self)
             .await?
             .map(|r| r.and_then(T::from_result)))
     }
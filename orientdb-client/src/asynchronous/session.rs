@@ -3,22 +3,37 @@ use super::network::cluster::{Cluster, Server};
 use super::client::OrientDBClientInternal;
 use super::live_statement::LiveStatement;
 use super::statement::Statement;
-use crate::common::protocol::messages::request::{Close, LiveQuery, Query};
+use crate::common::protocol::messages::request::Open;
+use crate::common::protocol::messages::request::{
+    BeginTransaction, Close, CommitTransaction, CreateRecord, DeleteRecord, LiveQuery, LoadRecord,
+    Query, RecordOperation, RollbackTransaction, SBTreeEntriesMajor, SBTreeFirstKey, SubscribePush,
+    UpdateRecord,
+};
 use crate::common::protocol::messages::response;
+use crate::common::protocol::messages::Response;
+use crate::common::session::{Credentials, Reopen, SessionCredentials};
+use crate::common::types::bag::{BagFetch, BagPage, BagSession, RidBag, BAG_PAGE_SIZE};
+use crate::common::types::rid::ORecordID;
 use crate::{OrientError, OrientResult};
 use mobc::{async_trait, Connection, Manager, Pool};
+use std::collections::HashMap;
 use std::convert::From;
 use std::future::Future;
 use std::sync::Arc;
+use std::time::Duration;
 
-use super::live::Unsubscriber;
+use super::live::{self, LiveStream, Subscription, Unsubscriber};
+use super::push::PushStream;
+use super::transaction::OTransaction;
+use super::types::bag::BagStream;
 use super::types::resultset::PagedResultSet;
-use crate::common::types::OResult;
-use crate::types::LiveResult;
+use crate::common::live::{LiveQueue, DEFAULT_BUFFER};
+use crate::common::types::transaction;
+use crate::common::types::{IntoDocument, ODocument, OResult, PushKind, TransactionResult};
+use crate::types::OverflowPolicy;
+use futures::channel::mpsc::unbounded;
 use futures::Stream;
 
-use futures::channel::mpsc::channel;
-
 #[derive(Debug)]
 pub struct OSessionRetry<'session>(&'session OSession);
 
@@ -27,21 +42,21 @@ impl<'session> OSessionRetry<'session> {
         OSessionRetry(session)
     }
 
-    pub fn query<QUERY: Into<String>>(&self, query: QUERY) -> Statement {
+    pub fn query<QUERY: Into<String>>(&self, query: QUERY) -> Statement<'_> {
         Statement::new(self.0, query.into())
     }
 
-    pub fn command<COMMAND: Into<String>>(&self, command: COMMAND) -> Statement {
+    pub fn command<COMMAND: Into<String>>(&self, command: COMMAND) -> Statement<'_> {
         Statement::new(self.0, command.into()).mode(0)
     }
 
-    pub fn script_sql<SCRIPT: Into<String>>(&self, script: SCRIPT) -> Statement {
+    pub fn script_sql<SCRIPT: Into<String>>(&self, script: SCRIPT) -> Statement<'_> {
         Statement::new(self.0, script.into())
             .mode(2)
             .language(String::from("SQL"))
     }
 
-    pub fn script<SCRIPT, LANGUAGE>(&self, script: SCRIPT, language: LANGUAGE) -> Statement
+    pub fn script<SCRIPT, LANGUAGE>(&self, script: SCRIPT, language: LANGUAGE) -> Statement<'_>
     where
         SCRIPT: Into<String>,
         LANGUAGE: Into<String>,
@@ -50,16 +65,21 @@ impl<'session> OSessionRetry<'session> {
             .mode(2)
             .language(language.into())
     }
+
+    pub fn begin(&self) -> OTransaction<'session> {
+        OTransaction::new(self.0)
+    }
+
+    pub async fn load(&self, rid: ORecordID) -> OrientResult<Option<ODocument>> {
+        self.0.load(rid).await
+    }
 }
 
 #[derive(Debug)]
 pub struct OSession {
     pub client_id: i32,
-    pub session_id: i32,
-    pub token: Option<Vec<u8>>,
-    // Unused for now. After can be used to switch server in case of failure
-    #[allow(unused_variables)]
-    #[allow(dead_code)]
+    credentials: Arc<SessionCredentials>,
+    reopen: Reopen,
     cluster: Arc<Cluster>,
     server: Arc<Server>,
     pooled: bool,
@@ -68,36 +88,74 @@ pub struct OSession {
 impl OSession {
     pub(crate) fn new(
         client_id: i32,
-        session_id: i32,
-        token: Option<Vec<u8>>,
+        credentials: Credentials,
+        reopen: Reopen,
         cluster: Arc<Cluster>,
         server: Arc<Server>,
         pooled: bool,
     ) -> OSession {
         OSession {
             client_id,
-            session_id,
-            token,
+            credentials: Arc::new(SessionCredentials::new(credentials)),
+            reopen,
             cluster,
             server,
             pooled,
         }
     }
 
-    pub fn query<T: Into<String>>(&self, query: T) -> Statement {
+    /// The id of the session, it changes when the session is opened again
+    pub fn session_id(&self) -> i32 {
+        self.credentials.get().session_id
+    }
+
+    /// The token of the session, it changes when the session is opened again
+    pub fn token(&self) -> Option<Vec<u8>> {
+        self.credentials.get().token
+    }
+
+    /// Run `f` with the credentials of the session. When the server reports that the
+    /// session expired, the session is opened again and `f` runs once more
+    async fn with_credentials<T, F, R>(&self, f: F) -> OrientResult<T>
+    where
+        F: Fn(Credentials) -> R,
+        R: Future<Output = OrientResult<T>>,
+    {
+        let credentials = self.credentials.get();
+        match f(credentials.clone()).await {
+            Err(e) if self.reopen.allows(&e) => f(self.reopen(&credentials).await?).await,
+            result => result,
+        }
+    }
+
+    // Adopt the token renewed by the server in the response to a request sent as `session_id`
+    fn renewed(&self, session_id: i32, response: Response) -> Response {
+        self.credentials.renew(session_id, &response.header);
+        response
+    }
+
+    async fn reopen(&self, expired: &Credentials) -> OrientResult<Credentials> {
+        reopen(&self.server, &self.reopen, &self.credentials, expired).await
+    }
+
+    pub fn query<T: Into<String>>(&self, query: T) -> Statement<'_> {
         Statement::new(self, query.into())
     }
 
-    pub fn command<T: Into<String>>(&self, command: T) -> Statement {
+    pub fn command<T: Into<String>>(&self, command: T) -> Statement<'_> {
         Statement::new(self, command.into()).mode(0)
     }
 
-    pub fn script_sql<T: Into<String>>(&self, script: T) -> Statement {
+    pub fn script_sql<T: Into<String>>(&self, script: T) -> Statement<'_> {
         Statement::new(self, script.into())
             .mode(2)
             .language(String::from("SQL"))
     }
-    pub fn script<T: Into<String>, S: Into<String>>(&self, script: T, language: S) -> Statement {
+    pub fn script<T: Into<String>, S: Into<String>>(
+        &self,
+        script: T,
+        language: S,
+    ) -> Statement<'_> {
         Statement::new(self, script.into())
             .mode(2)
             .language(language.into())
@@ -114,9 +172,16 @@ impl OSession {
     {
         self.with_retry(retry, |s| async {
             let _ = self.command("begin").run().await?;
-            let results = f(s).await;
-            let _ = self.command("commit");
-            results
+            match f(s).await {
+                Ok(result) => {
+                    let _ = self.command("commit").run().await?;
+                    Ok(result)
+                }
+                Err(e) => {
+                    let _ = self.command("rollback").run().await;
+                    Err(e)
+                }
+            }
         })
         .await
     }
@@ -136,63 +201,268 @@ impl OSession {
             let retry_session = OSessionRetry::new(self);
             let result: OrientResult<T> = f(retry_session).await;
             match result {
-                Ok(t) => return Ok(t),
-                Err(e) => match &e {
-                    OrientError::Request(r) => {
-                        if n > 0 && r.code == 3 {
-                            n -= 1;
-                        } else {
-                            return Err(e);
-                        };
-                        continue;
-                    }
-                    _ => return Err(e),
-                },
+                Err(OrientError::ConcurrentModification(_)) if n > 0 => n -= 1,
+                result => return result,
+            }
+        }
+    }
+
+    /// Begin a client side transaction
+    pub fn begin(&self) -> OTransaction<'_> {
+        OTransaction::new(self)
+    }
+
+    pub(crate) async fn commit_transaction(
+        &self,
+        operations: Vec<RecordOperation>,
+    ) -> OrientResult<TransactionResult> {
+        if operations.is_empty() {
+            return Ok(TransactionResult::default());
+        }
+        self.with_credentials(|credentials| self.commit_with(operations.clone(), credentials))
+            .await
+    }
+
+    async fn commit_with(
+        &self,
+        operations: Vec<RecordOperation>,
+        credentials: Credentials,
+    ) -> OrientResult<TransactionResult> {
+        let Credentials { session_id, token } = credentials;
+        let tx_id = transaction::next_tx_id();
+        let mut conn = self.server.connection().await?;
+        let begin = BeginTransaction::new(session_id, token, tx_id, operations);
+        let result = match conn.send(begin.into()).await {
+            Ok(reply) => {
+                let begin: response::BeginTransaction = self.renewed(session_id, reply).payload();
+                // The reply may have renewed the token, the next requests send the current one
+                let Credentials { session_id, token } = self.credentials.get();
+                conn.send(CommitTransaction::new(session_id, token, tx_id).into())
+                    .await
+                    .map(|commit| {
+                        let commit = self.renewed(session_id, commit).payload();
+                        TransactionResult::new(begin, commit)
+                    })
             }
+            Err(e) => Err(e),
+        };
+        if result.is_err() {
+            let Credentials { session_id, token } = self.credentials.get();
+            let rollback = RollbackTransaction::new(session_id, token, tx_id);
+            let _ = conn.send(rollback.into()).await;
         }
+        result
     }
 
     pub fn live_query<'a, T: Into<String>>(&'a self, query: T) -> LiveStatement<'a> {
         LiveStatement::new(self, query.into())
     }
 
+    // Every live query runs on its own connection
     pub(crate) async fn live_run(
         &self,
         live_query: LiveQuery,
-    ) -> OrientResult<(Unsubscriber, impl Stream<Item = OrientResult<LiveResult>>)> {
-        let mut conn = self.server.connection().await?;
+        queue: LiveQueue,
+        resilient: bool,
+    ) -> OrientResult<(Unsubscriber, LiveStream)> {
+        let queue = Arc::new(queue);
+        // The subscription reads the credentials of the session on every (re)subscribe
+        // and unsubscribe, and opens the session again when it expired
+        let subscription = Arc::new(Subscription::new(
+            live_query,
+            self.server.clone(),
+            self.credentials.clone(),
+            self.reopen.clone(),
+        ));
+
+        if resilient {
+            let inner = Arc::new(LiveQueue::new(DEFAULT_BUFFER, OverflowPolicy::Block));
+            subscription.subscribe(inner.clone()).await?;
+            live::resubscribe(subscription.clone(), inner, queue.clone());
+        } else {
+            subscription.subscribe(queue.clone()).await?;
+        }
+
+        Ok((
+            Unsubscriber::new(subscription.clone()),
+            LiveStream::new(queue, subscription),
+        ))
+    }
+    /// Subscribe to the changes of `kind`, pushed by the server on a dedicated connection
+    pub async fn subscribe(&self, kind: PushKind) -> OrientResult<PushStream> {
+        self.with_credentials(|Credentials { session_id, token }| async move {
+            let mut conn = self.server.dedicated_connection().await?;
+            let (sender, receiver) = unbounded();
+            conn.register_push_handler(sender).await;
+            let subscribe = SubscribePush::new(session_id, token, kind);
+            let response = conn.send(subscribe.into()).await?;
+            let _: response::SubscribePush = self.renewed(session_id, response).payload();
+            Ok(PushStream::new(receiver, conn))
+        })
+        .await
+    }
+
+    /// Load a record by id, returns `None` if the record does not exist
+    pub async fn load(&self, rid: ORecordID) -> OrientResult<Option<ODocument>> {
+        let rid_ref = &rid;
+        let response: response::LoadRecord = self
+            .with_credentials(|Credentials { session_id, token }| async move {
+                let load = LoadRecord::new(session_id, token, rid_ref.clone());
+                let (_, response) = self.cluster.send(&self.server, load.into()).await?;
+                Ok(self.renewed(session_id, response).payload())
+            })
+            .await?;
+        Ok(response.record.map(|mut doc| {
+            doc.set_record_id(rid);
+            doc
+        }))
+    }
 
-        let (sender, receiver) = channel(10);
+    pub(crate) async fn fetch_bag(&self, fetch: BagFetch) -> OrientResult<BagPage> {
+        self.with_credentials(|credentials| self.fetch_bag_with(fetch.clone(), credentials))
+            .await
+    }
 
-        let q: response::LiveQuery = conn.send(live_query.into()).await?.payload();
+    async fn fetch_bag_with(
+        &self,
+        fetch: BagFetch,
+        credentials: Credentials,
+    ) -> OrientResult<BagPage> {
+        let Credentials { session_id, token } = credentials;
+        match fetch {
+            BagFetch::FirstKey(pointer) => {
+                let first_key = SBTreeFirstKey::new(session_id, token, pointer);
+                let (_, response) = self.cluster.send(&self.server, first_key.into()).await?;
+                let response: response::SBTreeFirstKey =
+                    self.renewed(session_id, response).payload();
+                Ok(BagPage::FirstKey(response.key))
+            }
+            BagFetch::EntriesMajor(pointer, key, inclusive) => {
+                let entries = SBTreeEntriesMajor::new(
+                    session_id,
+                    token,
+                    pointer,
+                    key,
+                    inclusive,
+                    BAG_PAGE_SIZE,
+                );
+                let (_, response) = self.cluster.send(&self.server, entries.into()).await?;
+                let response: response::SBTreeEntriesMajor =
+                    self.renewed(session_id, response).payload();
+                Ok(BagPage::Entries(response.entries))
+            }
+        }
+    }
+
+    /// Save a document, creating a new record if the document was never stored.
+    /// Returns the record id and the version of the stored record
+    pub async fn save<T>(&self, doc: &T) -> OrientResult<(ORecordID, i32)>
+    where
+        T: IntoDocument + ?Sized,
+    {
+        self.save_document(&mut doc.into_document()).await
+    }
 
-        conn.register_handler(q.monitor_id, sender).await?;
+    /// Save a document like [`save`](OSession::save),
+    /// the record id and the version of the stored record are also set on the document
+    pub async fn save_document(&self, doc: &mut ODocument) -> OrientResult<(ORecordID, i32)> {
+        if !doc.record_id().is_new() {
+            return self.update(doc).await;
+        }
+        let stored = &*doc;
+        let response: response::CreateRecord = self
+            .with_credentials(|Credentials { session_id, token }| async move {
+                let create = CreateRecord::new(session_id, token, stored.clone());
+                let mut conn = self.server.connection().await?;
+                let response = conn.send(create.into()).await?;
+                Ok(self.renewed(session_id, response).payload())
+            })
+            .await?;
+        doc.set_record_id(response.rid.clone());
+        doc.set_version(response.version);
+        Ok((response.rid, response.version))
+    }
 
-        let unsubscriber = Unsubscriber::new(
-            q.monitor_id,
-            self.session_id,
-            self.token.clone(),
-            self.server.clone(),
-        );
+    /// Update the record of a stored document.
+    /// The new version of the record is set on the document and returned with the record id
+    pub async fn update(&self, doc: &mut ODocument) -> OrientResult<(ORecordID, i32)> {
+        let stored = &*doc;
+        let response: response::UpdateRecord = self
+            .with_credentials(|Credentials { session_id, token }| async move {
+                let update = UpdateRecord::new(session_id, token, stored.clone());
+                let mut conn = self.server.connection().await?;
+                let response = conn.send(update.into()).await?;
+                Ok(self.renewed(session_id, response).payload())
+            })
+            .await?;
+        doc.set_version(response.version);
+        Ok((doc.record_id().clone(), response.version))
+    }
 
-        Ok((unsubscriber, receiver))
+    /// Delete a record with the given version, returns `false` if nothing was deleted
+    pub async fn delete(&self, rid: ORecordID, version: i32) -> OrientResult<bool> {
+        let rid = &rid;
+        let response: response::DeleteRecord = self
+            .with_credentials(|Credentials { session_id, token }| async move {
+                let delete = DeleteRecord::new(session_id, token, rid.clone(), version);
+                let mut conn = self.server.connection().await?;
+                let response = conn.send(delete.into()).await?;
+                Ok(self.renewed(session_id, response).payload())
+            })
+            .await?;
+        Ok(response.deleted)
     }
+
     pub(crate) async fn run(
         &self,
         query: Query,
+        timeout: Option<Duration>,
     ) -> OrientResult<impl Stream<Item = OrientResult<OResult>>> {
-        let mut conn = self.server.connection().await?;
         let page_size = query.page_size;
-        let q: response::Query = conn.send(query.into()).await?.payload();
+        let query = &query;
+        self.with_credentials(|Credentials { session_id, token }| async move {
+            let mut query = query.clone();
+            query.session_id = session_id;
+            query.token = token;
+            // Next pages must be fetched from the server that runs the query
+            let (server, response) = self
+                .cluster
+                .send_with_timeout(&self.server, query.into(), timeout)
+                .await?;
+            let q: response::Query = self.renewed(session_id, response).payload();
+
+            Ok(PagedResultSet::new(
+                server,
+                q,
+                self.credentials.clone(),
+                page_size,
+                timeout,
+            ))
+        })
+        .await
+    }
 
-        Ok(PagedResultSet::new(
-            self.server.clone(),
-            q,
-            self.session_id,
-            self.token.clone(),
-            page_size,
-        ))
+    /// Check that the server still knows the session, with a query that reads nothing
+    pub(crate) async fn ping(&self) -> OrientResult<()> {
+        self.with_credentials(|Credentials { session_id, token }| async move {
+            let query = Query::new(
+                session_id,
+                token,
+                "SELECT 1",
+                HashMap::new(),
+                false,
+                "sql",
+                1,
+                10,
+            );
+            let mut conn = self.server.connection().await?;
+            let response = conn.send(query.into()).await?;
+            let _: response::Query = self.renewed(session_id, response).payload();
+            Ok(())
+        })
+        .await
     }
+
     /// Close a session
     pub async fn close(self) -> OrientResult<()> {
         if !self.pooled {
@@ -201,29 +471,60 @@ impl OSession {
         Ok(())
     }
 
-    async fn force_close(mut self) -> OrientResult<()> {
+    async fn force_close(self) -> OrientResult<()> {
         let mut conn = self.server.connection().await?;
-        self.session_id = -1;
-        self.token = None;
-        conn.send_and_forget(Close::new(self.session_id, self.token).into())
+        self.credentials.set(Credentials::new(-1, None));
+        let Credentials { session_id, token } = self.credentials.get();
+        conn.send_and_forget(Close::new(session_id, token).into())
             .await?;
         Ok(())
     }
 }
 
+// Open the session of `credentials` again, unless another task already did it
+pub(crate) async fn reopen(
+    server: &Server,
+    reopen: &Reopen,
+    credentials: &SessionCredentials,
+    expired: &Credentials,
+) -> OrientResult<Credentials> {
+    let current = credentials.get();
+    if current != *expired {
+        return Ok(current);
+    }
+    let open = Open::new(&reopen.db, &reopen.user, &reopen.password);
+    let response: response::Open = server
+        .connection()
+        .await?
+        .send(open.into())
+        .await?
+        .payload();
+    let fresh = Credentials::new(response.session_id, response.token);
+    Ok(credentials.replace(expired, fresh))
+}
+
+impl<'session> BagSession<'session> for OSession {
+    type Iter = BagStream<'session>;
+
+    fn bag_iter(&'session self, bag: &RidBag) -> BagStream<'session> {
+        BagStream::new(self, bag)
+    }
+}
+
+// Idle time after which a pooled session is pinged before being checked out
+const SESSION_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
 #[derive(Debug)]
 pub struct SessionPoolManager {
     db: String,
     user: String,
     password: String,
-    server: Arc<Server>,
     client: OrientDBClientInternal,
 }
 
 impl SessionPoolManager {
     pub(crate) fn new(
         client: OrientDBClientInternal,
-        server: Arc<Server>,
         db_name: &str,
         user: &str,
         password: &str,
@@ -232,7 +533,6 @@ impl SessionPoolManager {
             db: String::from(db_name),
             user: String::from(user),
             password: String::from(password),
-            server,
             client,
         }
     }
@@ -243,9 +543,12 @@ impl SessionPoolManager {
         max_size: Option<u32>,
     ) -> OrientResult<SessionPool> {
         let size = max_size.unwrap_or(20);
+        // Only the sessions idle for a while are pinged on checkout, an expired
+        // session is opened again by its next request anyway
         let pool = Pool::builder()
             .max_open(size as u64)
             .max_idle(size as u64)
+            .health_check_interval(Some(SESSION_CHECK_INTERVAL))
             .build(self);
 
         Ok(SessionPool(pool))
@@ -259,19 +562,18 @@ impl Manager for SessionPoolManager {
 
     async fn connect(&self) -> Result<Self::Connection, Self::Error> {
         self.client
-            ._server_session(
-                self.server.clone(),
-                &self.db,
-                &self.user,
-                &self.password,
-                true,
-            )
+            ._session(&self.db, &self.user, &self.password, true)
             .await
     }
 
     async fn check(&self, conn: Self::Connection) -> Result<Self::Connection, Self::Error> {
+        conn.ping().await?;
         Ok(conn)
     }
+
+    fn validate(&self, conn: &mut Self::Connection) -> bool {
+        conn.server.is_healthy()
+    }
 }
 
 #[derive(Clone)]
@@ -306,7 +608,9 @@ impl From<mobc::Error<OrientError>> for OrientError {
         match e {
             mobc::Error::Inner(e) => e,
             mobc::Error::BadConn => OrientError::Generic(String::from("Async pool bad connection")),
-            mobc::Error::Timeout => OrientError::Generic(String::from("Async pool timeout")),
+            mobc::Error::Timeout => {
+                OrientError::Timeout(String::from("waiting for a pooled connection"))
+            }
         }
     }
 }
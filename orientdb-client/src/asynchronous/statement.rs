@@ -1,5 +1,6 @@
 use super::session::OSession;
 use crate::common::protocol::messages::request::Query;
+use crate::common::types::document::IntoDocument;
 use crate::common::types::value::{IntoOValue, OValue};
 use crate::common::types::OResult;
 #[cfg(feature = "sugar")]
@@ -7,6 +8,7 @@ use crate::types::result::FromResult;
 use crate::OrientResult;
 use futures::Stream;
 use std::collections::HashMap;
+use std::time::Duration;
 
 #[cfg(feature = "sugar")]
 use futures::StreamExt;
@@ -19,6 +21,7 @@ pub struct Statement<'a> {
     page_size: i32,
     mode: i8,
     named: bool,
+    timeout: Option<Duration>,
 }
 
 impl<'a> Statement<'a> {
@@ -31,6 +34,7 @@ impl<'a> Statement<'a> {
             mode: 1,
             language: String::from("sql"),
             page_size: 150,
+            timeout: None,
         }
     }
     pub(crate) fn mode(mut self, mode: i8) -> Self {
@@ -52,10 +56,16 @@ impl<'a> Statement<'a> {
         self.named = false;
         self
     }
-    pub fn named(mut self, params: &[(&str, &dyn IntoOValue)]) -> Self {
+    /// Bind named parameters, given as pairs of name and value
+    /// or as the fields of a document
+    pub fn named<T>(mut self, params: &T) -> Self
+    where
+        T: IntoDocument + ?Sized,
+    {
         self.params = params
+            .into_document()
             .iter()
-            .map(|&(k, ref v)| (String::from(k), v.into_ovalue()))
+            .map(|(k, v)| (k.clone(), v.clone()))
             .collect();
 
         self.named = true;
@@ -66,8 +76,17 @@ impl<'a> Statement<'a> {
         self.page_size = page_size;
         self
     }
+
+    /// Wait at most `timeout` for each response of the statement,
+    /// instead of the request timeout of the connection options
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
     pub async fn run(self) -> OrientResult<impl Stream<Item = OrientResult<OResult>>> {
-        self.session.run(self.into()).await
+        let session = self.session;
+        let timeout = self.timeout;
+        session.run(self.into(), timeout).await
     }
 
     #[cfg(feature = "sugar")]
@@ -75,11 +94,7 @@ impl<'a> Statement<'a> {
     where
         T: FromResult,
     {
-        let mut stream = self
-            .session
-            .run(self.into())
-            .await?
-            .map(|r| r.and_then(T::from_result));
+        let mut stream = self.run().await?.map(|r| r.and_then(T::from_result));
 
         match stream.next().await {
             Some(r) => Ok(Some(r?)),
@@ -92,11 +107,7 @@ impl<'a> Statement<'a> {
     where
         T: FromResult,
     {
-        let mut stream = self
-            .session
-            .run(self.into())
-            .await?
-            .map(|r| r.and_then(T::from_result));
+        let mut stream = self.run().await?.map(|r| r.and_then(T::from_result));
 
         let mut results = Vec::new();
 
@@ -111,19 +122,15 @@ impl<'a> Statement<'a> {
     where
         T: FromResult,
     {
-        Ok(self
-            .session
-            .run(self.into())
-            .await?
-            .map(|r| r.and_then(T::from_result)))
+        Ok(self.run().await?.map(|r| r.and_then(T::from_result)))
     }
 }
 
 impl<'a> From<Statement<'a>> for Query {
     fn from(x: Statement) -> Query {
         Query {
-            session_id: x.session.session_id,
-            token: x.session.token.clone(),
+            session_id: x.session.session_id(),
+            token: x.session.token(),
             query: x.stm,
             language: x.language,
             named: x.named,
@@ -0,0 +1,60 @@
+use crate::asynchronous::session::OSession;
+use crate::common::types::bag::{BagCursor, BagPage, RidBag};
+use crate::common::types::rid::ORecordID;
+use crate::OrientResult;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+type PageFuture<'session> = Pin<Box<dyn Future<Output = OrientResult<BagPage>> + Send + 'session>>;
+
+/// Stream over the links of a bag, tree bags are fetched one page at time
+pub struct BagStream<'session> {
+    session: &'session OSession,
+    cursor: BagCursor,
+    next_page: Option<PageFuture<'session>>,
+}
+
+impl<'session> BagStream<'session> {
+    pub(crate) fn new(session: &'session OSession, bag: &RidBag) -> BagStream<'session> {
+        BagStream {
+            session,
+            cursor: BagCursor::new(bag),
+            next_page: None,
+        }
+    }
+}
+
+impl<'session> futures::Stream for BagStream<'session> {
+    type Item = OrientResult<ORecordID>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            match self.next_page.as_mut() {
+                Some(page) => {
+                    let page = futures::ready!(page.as_mut().poll(cx));
+                    self.next_page = None;
+                    match page {
+                        Ok(page) => self.cursor.receive(page),
+                        Err(e) => {
+                            self.cursor.fail();
+                            return Poll::Ready(Some(Err(e)));
+                        }
+                    }
+                }
+                None => {
+                    if let Some(rid) = self.cursor.pop() {
+                        return Poll::Ready(Some(Ok(rid)));
+                    }
+                    match self.cursor.fetch() {
+                        Some(fetch) => {
+                            let session = self.session;
+                            self.next_page = Some(Box::pin(session.fetch_bag(fetch)));
+                        }
+                        None => return Poll::Ready(None),
+                    }
+                }
+            }
+        }
+    }
+}
@@ -1 +1,2 @@
+pub mod bag;
 pub mod resultset;
@@ -1,6 +1,7 @@
 use crate::asynchronous::network::cluster::Server;
-use crate::common::protocol::messages::request::{QueryClose, QueryNext};
+use crate::common::protocol::messages::request::{Close, QueryClose, QueryNext};
 use crate::common::protocol::messages::response::{Query, ServerQuery};
+use crate::common::session::{Credentials, SessionCredentials};
 use crate::common::types::result::OResult;
 use crate::OrientResult;
 #[cfg(feature = "async-std-runtime")]
@@ -9,15 +10,17 @@ use std::future::Future;
 use std::pin::Pin;
 use std::sync::Arc;
 use std::task::{Context, Poll};
+use std::time::Duration;
 #[cfg(feature = "tokio-runtime")]
 use tokio::task;
 
 pub struct PagedResultSet {
     server: Arc<Server>,
     response: Query,
-    session_id: i32,
-    token: Option<Vec<u8>>,
+    credentials: Arc<SessionCredentials>,
     page_size: i32,
+    // Of the statement, to fetch the next pages
+    timeout: Option<Duration>,
     state: ResultState,
 }
 
@@ -30,16 +33,16 @@ impl PagedResultSet {
     pub(crate) fn new(
         server: Arc<Server>,
         response: Query,
-        session_id: i32,
-        token: Option<Vec<u8>>,
+        credentials: Arc<SessionCredentials>,
         page_size: i32,
+        timeout: Option<Duration>,
     ) -> PagedResultSet {
         PagedResultSet {
             server,
             response,
-            session_id,
-            token,
+            credentials,
             page_size,
+            timeout,
             state: ResultState::Looping,
         }
     }
@@ -56,18 +59,23 @@ impl futures::Stream for PagedResultSet {
                     None => {
                         if self.response.has_next {
                             let server = self.server.clone();
+                            let timeout = self.timeout;
+                            let credentials = self.credentials.clone();
 
+                            let Credentials { session_id, token } = credentials.get();
                             let next = QueryNext {
-                                session_id: self.session_id,
-                                token: self.token.clone(),
+                                session_id,
+                                token,
                                 query_id: self.response.query_id.clone(),
                                 page_size: self.page_size,
                             };
                             let response = async move {
                                 let mut conn = server.connection().await?;
-                                let response: Query = conn.send(next.into()).await?.payload();
+                                let mut response =
+                                    conn.send_with_timeout(next.into(), timeout).await?;
+                                credentials.renew(session_id, &response.header);
 
-                                Ok(response)
+                                Ok(response.payload())
                             };
 
                             self.state = ResultState::NextPage(Box::new(response));
@@ -79,8 +87,15 @@ impl futures::Stream for PagedResultSet {
                 ResultState::NextPage(p) => {
                     let response =
                         futures::ready!(unsafe { Pin::new_unchecked(p.as_mut()) }.poll(cx));
-                    self.response = response.unwrap();
                     self.state = ResultState::Looping;
+                    match response {
+                        Ok(response) => self.response = response,
+                        Err(e) => {
+                            // The cursor state is unknown after a failure, stop paging
+                            self.response.has_next = false;
+                            return Poll::Ready(Some(Err(e)));
+                        }
+                    }
                 }
             };
         }
@@ -103,8 +118,7 @@ fn spawn_drop(resultset: &mut PagedResultSet) {
     let has_next = resultset.response.has_next;
     let server = resultset.server.clone();
     let query_id = resultset.response.query_id.clone();
-    let session_id = resultset.session_id.clone();
-    let token = resultset.token.clone();
+    let Credentials { session_id, token } = resultset.credentials.get();
 
     if has_next {
         task::spawn(async move { close_result(server, query_id, session_id, token).await });
@@ -125,19 +139,54 @@ async fn close_result(
 }
 
 pub struct ServerResultSet {
-    response: ServerQuery,
+    inner: PagedResultSet,
 }
 
 impl ServerResultSet {
-    pub(crate) fn new(response: ServerQuery) -> ServerResultSet {
-        ServerResultSet { response }
+    pub(crate) fn new(
+        server: Arc<Server>,
+        response: ServerQuery,
+        session_id: i32,
+        token: Option<Vec<u8>>,
+        page_size: i32,
+        timeout: Option<Duration>,
+    ) -> ServerResultSet {
+        ServerResultSet {
+            inner: PagedResultSet::new(
+                server,
+                response.into(),
+                Arc::new(SessionCredentials::new(Credentials::new(session_id, token))),
+                page_size,
+                timeout,
+            ),
+        }
     }
 }
 
 impl futures::Stream for ServerResultSet {
     type Item = OrientResult<OResult>;
 
-    fn poll_next(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
-        Poll::Ready(self.response.records.pop_front().map(|x| Ok(x)))
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Pin::new(&mut self.inner).poll_next(cx)
+    }
+}
+
+impl Drop for ServerResultSet {
+    fn drop(&mut self) {
+        // The admin session lives as long as the result set
+        let inner = &mut self.inner;
+        let has_next = inner.response.has_next;
+        inner.response.has_next = false;
+        let server = inner.server.clone();
+        let query_id = inner.response.query_id.clone();
+        let Credentials { session_id, token } = inner.credentials.get();
+        task::spawn(async move {
+            if has_next {
+                let _ = close_result(server.clone(), query_id, session_id, token.clone()).await;
+            }
+            let mut conn = server.connection().await?;
+            conn.send_and_forget(Close::new(session_id, token).into())
+                .await
+        });
     }
 }
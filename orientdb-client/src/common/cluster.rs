@@ -0,0 +1,150 @@
+#[cfg(feature = "tls")]
+use crate::common::tls::TlsConnector;
+use crate::OrientResult;
+#[cfg(feature = "tls")]
+use rustls::pki_types::ServerName;
+use std::fmt::Debug;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+/// Strategy used to pick the server of new sessions and connections
+/// when the client is connected to more than one server.
+pub trait SelectionStrategy: Debug + Send + Sync {
+    /// Pick a server given the health of each one.
+    /// Returns `None` when no server can be used
+    fn select(&self, healthy: &[bool]) -> Option<usize>;
+}
+
+/// Use the first healthy server in the order of the configured addresses
+#[derive(Debug, Default)]
+pub struct FirstAvailable;
+
+impl SelectionStrategy for FirstAvailable {
+    fn select(&self, healthy: &[bool]) -> Option<usize> {
+        healthy.iter().position(|h| *h)
+    }
+}
+
+/// Spread the load cycling over the healthy servers
+#[derive(Debug, Default)]
+pub struct RoundRobin {
+    next: AtomicUsize,
+}
+
+impl SelectionStrategy for RoundRobin {
+    fn select(&self, healthy: &[bool]) -> Option<usize> {
+        let len = healthy.len();
+        if len == 0 {
+            return None;
+        }
+        let start = self.next.fetch_add(1, Ordering::SeqCst) % len;
+        (0..len).map(|i| (start + i) % len).find(|i| healthy[*i])
+    }
+}
+
+/// Keep using the same server until it is marked as failed
+#[derive(Debug, Default)]
+pub struct Sticky {
+    current: AtomicUsize,
+}
+
+impl SelectionStrategy for Sticky {
+    fn select(&self, healthy: &[bool]) -> Option<usize> {
+        let len = healthy.len();
+        let current = self.current.load(Ordering::SeqCst);
+        let selected = (0..len)
+            .map(|i| (current + i) % len)
+            .find(|i| healthy[*i])?;
+        self.current.store(selected, Ordering::SeqCst);
+        Some(selected)
+    }
+}
+
+/// Limits on the time taken by the operations of a connection, `None` waits forever
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct Timeouts {
+    pub(crate) connect: Option<Duration>,
+    /// Reads and writes on the socket
+    pub(crate) io: Option<Duration>,
+    /// From sending a request to receiving its response
+    pub(crate) request: Option<Duration>,
+}
+
+/// Address of a server along with the settings of its connections
+#[derive(Clone, Debug)]
+pub(crate) struct Endpoint {
+    pub(crate) address: SocketAddr,
+    pub(crate) timeouts: Timeouts,
+    #[cfg(feature = "tls")]
+    pub(crate) tls: Option<(TlsConnector, ServerName<'static>)>,
+}
+
+impl Endpoint {
+    /// A server reached without TLS
+    pub(crate) fn plain(address: SocketAddr) -> Endpoint {
+        Endpoint {
+            address,
+            timeouts: Timeouts::default(),
+            #[cfg(feature = "tls")]
+            tls: None,
+        }
+    }
+}
+
+/// How the connections to the servers are made
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Transport {
+    pub(crate) timeouts: Timeouts,
+    #[cfg(feature = "tls")]
+    pub(crate) tls: Option<TlsConnector>,
+}
+
+impl Transport {
+    /// The endpoint of the server `host` resolved to `address`
+    #[cfg_attr(not(feature = "tls"), allow(unused_variables))]
+    pub(crate) fn endpoint(&self, host: &str, address: SocketAddr) -> OrientResult<Endpoint> {
+        Ok(Endpoint {
+            address,
+            timeouts: self.timeouts,
+            #[cfg(feature = "tls")]
+            tls: match &self.tls {
+                Some(connector) => Some((connector.clone(), connector.server_name(host)?)),
+                None => None,
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FirstAvailable, RoundRobin, SelectionStrategy, Sticky};
+
+    #[test]
+    fn first_available_test() {
+        let strategy = FirstAvailable;
+        assert_eq!(Some(0), strategy.select(&[true, true]));
+        assert_eq!(Some(1), strategy.select(&[false, true]));
+        assert_eq!(None, strategy.select(&[false, false]));
+    }
+
+    #[test]
+    fn round_robin_test() {
+        let strategy = RoundRobin::default();
+        assert_eq!(Some(0), strategy.select(&[true, true, true]));
+        assert_eq!(Some(1), strategy.select(&[true, true, true]));
+        assert_eq!(Some(0), strategy.select(&[true, false, false]));
+        assert_eq!(Some(0), strategy.select(&[true, true, false]));
+        assert_eq!(None, strategy.select(&[]));
+    }
+
+    #[test]
+    fn sticky_test() {
+        let strategy = Sticky::default();
+        assert_eq!(Some(0), strategy.select(&[true, true]));
+        assert_eq!(Some(0), strategy.select(&[true, true]));
+        assert_eq!(Some(1), strategy.select(&[false, true]));
+        assert_eq!(Some(1), strategy.select(&[true, true]));
+        assert_eq!(None, strategy.select(&[false, false]));
+    }
+}
@@ -0,0 +1,299 @@
+use crate::types::{LiveEvent, OverflowPolicy};
+use crate::{OrientError, OrientResult};
+use std::collections::VecDeque;
+#[cfg(feature = "async")]
+use std::future::poll_fn;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Condvar, Mutex, MutexGuard};
+#[cfg(feature = "async")]
+use std::task::Context;
+use std::task::{Poll, Waker};
+
+pub(crate) const DEFAULT_BUFFER: usize = 10;
+
+type Item = OrientResult<LiveEvent>;
+
+/// Bounded buffer between the connection of a live query and its subscriber.
+///
+/// It is shared by the sync and async clients: blocking calls wait on the
+/// condition variable, async calls register their waker.
+pub(crate) struct LiveQueue {
+    state: Mutex<State>,
+    changed: Condvar,
+    capacity: usize,
+    overflow: OverflowPolicy,
+    dropped: AtomicU64,
+}
+
+#[derive(Default)]
+struct State {
+    items: VecDeque<Item>,
+    // No more events will be pushed
+    closed: bool,
+    // The subscriber is gone or was disconnected by the overflow policy
+    disconnected: bool,
+    receiver: Option<Waker>,
+    senders: Vec<Waker>,
+}
+
+impl LiveQueue {
+    pub(crate) fn new(capacity: usize, overflow: OverflowPolicy) -> LiveQueue {
+        LiveQueue {
+            state: Mutex::new(State::default()),
+            changed: Condvar::new(),
+            capacity: capacity.max(1),
+            overflow,
+            dropped: AtomicU64::new(0),
+        }
+    }
+
+    /// Number of events dropped because the buffer was full
+    pub(crate) fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::SeqCst)
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    // Takes the event out of `item` unless the buffer is full and the policy
+    // is to block, ready with `false` once the subscriber is gone
+    fn try_push(&self, state: &mut State, item: &mut Option<Item>) -> Poll<bool> {
+        if state.disconnected || state.closed {
+            return Poll::Ready(false);
+        }
+        if state.items.len() < self.capacity {
+            state.items.push_back(item.take().expect("pending event"));
+        } else {
+            match self.overflow {
+                OverflowPolicy::Block => return Poll::Pending,
+                OverflowPolicy::DropNewest => {
+                    item.take();
+                    self.dropped.fetch_add(1, Ordering::SeqCst);
+                }
+                OverflowPolicy::DropOldest => {
+                    state.items.pop_front();
+                    state.items.push_back(item.take().expect("pending event"));
+                    self.dropped.fetch_add(1, Ordering::SeqCst);
+                }
+                OverflowPolicy::Disconnect => {
+                    item.take();
+                    self.dropped.fetch_add(1, Ordering::SeqCst);
+                    state.items.push_back(Err(OrientError::LiveOverflow));
+                    state.closed = true;
+                    state.disconnected = true;
+                }
+            }
+        }
+        self.notify_receiver(state);
+        Poll::Ready(!state.disconnected)
+    }
+
+    fn notify_receiver(&self, state: &mut State) {
+        if let Some(waker) = state.receiver.take() {
+            waker.wake();
+        }
+        self.changed.notify_all();
+    }
+
+    fn notify_senders(&self, state: &mut State) {
+        for waker in state.senders.drain(..) {
+            waker.wake();
+        }
+        self.changed.notify_all();
+    }
+
+    #[cfg(feature = "async")]
+    /// Push an event, returns `false` once the subscriber is gone
+    pub(crate) async fn push(&self, item: Item) -> bool {
+        let mut item = Some(item);
+        poll_fn(|cx| {
+            let mut state = self.lock();
+            let pushed = self.try_push(&mut state, &mut item);
+            if pushed.is_pending() {
+                state.senders.push(cx.waker().clone());
+            }
+            pushed
+        })
+        .await
+    }
+
+    /// Blocking version of `push`
+    pub(crate) fn push_blocking(&self, item: Item) -> bool {
+        let mut item = Some(item);
+        let mut state = self.lock();
+        loop {
+            match self.try_push(&mut state, &mut item) {
+                Poll::Ready(pushed) => return pushed,
+                Poll::Pending => {
+                    state = self.changed.wait(state).unwrap_or_else(|e| e.into_inner());
+                }
+            }
+        }
+    }
+
+    fn try_recv(&self, state: &mut State) -> Option<Option<Item>> {
+        match state.items.pop_front() {
+            Some(item) => {
+                self.notify_senders(state);
+                Some(Some(item))
+            }
+            None if state.closed => Some(None),
+            None => None,
+        }
+    }
+
+    #[cfg(feature = "async")]
+    pub(crate) fn poll_recv(&self, cx: &mut Context<'_>) -> Poll<Option<Item>> {
+        let mut state = self.lock();
+        match self.try_recv(&mut state) {
+            Some(item) => Poll::Ready(item),
+            None => {
+                state.receiver = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+
+    #[cfg(feature = "async")]
+    pub(crate) async fn recv(&self) -> Option<Item> {
+        poll_fn(|cx| self.poll_recv(cx)).await
+    }
+
+    /// Wait for the next event, `None` once the live query ended
+    pub(crate) fn recv_blocking(&self) -> Option<Item> {
+        let mut state = self.lock();
+        loop {
+            match self.try_recv(&mut state) {
+                Some(item) => return item,
+                None => {
+                    state = self.changed.wait(state).unwrap_or_else(|e| e.into_inner());
+                }
+            }
+        }
+    }
+
+    /// No more events will be pushed, the subscriber still gets the buffered ones
+    pub(crate) fn close(&self) {
+        let mut state = self.lock();
+        state.closed = true;
+        self.notify_receiver(&mut state);
+    }
+
+    /// End the events with `error`, even if the buffer is full
+    pub(crate) fn fail(&self, error: OrientError) {
+        let mut state = self.lock();
+        if !state.disconnected && !state.closed {
+            state.items.push_back(Err(error));
+        }
+        state.closed = true;
+        self.notify_receiver(&mut state);
+    }
+
+    /// The subscriber is gone, pending and future pushes are refused
+    pub(crate) fn disconnect(&self) {
+        let mut state = self.lock();
+        state.disconnected = true;
+        state.items.clear();
+        self.notify_senders(&mut state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LiveQueue;
+    use crate::types::value::OValue;
+    use crate::types::{LiveEvent, LiveResult, OResult, OverflowPolicy, Projection};
+    use crate::OrientError;
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::SystemTime;
+
+    fn event(n: i32) -> crate::OrientResult<LiveEvent> {
+        let mut projection = Projection::default();
+        projection.push(String::from("n"), OValue::I32(n));
+        let result = LiveResult::Created(OResult::from(projection));
+        Ok(LiveEvent::new(1, SystemTime::now(), result))
+    }
+
+    fn drain(queue: &LiveQueue) -> Vec<i32> {
+        queue.close();
+        let mut values = vec![];
+        while let Some(item) = queue.recv_blocking() {
+            match item.map(LiveEvent::into_result) {
+                Ok(LiveResult::Created(result)) => values.push(result.get("n")),
+                Err(e) => panic!("unexpected error {:?}", e),
+                _ => panic!("unexpected event"),
+            }
+        }
+        values
+    }
+
+    #[test]
+    fn drop_oldest_on_overflow() {
+        let queue = LiveQueue::new(2, OverflowPolicy::DropOldest);
+        for n in 0..5 {
+            assert!(queue.push_blocking(event(n)));
+        }
+        assert_eq!(queue.dropped(), 3);
+        assert_eq!(drain(&queue), vec![3, 4]);
+    }
+
+    #[test]
+    fn drop_newest_on_overflow() {
+        let queue = LiveQueue::new(2, OverflowPolicy::DropNewest);
+        for n in 0..5 {
+            assert!(queue.push_blocking(event(n)));
+        }
+        assert_eq!(queue.dropped(), 3);
+        assert_eq!(drain(&queue), vec![0, 1]);
+    }
+
+    #[test]
+    fn disconnect_on_overflow() {
+        let queue = LiveQueue::new(1, OverflowPolicy::Disconnect);
+        assert!(queue.push_blocking(event(0)));
+        assert!(!queue.push_blocking(event(1)));
+        assert!(!queue.push_blocking(event(2)));
+        assert_eq!(queue.dropped(), 1);
+
+        assert!(matches!(queue.recv_blocking(), Some(Ok(_))));
+        assert!(matches!(
+            queue.recv_blocking(),
+            Some(Err(OrientError::LiveOverflow))
+        ));
+        assert!(queue.recv_blocking().is_none());
+    }
+
+    #[test]
+    fn block_until_received() {
+        let queue = Arc::new(LiveQueue::new(1, OverflowPolicy::Block));
+        let sender = queue.clone();
+        let handle = thread::spawn(move || {
+            for n in 0..3 {
+                assert!(sender.push_blocking(event(n)));
+            }
+            sender.close();
+        });
+        let mut values = vec![];
+        while let Some(Ok(event)) = queue.recv_blocking() {
+            let LiveResult::Created(result) = event.into_result() else {
+                panic!("unexpected event");
+            };
+            values.push(result.get::<i32>("n"));
+        }
+        handle.join().unwrap();
+        assert_eq!(values, vec![0, 1, 2]);
+        assert_eq!(queue.dropped(), 0);
+    }
+
+    #[test]
+    fn disconnect_releases_blocked_sender() {
+        let queue = Arc::new(LiveQueue::new(1, OverflowPolicy::Block));
+        assert!(queue.push_blocking(event(0)));
+        let sender = queue.clone();
+        let handle = thread::spawn(move || sender.push_blocking(event(1)));
+        queue.disconnect();
+        assert!(!handle.join().unwrap());
+    }
+}
@@ -1,9 +1,21 @@
+pub mod cluster;
+pub(crate) mod live;
 pub mod protocol;
+pub mod session;
+#[cfg(feature = "tls")]
+pub mod tls;
 pub mod types;
 
+use crate::common::cluster::{FirstAvailable, SelectionStrategy, Timeouts};
+use crate::common::session::{AlwaysReopen, ReopenPolicy};
+#[cfg(feature = "tls")]
+use crate::common::tls::TlsOptions;
+use std::sync::Arc;
+use std::time::Duration;
+
 pub use crate::common::types::error::OrientError;
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 pub enum DatabaseType {
     Memory,
     PLocal,
@@ -24,7 +36,17 @@ pub type OrientResult<T> = Result<T, OrientError>;
 pub struct ConnectionOptions {
     pub(crate) host: String,
     pub(crate) port: u16,
+    pub(crate) servers: Vec<(String, u16)>,
     pub(crate) pool_size: u32,
+    pub(crate) strategy: Arc<dyn SelectionStrategy>,
+    pub(crate) discover: bool,
+    pub(crate) reopen: Arc<dyn ReopenPolicy>,
+    pub(crate) connect_timeout: Option<Duration>,
+    pub(crate) io_timeout: Option<Duration>,
+    pub(crate) request_timeout: Option<Duration>,
+    pub(crate) pool_timeout: Option<Duration>,
+    #[cfg(feature = "tls")]
+    pub(crate) tls: Option<TlsOptions>,
 }
 
 impl Default for ConnectionOptions {
@@ -32,7 +54,17 @@ impl Default for ConnectionOptions {
         ConnectionOptions {
             host: String::from("localhost"),
             port: 2424,
+            servers: vec![],
             pool_size: 10,
+            strategy: Arc::new(FirstAvailable),
+            discover: true,
+            reopen: Arc::new(AlwaysReopen),
+            connect_timeout: None,
+            io_timeout: None,
+            request_timeout: None,
+            pool_timeout: None,
+            #[cfg(feature = "tls")]
+            tls: None,
         }
     }
 }
@@ -41,32 +73,46 @@ impl ConnectionOptions {
     pub fn builder() -> ConnectionOptionsBuilder {
         ConnectionOptionsBuilder(ConnectionOptions::default())
     }
+
+    pub(crate) fn addresses(&self) -> Vec<(&str, u16)> {
+        std::iter::once((self.host.as_str(), self.port))
+            .chain(self.servers.iter().map(|(h, p)| (h.as_str(), *p)))
+            .collect()
+    }
+
+    pub(crate) fn timeouts(&self) -> Timeouts {
+        Timeouts {
+            connect: self.connect_timeout,
+            io: self.io_timeout,
+            request: self.request_timeout,
+        }
+    }
 }
 
-impl Into<ConnectionOptions> for (&str, u16) {
-    fn into(self) -> ConnectionOptions {
+impl From<(&str, u16)> for ConnectionOptions {
+    fn from(address: (&str, u16)) -> ConnectionOptions {
         ConnectionOptions {
-            host: String::from(self.0),
-            port: self.1,
+            host: String::from(address.0),
+            port: address.1,
             ..Default::default()
         }
     }
 }
 
-impl Into<ConnectionOptions> for (String, u16) {
-    fn into(self) -> ConnectionOptions {
+impl From<(String, u16)> for ConnectionOptions {
+    fn from(address: (String, u16)) -> ConnectionOptions {
         ConnectionOptions {
-            host: self.0,
-            port: self.1,
+            host: address.0,
+            port: address.1,
             ..Default::default()
         }
     }
 }
 
-impl Into<ConnectionOptions> for &str {
-    fn into(self) -> ConnectionOptions {
+impl From<&str> for ConnectionOptions {
+    fn from(host: &str) -> ConnectionOptions {
         ConnectionOptions {
-            host: String::from(self),
+            host: String::from(host),
             ..Default::default()
         }
     }
@@ -93,6 +139,130 @@ impl ConnectionOptionsBuilder {
         self
     }
 
+    /// Add another server of a distributed deployment
+    pub fn add_server<T>(mut self, host: T, port: u16) -> Self
+    where
+        T: Into<String>,
+    {
+        self.0.servers.push((host.into(), port));
+        self
+    }
+
+    /// Set the strategy used to pick a server, the default is [`FirstAvailable`](cluster::FirstAvailable)
+    pub fn strategy<T>(mut self, strategy: T) -> Self
+    where
+        T: SelectionStrategy + 'static,
+    {
+        self.0.strategy = Arc::new(strategy);
+        self
+    }
+
+    /// Keep the servers in sync with the members of the distributed
+    /// configuration pushed by the server, enabled by default.
+    /// The configured servers are always kept, to reconnect when the discovered ones go away.
+    ///
+    /// Only the async client subscribes to the configuration, the sync client
+    /// keeps using the servers it was built with
+    pub fn discover(mut self, discover: bool) -> Self {
+        self.0.discover = discover;
+        self
+    }
+
+    /// Set the policy deciding whether the sessions that expired on the server are opened
+    /// again, the default is [`AlwaysReopen`](session::AlwaysReopen).
+    /// It can be used to log the reconnects or to veto them
+    pub fn reopen<T>(mut self, policy: T) -> Self
+    where
+        T: ReopenPolicy + 'static,
+    {
+        self.0.reopen = Arc::new(policy);
+        self
+    }
+
+    /// Time to wait for a connection to a server to be established, no limit by default
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.0.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// Time to wait for a read or a write on a connection, no limit by default.
+    /// A connection that times out is closed.
+    /// The async client only bounds the writes, the reads are bounded by the request timeout
+    pub fn io_timeout(mut self, timeout: Duration) -> Self {
+        self.0.io_timeout = Some(timeout);
+        self
+    }
+
+    /// Time to wait for the response to a request, no limit by default.
+    /// It can be set for a single statement with `Statement::timeout`
+    pub fn request_timeout(mut self, timeout: Duration) -> Self {
+        self.0.request_timeout = Some(timeout);
+        self
+    }
+
+    /// Time to wait for a connection of the pool of a server, 30 seconds by default
+    pub fn pool_timeout(mut self, timeout: Duration) -> Self {
+        self.0.pool_timeout = Some(timeout);
+        self
+    }
+
+    #[cfg(feature = "tls")]
+    /// Connect to the SSL binary listener of the servers,
+    /// by default the certificates are checked against the webpki roots
+    pub fn tls(mut self, tls: bool) -> Self {
+        self.0.tls = if tls {
+            Some(self.0.tls.unwrap_or_default())
+        } else {
+            None
+        };
+        self
+    }
+
+    #[cfg(feature = "tls")]
+    /// Trust the PEM encoded CA certificates instead of the webpki roots, enables TLS.
+    /// Connecting fails with `OrientError::Tls` when the PEM holds no certificate
+    pub fn tls_ca_pem<T>(mut self, pem: T) -> Self
+    where
+        T: Into<Vec<u8>>,
+    {
+        self.tls_options().ca_roots.push(pem.into());
+        self
+    }
+
+    #[cfg(feature = "tls")]
+    /// Authenticate with the PEM encoded certificate chain and private key, enables TLS
+    pub fn tls_client_auth<C, K>(mut self, cert_chain: C, key: K) -> Self
+    where
+        C: Into<Vec<u8>>,
+        K: Into<Vec<u8>>,
+    {
+        self.tls_options().client_auth = Some((cert_chain.into(), key.into()));
+        self
+    }
+
+    #[cfg(feature = "tls")]
+    /// Name sent with SNI and checked against the server certificates
+    /// instead of the server host, enables TLS
+    pub fn tls_server_name<T>(mut self, name: T) -> Self
+    where
+        T: Into<String>,
+    {
+        self.tls_options().server_name = Some(name.into());
+        self
+    }
+
+    #[cfg(feature = "tls")]
+    /// Accept any server certificate, only meant for tests, enables TLS
+    pub fn tls_insecure(mut self, insecure: bool) -> Self {
+        self.tls_options().insecure = insecure;
+        self
+    }
+
+    #[cfg(feature = "tls")]
+    fn tls_options(&mut self) -> &mut TlsOptions {
+        self.0.tls.get_or_insert_with(TlsOptions::default)
+    }
+
     pub fn build(self) -> ConnectionOptions {
         self.0
     }
@@ -1,3 +1,4 @@
+use crate::common::types::rid::ORecordID;
 use crate::common::OrientResult;
 use byteorder::{BigEndian, WriteBytesExt};
 use std::io::Write;
@@ -39,6 +40,18 @@ impl OBuffer {
         self.inner.write_i16::<BigEndian>(n)?;
         Ok(())
     }
+    pub fn put_i64(&mut self, n: i64) -> OrientResult<()> {
+        self.inner.write_i64::<BigEndian>(n)?;
+        Ok(())
+    }
+    pub fn put_f32(&mut self, n: f32) -> OrientResult<()> {
+        self.inner.write_f32::<BigEndian>(n)?;
+        Ok(())
+    }
+    pub fn put_f64(&mut self, n: f64) -> OrientResult<()> {
+        self.inner.write_f64::<BigEndian>(n)?;
+        Ok(())
+    }
 
     pub fn put_slice(&mut self, src: &[u8]) -> OrientResult<()> {
         self.inner.write_all(src)?;
@@ -59,6 +72,12 @@ impl OBuffer {
         Ok(())
     }
 
+    pub fn write_identity(&mut self, rid: &ORecordID) -> OrientResult<()> {
+        self.put_i16(rid.cluster)?;
+        self.put_i64(rid.position)?;
+        Ok(())
+    }
+
     pub fn write_bool(&mut self, boolean: bool) -> OrientResult<()> {
         if boolean {
             self.put_i8(1)
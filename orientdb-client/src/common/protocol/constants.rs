@@ -9,8 +9,7 @@ pub const FLOAT: i8 = 4;
 pub const DOUBLE: i8 = 5;
 pub const DATETIME: i8 = 6;
 pub const STRING: i8 = 7;
-#[allow(dead_code)]
-const BINARY: i8 = 8;
+pub const BINARY: i8 = 8;
 pub const EMBEDDED: i8 = 9;
 pub const EMBEDDEDLIST: i8 = 10;
 pub const EMBEDDEDSET: i8 = 11;
@@ -18,16 +17,11 @@ pub const EMBEDDEDMAP: i8 = 12;
 pub const LINK: i8 = 13;
 pub const LINKLIST: i8 = 14;
 pub const LINKSET: i8 = 15;
-#[allow(dead_code)]
-const LINKMAP: i8 = 16;
+pub const LINKMAP: i8 = 16;
 pub const BYTE: i8 = 17;
-#[allow(dead_code)]
-const TRANSIENT: i8 = 18;
+pub const TRANSIENT: i8 = 18;
 pub const DATE: i8 = 19;
-#[allow(dead_code)]
-const CUSTOM: i8 = 20;
-#[allow(dead_code)]
-const DECIMAL: i8 = 21;
+pub const CUSTOM: i8 = 20;
+pub const DECIMAL: i8 = 21;
 pub const LINKBAG: i8 = 22;
-#[allow(dead_code)]
-const ANY: i8 = 23;
+pub const ANY: i8 = 23;
@@ -1,8 +1,12 @@
+use crate::common::types::bag::BagPointer;
+use crate::common::types::document::ODocument;
+use crate::common::types::push::PushKind;
+use crate::common::types::rid::ORecordID;
 use crate::common::types::value::OValue;
 use crate::common::DatabaseType;
 use std::collections::HashMap;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct HandShake {
     pub p_version: i16,
     pub name: String,
@@ -28,7 +32,7 @@ impl From<HandShake> for Request {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct MsgHeader {
     pub session_id: i32,
     pub token: Option<Vec<u8>>,
@@ -40,7 +44,7 @@ impl MsgHeader {
     }
 }
 // Connect Message
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Connect {
     pub username: String,
     pub password: String,
@@ -65,7 +69,7 @@ impl From<Connect> for Request {
 }
 
 // Open Message
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Open {
     pub db: String,
     pub username: String,
@@ -91,7 +95,7 @@ impl From<Open> for Request {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct LiveQuery {
     pub session_id: i32,
     pub token: Option<Vec<u8>>,
@@ -124,7 +128,7 @@ impl From<LiveQuery> for Request {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct UnsubscribeLiveQuery {
     pub session_id: i32,
     pub token: Option<Vec<u8>>,
@@ -145,8 +149,49 @@ impl From<UnsubscribeLiveQuery> for Request {
         Request::UnsubscribeLiveQuery(input)
     }
 }
+
+#[derive(Debug, Clone)]
+pub struct SubscribeDistributedConfig {
+    pub session_id: i32,
+    pub token: Option<Vec<u8>>,
+}
+
+impl SubscribeDistributedConfig {
+    pub fn new(session_id: i32, token: Option<Vec<u8>>) -> SubscribeDistributedConfig {
+        SubscribeDistributedConfig { session_id, token }
+    }
+}
+
+impl From<SubscribeDistributedConfig> for Request {
+    fn from(input: SubscribeDistributedConfig) -> Request {
+        Request::SubscribeDistributedConfig(input)
+    }
+}
+#[derive(Debug, Clone)]
+pub struct SubscribePush {
+    pub session_id: i32,
+    pub token: Option<Vec<u8>>,
+    pub kind: PushKind,
+}
+
+impl SubscribePush {
+    pub fn new(session_id: i32, token: Option<Vec<u8>>, kind: PushKind) -> SubscribePush {
+        SubscribePush {
+            session_id,
+            token,
+            kind,
+        }
+    }
+}
+
+impl From<SubscribePush> for Request {
+    fn from(input: SubscribePush) -> Request {
+        Request::SubscribePush(input)
+    }
+}
+
 // Query Message
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Query {
     pub session_id: i32,
     pub token: Option<Vec<u8>>,
@@ -192,7 +237,7 @@ impl From<Query> for Request {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Close {
     pub session_id: i32,
     pub token: Option<Vec<u8>>,
@@ -210,7 +255,7 @@ impl From<Close> for Request {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct QueryNext {
     pub session_id: i32,
     pub token: Option<Vec<u8>>,
@@ -238,7 +283,7 @@ impl From<QueryNext> for Request {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct QueryClose {
     pub session_id: i32,
     pub token: Option<Vec<u8>>,
@@ -265,7 +310,7 @@ impl From<QueryClose> for Request {
 }
 
 // CreateDB Message
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct CreateDB {
     pub header: MsgHeader,
     pub name: String,
@@ -294,7 +339,7 @@ impl From<CreateDB> for Request {
 }
 
 // ExistDB Message
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct ExistDB {
     pub header: MsgHeader,
     pub name: String,
@@ -321,7 +366,7 @@ impl From<ExistDB> for Request {
 }
 
 // DropDB Message
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct DropDB {
     pub header: MsgHeader,
     pub name: String,
@@ -348,7 +393,7 @@ impl From<DropDB> for Request {
 }
 
 // Server Query Message
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct ServerQuery {
     pub session_id: i32,
     pub token: Option<Vec<u8>>,
@@ -394,7 +439,277 @@ impl From<ServerQuery> for Request {
     }
 }
 
-#[derive(Debug)]
+// Load Record Message
+#[derive(Debug, Clone)]
+pub struct LoadRecord {
+    pub session_id: i32,
+    pub token: Option<Vec<u8>>,
+    pub rid: ORecordID,
+    pub fetch_plan: String,
+    pub ignore_cache: bool,
+}
+
+impl LoadRecord {
+    pub fn new(session_id: i32, token: Option<Vec<u8>>, rid: ORecordID) -> LoadRecord {
+        LoadRecord {
+            session_id,
+            token,
+            rid,
+            fetch_plan: String::from(""),
+            ignore_cache: false,
+        }
+    }
+}
+
+impl From<LoadRecord> for Request {
+    fn from(input: LoadRecord) -> Request {
+        Request::LoadRecord(input)
+    }
+}
+
+// SBTree Bonsai First Key Message
+#[derive(Debug, Clone)]
+pub struct SBTreeFirstKey {
+    pub session_id: i32,
+    pub token: Option<Vec<u8>>,
+    pub pointer: BagPointer,
+}
+
+impl SBTreeFirstKey {
+    pub fn new(session_id: i32, token: Option<Vec<u8>>, pointer: BagPointer) -> SBTreeFirstKey {
+        SBTreeFirstKey {
+            session_id,
+            token,
+            pointer,
+        }
+    }
+}
+
+impl From<SBTreeFirstKey> for Request {
+    fn from(input: SBTreeFirstKey) -> Request {
+        Request::SBTreeFirstKey(input)
+    }
+}
+
+// SBTree Bonsai Get Entries Major Message
+#[derive(Debug, Clone)]
+pub struct SBTreeEntriesMajor {
+    pub session_id: i32,
+    pub token: Option<Vec<u8>>,
+    pub pointer: BagPointer,
+    pub key: ORecordID,
+    pub inclusive: bool,
+    pub page_size: i32,
+}
+
+impl SBTreeEntriesMajor {
+    pub fn new(
+        session_id: i32,
+        token: Option<Vec<u8>>,
+        pointer: BagPointer,
+        key: ORecordID,
+        inclusive: bool,
+        page_size: i32,
+    ) -> SBTreeEntriesMajor {
+        SBTreeEntriesMajor {
+            session_id,
+            token,
+            pointer,
+            key,
+            inclusive,
+            page_size,
+        }
+    }
+}
+
+impl From<SBTreeEntriesMajor> for Request {
+    fn from(input: SBTreeEntriesMajor) -> Request {
+        Request::SBTreeEntriesMajor(input)
+    }
+}
+
+// Create Record Message
+#[derive(Debug, Clone)]
+pub struct CreateRecord {
+    pub session_id: i32,
+    pub token: Option<Vec<u8>>,
+    pub cluster_id: i16,
+    pub content: ODocument,
+}
+
+impl CreateRecord {
+    pub fn new(session_id: i32, token: Option<Vec<u8>>, content: ODocument) -> CreateRecord {
+        CreateRecord {
+            session_id,
+            token,
+            cluster_id: content.record_id().cluster,
+            content,
+        }
+    }
+}
+
+impl From<CreateRecord> for Request {
+    fn from(input: CreateRecord) -> Request {
+        Request::CreateRecord(input)
+    }
+}
+
+// Update Record Message
+#[derive(Debug, Clone)]
+pub struct UpdateRecord {
+    pub session_id: i32,
+    pub token: Option<Vec<u8>>,
+    pub rid: ORecordID,
+    pub version: i32,
+    pub content: ODocument,
+}
+
+impl UpdateRecord {
+    pub fn new(session_id: i32, token: Option<Vec<u8>>, content: ODocument) -> UpdateRecord {
+        UpdateRecord {
+            session_id,
+            token,
+            rid: content.record_id().clone(),
+            version: content.version(),
+            content,
+        }
+    }
+}
+
+impl From<UpdateRecord> for Request {
+    fn from(input: UpdateRecord) -> Request {
+        Request::UpdateRecord(input)
+    }
+}
+
+// Delete Record Message
+#[derive(Debug, Clone)]
+pub struct DeleteRecord {
+    pub session_id: i32,
+    pub token: Option<Vec<u8>>,
+    pub rid: ORecordID,
+    pub version: i32,
+}
+
+impl DeleteRecord {
+    pub fn new(
+        session_id: i32,
+        token: Option<Vec<u8>>,
+        rid: ORecordID,
+        version: i32,
+    ) -> DeleteRecord {
+        DeleteRecord {
+            session_id,
+            token,
+            rid,
+            version,
+        }
+    }
+}
+
+impl From<DeleteRecord> for Request {
+    fn from(input: DeleteRecord) -> Request {
+        Request::DeleteRecord(input)
+    }
+}
+
+// Record operation buffered in a transaction
+#[derive(Debug, Clone, PartialEq)]
+pub enum RecordOperation {
+    Create(ODocument),
+    Update(ODocument),
+    Delete(ORecordID, i32),
+}
+
+impl RecordOperation {
+    pub fn rid(&self) -> &ORecordID {
+        match self {
+            RecordOperation::Create(doc) | RecordOperation::Update(doc) => doc.record_id(),
+            RecordOperation::Delete(rid, _) => rid,
+        }
+    }
+}
+
+// Begin Transaction Message
+#[derive(Debug, Clone)]
+pub struct BeginTransaction {
+    pub session_id: i32,
+    pub token: Option<Vec<u8>>,
+    pub tx_id: i32,
+    pub operations: Vec<RecordOperation>,
+}
+
+impl BeginTransaction {
+    pub fn new(
+        session_id: i32,
+        token: Option<Vec<u8>>,
+        tx_id: i32,
+        operations: Vec<RecordOperation>,
+    ) -> BeginTransaction {
+        BeginTransaction {
+            session_id,
+            token,
+            tx_id,
+            operations,
+        }
+    }
+}
+
+impl From<BeginTransaction> for Request {
+    fn from(input: BeginTransaction) -> Request {
+        Request::BeginTransaction(input)
+    }
+}
+
+// Commit Transaction Message
+#[derive(Debug, Clone)]
+pub struct CommitTransaction {
+    pub session_id: i32,
+    pub token: Option<Vec<u8>>,
+    pub tx_id: i32,
+}
+
+impl CommitTransaction {
+    pub fn new(session_id: i32, token: Option<Vec<u8>>, tx_id: i32) -> CommitTransaction {
+        CommitTransaction {
+            session_id,
+            token,
+            tx_id,
+        }
+    }
+}
+
+impl From<CommitTransaction> for Request {
+    fn from(input: CommitTransaction) -> Request {
+        Request::CommitTransaction(input)
+    }
+}
+
+// Rollback Transaction Message
+#[derive(Debug, Clone)]
+pub struct RollbackTransaction {
+    pub session_id: i32,
+    pub token: Option<Vec<u8>>,
+    pub tx_id: i32,
+}
+
+impl RollbackTransaction {
+    pub fn new(session_id: i32, token: Option<Vec<u8>>, tx_id: i32) -> RollbackTransaction {
+        RollbackTransaction {
+            session_id,
+            token,
+            tx_id,
+        }
+    }
+}
+
+impl From<RollbackTransaction> for Request {
+    fn from(input: RollbackTransaction) -> Request {
+        Request::RollbackTransaction(input)
+    }
+}
+
+#[derive(Debug, Clone)]
 pub enum Request {
     HandShake(HandShake),
     Connect(Connect),
@@ -406,16 +721,54 @@ pub enum Request {
     Query(Query),
     LiveQuery(LiveQuery),
     UnsubscribeLiveQuery(UnsubscribeLiveQuery),
+    SubscribeDistributedConfig(SubscribeDistributedConfig),
+    SubscribePush(SubscribePush),
     QueryNext(QueryNext),
     QueryClose(QueryClose),
+    LoadRecord(LoadRecord),
+    SBTreeFirstKey(SBTreeFirstKey),
+    SBTreeEntriesMajor(SBTreeEntriesMajor),
+    CreateRecord(CreateRecord),
+    UpdateRecord(UpdateRecord),
+    DeleteRecord(DeleteRecord),
+    BeginTransaction(BeginTransaction),
+    CommitTransaction(CommitTransaction),
+    RollbackTransaction(RollbackTransaction),
     Close(Close),
 }
 
 impl Request {
     pub fn need_response(&self) -> bool {
+        !matches!(self, Request::Close(_))
+    }
+
+    /// Requests that can be sent again to another server when the connection fails
+    pub fn is_idempotent(&self) -> bool {
+        match self {
+            Request::Connect(_)
+            | Request::Open(_)
+            | Request::ExistDB(_)
+            | Request::LoadRecord(_)
+            | Request::SBTreeFirstKey(_)
+            | Request::SBTreeEntriesMajor(_) => true,
+            Request::Query(query) => query.mode == 1,
+            _ => false,
+        }
+    }
+
+    /// Requests that do not run in a session, so any server of the cluster can answer them
+    pub fn is_sessionless(&self) -> bool {
+        matches!(self, Request::Connect(_) | Request::Open(_))
+    }
+
+    /// The kind of push subscribed by the request, used to tell apart
+    /// the responses of the subscribe message
+    pub(crate) fn subscription(&self) -> Option<i8> {
         match self {
-            Request::Close(_) => false,
-            _ => true,
+            Request::SubscribeDistributedConfig(_) => Some(1),
+            Request::LiveQuery(_) => Some(2),
+            Request::SubscribePush(subscribe) => Some(subscribe.kind.code()),
+            _ => None,
         }
     }
 }
@@ -1,7 +1,11 @@
-use crate::common::types::live::LiveResult;
+use crate::common::types::document::ODocument;
+use crate::common::types::live::{LiveEvent, LiveResult};
+use crate::common::types::push::PushEvent;
 use crate::common::types::result::OResult;
+use crate::common::types::rid::ORecordID;
 use std::collections::HashMap;
 use std::collections::VecDeque;
+use std::time::SystemTime;
 
 #[derive(Debug)]
 pub struct Response {
@@ -79,6 +83,7 @@ pub struct LiveQueryResult {
     pub monitor_id: i32,
     pub ended: bool,
     pub events: Vec<LiveResult>,
+    pub received_at: SystemTime,
 }
 
 impl LiveQueryResult {
@@ -87,8 +92,18 @@ impl LiveQueryResult {
             monitor_id,
             ended,
             events,
+            received_at: SystemTime::now(),
         }
     }
+
+    /// The events of the push, in the order the server sent them
+    pub fn into_events(self) -> impl Iterator<Item = LiveEvent> {
+        let monitor_id = self.monitor_id;
+        let received_at = self.received_at;
+        self.events
+            .into_iter()
+            .map(move |result| LiveEvent::new(monitor_id, received_at, result))
+    }
 }
 
 impl From<LiveQueryResult> for ResponseType {
@@ -97,6 +112,58 @@ impl From<LiveQueryResult> for ResponseType {
     }
 }
 
+#[derive(Debug)]
+pub struct UnsubscribeLiveQuery {}
+
+impl From<UnsubscribeLiveQuery> for ResponseType {
+    fn from(input: UnsubscribeLiveQuery) -> ResponseType {
+        ResponseType::UnsubscribeLiveQuery(Some(input))
+    }
+}
+
+#[derive(Debug)]
+pub struct SubscribePush {}
+
+impl From<SubscribePush> for ResponseType {
+    fn from(input: SubscribePush) -> ResponseType {
+        ResponseType::SubscribePush(Some(input))
+    }
+}
+
+impl From<PushEvent> for ResponseType {
+    fn from(input: PushEvent) -> ResponseType {
+        ResponseType::PushEvent(Some(input))
+    }
+}
+
+#[derive(Debug)]
+pub struct SubscribeDistributedConfig {}
+
+impl From<SubscribeDistributedConfig> for ResponseType {
+    fn from(input: SubscribeDistributedConfig) -> ResponseType {
+        ResponseType::SubscribeDistributedConfig(Some(input))
+    }
+}
+
+/// Members of a distributed deployment pushed by the server,
+/// as the `host:port` addresses of their binary listeners
+#[derive(Debug)]
+pub struct DistributedConfig {
+    pub hosts: Vec<String>,
+}
+
+impl DistributedConfig {
+    pub fn new(hosts: Vec<String>) -> DistributedConfig {
+        DistributedConfig { hosts }
+    }
+}
+
+impl From<DistributedConfig> for ResponseType {
+    fn from(input: DistributedConfig) -> ResponseType {
+        ResponseType::DistributedConfig(Some(input))
+    }
+}
+
 #[derive(Debug)]
 pub struct LiveQuery {
     pub monitor_id: i32,
@@ -223,12 +290,177 @@ impl ServerQuery {
     }
 }
 
+// Next pages of a server query are plain query responses
+impl From<ServerQuery> for Query {
+    fn from(input: ServerQuery) -> Query {
+        Query {
+            query_id: input.query_id,
+            tx_changes: input.tx_changes,
+            execution_plan: input.execution_plan,
+            records: input.records,
+            has_next: input.has_next,
+            stats: input.stats,
+        }
+    }
+}
+
 impl From<ServerQuery> for ResponseType {
     fn from(input: ServerQuery) -> ResponseType {
         ResponseType::ServerQuery(Some(input))
     }
 }
 
+#[derive(Debug)]
+pub struct LoadRecord {
+    pub record: Option<ODocument>,
+}
+
+impl LoadRecord {
+    pub fn new(record: Option<ODocument>) -> LoadRecord {
+        LoadRecord { record }
+    }
+}
+
+impl From<LoadRecord> for ResponseType {
+    fn from(input: LoadRecord) -> ResponseType {
+        ResponseType::LoadRecord(Some(input))
+    }
+}
+
+#[derive(Debug)]
+pub struct SBTreeFirstKey {
+    pub key: Option<ORecordID>,
+}
+
+impl SBTreeFirstKey {
+    pub fn new(key: Option<ORecordID>) -> SBTreeFirstKey {
+        SBTreeFirstKey { key }
+    }
+}
+
+impl From<SBTreeFirstKey> for ResponseType {
+    fn from(input: SBTreeFirstKey) -> ResponseType {
+        ResponseType::SBTreeFirstKey(Some(input))
+    }
+}
+
+#[derive(Debug)]
+pub struct SBTreeEntriesMajor {
+    pub entries: Vec<(ORecordID, i32)>,
+}
+
+impl SBTreeEntriesMajor {
+    pub fn new(entries: Vec<(ORecordID, i32)>) -> SBTreeEntriesMajor {
+        SBTreeEntriesMajor { entries }
+    }
+}
+
+impl From<SBTreeEntriesMajor> for ResponseType {
+    fn from(input: SBTreeEntriesMajor) -> ResponseType {
+        ResponseType::SBTreeEntriesMajor(Some(input))
+    }
+}
+
+#[derive(Debug)]
+pub struct CreateRecord {
+    pub rid: ORecordID,
+    pub version: i32,
+}
+
+impl CreateRecord {
+    pub fn new(rid: ORecordID, version: i32) -> CreateRecord {
+        CreateRecord { rid, version }
+    }
+}
+
+impl From<CreateRecord> for ResponseType {
+    fn from(input: CreateRecord) -> ResponseType {
+        ResponseType::CreateRecord(Some(input))
+    }
+}
+
+#[derive(Debug)]
+pub struct UpdateRecord {
+    pub version: i32,
+}
+
+impl UpdateRecord {
+    pub fn new(version: i32) -> UpdateRecord {
+        UpdateRecord { version }
+    }
+}
+
+impl From<UpdateRecord> for ResponseType {
+    fn from(input: UpdateRecord) -> ResponseType {
+        ResponseType::UpdateRecord(Some(input))
+    }
+}
+
+#[derive(Debug)]
+pub struct DeleteRecord {
+    pub deleted: bool,
+}
+
+impl DeleteRecord {
+    pub fn new(deleted: bool) -> DeleteRecord {
+        DeleteRecord { deleted }
+    }
+}
+
+impl From<DeleteRecord> for ResponseType {
+    fn from(input: DeleteRecord) -> ResponseType {
+        ResponseType::DeleteRecord(Some(input))
+    }
+}
+
+#[derive(Debug)]
+pub struct BeginTransaction {
+    pub tx_id: i32,
+    pub updated_ids: Vec<(ORecordID, ORecordID)>,
+}
+
+impl BeginTransaction {
+    pub fn new(tx_id: i32, updated_ids: Vec<(ORecordID, ORecordID)>) -> BeginTransaction {
+        BeginTransaction { tx_id, updated_ids }
+    }
+}
+
+impl From<BeginTransaction> for ResponseType {
+    fn from(input: BeginTransaction) -> ResponseType {
+        ResponseType::BeginTransaction(Some(input))
+    }
+}
+
+#[derive(Debug)]
+pub struct CommitTransaction {
+    pub created: Vec<(ORecordID, ORecordID)>,
+    pub updated: Vec<(ORecordID, i32)>,
+}
+
+impl CommitTransaction {
+    pub fn new(
+        created: Vec<(ORecordID, ORecordID)>,
+        updated: Vec<(ORecordID, i32)>,
+    ) -> CommitTransaction {
+        CommitTransaction { created, updated }
+    }
+}
+
+impl From<CommitTransaction> for ResponseType {
+    fn from(input: CommitTransaction) -> ResponseType {
+        ResponseType::CommitTransaction(Some(input))
+    }
+}
+
+#[derive(Debug)]
+pub struct RollbackTransaction {}
+
+impl From<RollbackTransaction> for ResponseType {
+    fn from(input: RollbackTransaction) -> ResponseType {
+        ResponseType::RollbackTransaction(Some(input))
+    }
+}
+
 #[allow(clippy::large_enum_variant)]
 #[derive(Debug)]
 pub enum ResponseType {
@@ -242,7 +474,21 @@ pub enum ResponseType {
     ServerQuery(Option<ServerQuery>),
     LiveQuery(Option<LiveQuery>),
     LiveQueryResult(Option<LiveQueryResult>),
+    UnsubscribeLiveQuery(Option<UnsubscribeLiveQuery>),
+    SubscribeDistributedConfig(Option<SubscribeDistributedConfig>),
+    SubscribePush(Option<SubscribePush>),
+    PushEvent(Option<PushEvent>),
+    DistributedConfig(Option<DistributedConfig>),
     QueryClose(Option<QueryClose>),
+    LoadRecord(Option<LoadRecord>),
+    SBTreeFirstKey(Option<SBTreeFirstKey>),
+    SBTreeEntriesMajor(Option<SBTreeEntriesMajor>),
+    CreateRecord(Option<CreateRecord>),
+    UpdateRecord(Option<UpdateRecord>),
+    DeleteRecord(Option<DeleteRecord>),
+    BeginTransaction(Option<BeginTransaction>),
+    CommitTransaction(Option<CommitTransaction>),
+    RollbackTransaction(Option<RollbackTransaction>),
 }
 
 #[derive(Debug, PartialEq)]
@@ -299,4 +545,18 @@ impl_payload!(ExistDB);
 impl_payload!(Connect);
 impl_payload!(LiveQuery);
 impl_payload!(LiveQueryResult);
+impl_payload!(UnsubscribeLiveQuery);
+impl_payload!(SubscribeDistributedConfig);
+impl_payload!(SubscribePush);
+impl_payload!(PushEvent);
+impl_payload!(DistributedConfig);
 impl_payload!(ServerQuery);
+impl_payload!(LoadRecord);
+impl_payload!(SBTreeFirstKey);
+impl_payload!(SBTreeEntriesMajor);
+impl_payload!(CreateRecord);
+impl_payload!(UpdateRecord);
+impl_payload!(DeleteRecord);
+impl_payload!(BeginTransaction);
+impl_payload!(CommitTransaction);
+impl_payload!(RollbackTransaction);
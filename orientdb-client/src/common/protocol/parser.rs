@@ -1,16 +1,72 @@
 use crate::common::types::rid::ORecordID;
+use nom::error::{ErrorKind, ParseError};
 use nom::number::streaming::{be_i8, be_u8};
 use nom::IResult;
 use nom::{do_parse, named, take, try_parse};
 
-named!(pub parse_bool<&[u8],bool>,
+/// Error raised while parsing a serialized record.
+///
+/// It keeps the input left when the error happened, to tell its offset,
+/// and the name of the field being parsed
+#[derive(Debug, PartialEq)]
+pub struct DecodeError<'a> {
+    input: &'a [u8],
+    message: String,
+    field: Option<String>,
+}
+
+impl<'a> DecodeError<'a> {
+    pub fn new<T: Into<String>>(input: &'a [u8], message: T) -> DecodeError<'a> {
+        DecodeError {
+            input,
+            message: message.into(),
+            field: None,
+        }
+    }
+
+    /// Record the field being parsed, nested fields are joined with a dot
+    pub fn in_field(mut self, name: &str) -> DecodeError<'a> {
+        self.field = Some(match self.field {
+            Some(inner) => format!("{}.{}", name, inner),
+            None => name.to_string(),
+        });
+        self
+    }
+
+    /// The bytes of `origin` consumed when the error happened
+    pub fn offset(&self, origin: &[u8]) -> usize {
+        origin.len() - self.input.len()
+    }
+
+    pub fn field(&self) -> Option<&str> {
+        self.field.as_deref()
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl<'a> ParseError<&'a [u8]> for DecodeError<'a> {
+    fn from_error_kind(input: &'a [u8], kind: ErrorKind) -> Self {
+        DecodeError::new(input, kind.description())
+    }
+
+    fn append(_input: &'a [u8], _kind: ErrorKind, other: Self) -> Self {
+        other
+    }
+}
+
+pub type DecodeResult<'a, T> = IResult<&'a [u8], T, DecodeError<'a>>;
+
+named!(pub parse_bool<&[u8],bool,DecodeError<'_>>,
     do_parse!(
         val : be_i8 >>
         (val == 1)
     )
 );
 
-named!(pub parse_optimized_identity<&[u8],ORecordID>,
+named!(pub parse_optimized_identity<&[u8],ORecordID,DecodeError<'_>>,
     do_parse!(
         cluster_id : parse_varint >>
         cluster_position : parse_varint >>
@@ -18,15 +74,26 @@ named!(pub parse_optimized_identity<&[u8],ORecordID>,
     )
 );
 
-named!(pub parse_string_varint<&[u8],String>,
+named!(pub parse_bytes_varint<&[u8],&[u8],DecodeError<'_>>,
   do_parse!(
     length : parse_varint >>
     bytes:  take!(length) >>
-    (String::from_utf8(bytes.to_vec()).unwrap())
+    (bytes)
   )
 );
 
-pub fn parse_varint(input: &[u8]) -> IResult<&[u8], i64> {
+pub fn parse_string_varint(input: &[u8]) -> DecodeResult<'_, String> {
+    let (remaining, bytes) = parse_bytes_varint(input)?;
+    match String::from_utf8(bytes.to_vec()) {
+        Ok(value) => Ok((remaining, value)),
+        Err(e) => Err(nom::Err::Failure(DecodeError::new(
+            input,
+            format!("Invalid UTF-8 string: {}", e),
+        ))),
+    }
+}
+
+pub fn parse_varint(input: &[u8]) -> DecodeResult<'_, i64> {
     let mut value: u64 = 0;
     let mut i: i64 = 0;
     let mut b: u64;
@@ -39,7 +106,10 @@ pub fn parse_varint(input: &[u8]) -> IResult<&[u8], i64> {
             value |= (b & 0x7F) << i;
             i += 7;
             if i > 63 {
-                panic!("Error deserializing varint")
+                return Err(nom::Err::Failure(DecodeError::new(
+                    input,
+                    "Varint longer than 64 bits",
+                )));
             }
         }
         if (b & 0x80) == 0 {
@@ -56,7 +126,7 @@ pub fn parse_varint(input: &[u8]) -> IResult<&[u8], i64> {
 #[cfg(test)]
 mod tests {
     use super::super::buffer::OBuffer;
-    use super::{parse_string_varint, parse_varint};
+    use super::{parse_string_varint, parse_varint, DecodeError};
 
     #[test]
     fn test_parse_varint() {
@@ -95,4 +165,28 @@ mod tests {
         assert_eq!(write_read_varint(-1), -1);
         assert_eq!(write_read_varint(-534), -534);
     }
+
+    #[test]
+    fn test_parse_invalid_input() {
+        let mut buf = OBuffer::new();
+        buf.write_varint(2).unwrap();
+        let mut input = buf.as_slice().to_vec();
+        input.extend_from_slice(&[0xC3, 0x28]);
+        match parse_string_varint(&input) {
+            Err(nom::Err::Failure(e)) => {
+                assert_eq!(0, e.offset(&input));
+                assert!(e.message().starts_with("Invalid UTF-8"));
+            }
+            r => panic!("Expected a failure, got {:?}", r),
+        }
+
+        let input = [0xFF; 11];
+        assert_eq!(
+            Err(nom::Err::Failure(DecodeError::new(
+                &input,
+                "Varint longer than 64 bits"
+            ))),
+            parse_varint(&input)
+        );
+    }
 }
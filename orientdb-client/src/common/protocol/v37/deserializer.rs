@@ -1,42 +1,101 @@
 use super::super::parser::{
-    parse_bool, parse_optimized_identity, parse_string_varint, parse_varint,
+    parse_bool, parse_bytes_varint, parse_optimized_identity, parse_string_varint, parse_varint,
+    DecodeError, DecodeResult,
 };
 
 use super::Protocol37;
 use crate::common::protocol::constants;
 use crate::common::protocol::deserializer::DocumentDeserializer;
-use crate::common::types::bag::RidBag;
+use crate::common::types::bag::{BagChange, BagPointer, RidBag, TreeBag};
+use crate::common::types::decimal::ODecimal;
 use crate::common::types::document::ODocument;
 use crate::common::types::projection::Projection;
+use crate::common::types::rid::ORecordID;
 use crate::common::types::value::OValue;
 
 use crate::{OrientError, OrientResult};
 
 use chrono::TimeZone;
 use chrono::Utc;
-use nom::IResult;
 
-use nom::number::streaming::{be_f32, be_f64, be_i64, be_i8, be_u8};
-use nom::{cond, do_parse, many_m_n, named, try_parse};
+use nom::number::streaming::{be_f32, be_f64, be_i16, be_i32, be_i64, be_i8, be_u8};
+use nom::{cond, do_parse, named, pair, take, try_parse};
 use std::collections::HashMap;
 
 impl DocumentDeserializer for Protocol37 {
     fn decode_document(input: &[u8]) -> OrientResult<ODocument> {
-        let (rm, doc) = parse_document(input)
-            .map_err(|e| OrientError::Decoder(format!("Error decoding document: {:?}", e)))?;
-        assert_eq!(rm.len(), 0);
+        let (rm, doc) = parse_document(input).map_err(|e| decoder_error("document", input, e))?;
+        check_consumed("document", input, rm)?;
         Ok(doc)
     }
     fn decode_projection(input: &[u8]) -> OrientResult<Projection> {
-        let (rm, projection) = parse_projection(input)
-            .map_err(|e| OrientError::Decoder(format!("Error decoding projection: {:?}", e)))?;
-
-        assert_eq!(rm.len(), 0);
+        let (rm, projection) =
+            parse_projection(input).map_err(|e| decoder_error("projection", input, e))?;
+        check_consumed("projection", input, rm)?;
         Ok(projection)
     }
 }
 
-fn parse_document(input: &[u8]) -> IResult<&[u8], ODocument> {
+/// Decode the key returned by the SBTree bonsai first key request
+pub(crate) fn decode_bag_key(input: &[u8]) -> OrientResult<Option<ORecordID>> {
+    if input.is_empty() {
+        return Ok(None);
+    }
+    let (rm, (_serializer, key)) =
+        parse_bag_key(input).map_err(|e| decoder_error("ridbag key", input, e))?;
+    check_consumed("ridbag key", input, rm)?;
+    Ok(Some(key))
+}
+
+/// Decode the entries returned by the SBTree bonsai get entries major request
+pub(crate) fn decode_bag_entries(input: &[u8]) -> OrientResult<Vec<(ORecordID, i32)>> {
+    let (rm, entries) =
+        parse_bag_entries(input).map_err(|e| decoder_error("ridbag entries", input, e))?;
+    check_consumed("ridbag entries", input, rm)?;
+    Ok(entries)
+}
+
+fn decoder_error(what: &str, input: &[u8], err: nom::Err<DecodeError<'_>>) -> OrientError {
+    match err {
+        nom::Err::Incomplete(_) => OrientError::Decoder(format!(
+            "Error decoding {} at byte {}: unexpected end of input",
+            what,
+            input.len()
+        )),
+        nom::Err::Error(e) | nom::Err::Failure(e) => {
+            let field = e
+                .field()
+                .map(|f| format!(" in field {}", f))
+                .unwrap_or_default();
+            OrientError::Decoder(format!(
+                "Error decoding {} at byte {}{}: {}",
+                what,
+                e.offset(input),
+                field,
+                e.message()
+            ))
+        }
+    }
+}
+
+fn check_consumed(what: &str, input: &[u8], remaining: &[u8]) -> OrientResult<()> {
+    if remaining.is_empty() {
+        Ok(())
+    } else {
+        Err(OrientError::Decoder(format!(
+            "Error decoding {} at byte {}: {} trailing bytes",
+            what,
+            input.len() - remaining.len(),
+            remaining.len()
+        )))
+    }
+}
+
+fn failure<T>(input: &[u8], message: String) -> DecodeResult<'_, T> {
+    Err(nom::Err::Failure(DecodeError::new(input, message)))
+}
+
+fn parse_document(input: &[u8]) -> DecodeResult<'_, ODocument> {
     let (remaining, class_name) = try_parse!(input, parse_string_varint);
     let mut doc = ODocument::new(class_name);
     let (mut remaining, fields) = try_parse!(remaining, parse_varint);
@@ -51,7 +110,7 @@ fn parse_document(input: &[u8]) -> IResult<&[u8], ODocument> {
     Ok((remaining, doc))
 }
 
-fn parse_projection(input: &[u8]) -> IResult<&[u8], Projection> {
+fn parse_projection(input: &[u8]) -> DecodeResult<'_, Projection> {
     let (mut remaining, fields) = try_parse!(input, parse_varint);
 
     let mut projection = Projection::default();
@@ -60,28 +119,29 @@ fn parse_projection(input: &[u8]) -> IResult<&[u8], Projection> {
             let (remaining, value) = try_parse!(e, parse_projection);
             Ok((remaining, OValue::EmbeddedMap(value.take_map())))
         })?;
-        projection.insert(field, value);
+        projection.push(field, value);
         remaining = rm;
     }
     let (mut remaining, m_fields) = try_parse!(remaining, parse_varint);
 
     // metadata
     for _ in 0..m_fields {
-        let (rm, (_field, _value)) = parse_field(remaining, &|e| {
+        let (rm, (field, value)) = parse_field(remaining, &|e| {
             let (remaining, value) = try_parse!(e, parse_projection);
             Ok((remaining, OValue::EmbeddedMap(value.take_map())))
         })?;
+        projection.set_metadata(field, value);
         remaining = rm;
     }
 
     Ok((remaining, projection))
 }
 
-fn parse_value<'a, F>(remaining: &'a [u8], embedded: &F) -> IResult<&'a [u8], OValue>
+fn parse_value<'a, F>(input: &'a [u8], embedded: &F) -> DecodeResult<'a, OValue>
 where
-    F: Fn(&[u8]) -> IResult<&[u8], OValue>,
+    F: Fn(&[u8]) -> DecodeResult<'_, OValue>,
 {
-    let (remaining, f_type) = try_parse!(remaining, be_i8);
+    let (remaining, f_type) = try_parse!(input, be_i8);
     let (remaining, value) = match f_type {
         constants::BYTE => {
             let (remaining, value) = try_parse!(remaining, be_i8);
@@ -128,7 +188,11 @@ where
                 remaining = rm;
                 links.push(rid);
             }
-            (remaining, OValue::LinkList(links.into()))
+            if f_type == constants::LINKSET {
+                (remaining, OValue::LinkSet(links.into()))
+            } else {
+                (remaining, OValue::LinkList(links.into()))
+            }
         }
         constants::EMBEDDEDLIST | constants::EMBEDDEDSET => {
             let (mut remaining, size) = try_parse!(remaining, parse_varint);
@@ -138,7 +202,11 @@ where
                 remaining = rm;
                 elements.push(elem)
             }
-            (remaining, OValue::EmbeddedList(elements))
+            if f_type == constants::EMBEDDEDSET {
+                (remaining, OValue::EmbeddedSet(elements))
+            } else {
+                (remaining, OValue::EmbeddedList(elements))
+            }
         }
         constants::EMBEDDEDMAP => {
             let (mut remaining, size) = try_parse!(remaining, parse_varint);
@@ -152,39 +220,82 @@ where
         }
         constants::DATE => {
             let (remaining, timestamp) = try_parse!(remaining, parse_varint);
-            (
-                remaining,
-                OValue::Date(Utc.timestamp(timestamp * 86400, 0).date()),
-            )
+            #[allow(deprecated)]
+            match timestamp
+                .checked_mul(86400)
+                .and_then(|secs| Utc.timestamp_opt(secs, 0).single())
+            {
+                Some(date) => (remaining, OValue::Date(date.date())),
+                None => return failure(input, format!("Invalid date {}", timestamp)),
+            }
         }
         constants::DATETIME => {
             let (remaining, timestamp) = try_parse!(remaining, parse_varint);
-            (
-                remaining,
-                OValue::DateTime(
-                    Utc.timestamp(timestamp / 1000, (timestamp % 1000) as u32 * 1_000_000),
-                ),
-            )
+            match Utc.timestamp_millis_opt(timestamp).single() {
+                Some(datetime) => (remaining, OValue::DateTime(datetime)),
+                None => return failure(input, format!("Invalid datetime {}", timestamp)),
+            }
         }
         constants::LINKBAG => {
-            // just read for now and skip it
             let (remaining, (embedded, tree)) = try_parse!(remaining, parse_bags);
-            let bag = embedded.unwrap_or_else(|| tree.expect("Cannot find tree or embedded bags"));
-            (remaining, OValue::RidBag(bag))
+            match embedded.or(tree) {
+                Some(bag) => (remaining, OValue::RidBag(bag)),
+                None => return failure(input, String::from("Unsupported ridbag type")),
+            }
+        }
+        constants::DECIMAL => {
+            let (remaining, decimal) = try_parse!(remaining, parse_decimal);
+            (remaining, OValue::Decimal(decimal))
+        }
+        constants::BINARY => {
+            let (remaining, bytes) = try_parse!(remaining, parse_bytes_varint);
+            (remaining, OValue::Binary(bytes.to_vec()))
+        }
+        // custom values are java serialized, keep the class name and the bytes
+        constants::CUSTOM => {
+            let (remaining, class_name) = try_parse!(remaining, parse_string_varint);
+            let (remaining, bytes) = try_parse!(remaining, parse_bytes_varint);
+            (remaining, OValue::Custom(class_name, bytes.to_vec()))
         }
+        constants::LINKMAP => {
+            let (mut remaining, size) = try_parse!(remaining, parse_varint);
+            let mut links = HashMap::new();
+            for _ in 0..size {
+                let (rm, key_type) = try_parse!(remaining, be_i8);
+                if key_type != constants::STRING {
+                    return failure(
+                        remaining,
+                        format!("Unsupported link map key type {}", key_type),
+                    );
+                }
+                let (rm, key) = try_parse!(rm, parse_string_varint);
+                let (rm, rid) = try_parse!(rm, parse_optimized_identity);
+                remaining = rm;
+                links.insert(key, rid);
+            }
+            (remaining, OValue::LinkMap(links))
+        }
+        // transient values are never sent, nothing to read
+        constants::TRANSIENT | constants::ANY => (remaining, OValue::Null),
         constants::NULL => (remaining, OValue::Null),
-        _ => panic!("Unsupported field type {}", f_type),
+        _ => return failure(input, format!("Unsupported field type {}", f_type)),
     };
 
     Ok((remaining, value))
 }
 
-fn parse_field<'a, F>(remaining: &'a [u8], embedded: &F) -> IResult<&'a [u8], (String, OValue)>
+fn parse_field<'a, F>(input: &'a [u8], embedded: &F) -> DecodeResult<'a, (String, OValue)>
 where
-    F: Fn(&[u8]) -> IResult<&[u8], OValue>,
+    F: Fn(&[u8]) -> DecodeResult<'_, OValue>,
 {
-    let (remaining, name) = try_parse!(remaining, parse_string_varint);
-    let (remaining, value) = parse_value(remaining, embedded)?;
+    let (remaining, name) = try_parse!(input, parse_string_varint);
+    let (remaining, value) = parse_value(remaining, embedded).map_err(|e| match e {
+        nom::Err::Incomplete(_) => nom::Err::Failure(
+            DecodeError::new(&remaining[remaining.len()..], "Unexpected end of input")
+                .in_field(&name),
+        ),
+        e => e.map(|e| e.in_field(&name)),
+    })?;
     Ok((remaining, (name, value)))
 }
 
@@ -194,7 +305,16 @@ where
 //     Ok((remaining, (name, value)))
 // }
 
-named!(pub parse_bags<&[u8],(Option<RidBag>,Option<RidBag>)>,
+named!(pub parse_decimal<&[u8],ODecimal,DecodeError<'_>>,
+    do_parse!(
+        scale : be_i32 >>
+        length : be_i32 >>
+        unscaled : take!(length) >>
+        (ODecimal::new(unscaled.to_vec(), scale))
+    )
+);
+
+named!(pub parse_bags<&[u8],(Option<RidBag>,Option<RidBag>),DecodeError<'_>>,
     do_parse!(
         _uuid1 : be_i64 >>
         _uuid2 : be_i64 >>
@@ -205,22 +325,77 @@ named!(pub parse_bags<&[u8],(Option<RidBag>,Option<RidBag>)>,
     )
 );
 
-named!(pub parse_bags_embedded<&[u8],RidBag>,
+fn parse_bags_embedded(input: &[u8]) -> DecodeResult<'_, RidBag> {
+    let (mut remaining, size) = try_parse!(input, parse_varint);
+    if size < 0 {
+        return failure(input, format!("Negative ridbag size {}", size));
+    }
+    let mut links = Vec::new();
+    for _ in 0..size {
+        let (rm, rid) = try_parse!(remaining, parse_optimized_identity);
+        links.push(rid);
+        remaining = rm;
+    }
+    Ok((remaining, RidBag::Embedded(links)))
+}
+
+fn parse_bags_tree(input: &[u8]) -> DecodeResult<'_, RidBag> {
+    let (remaining, file_id) = try_parse!(input, parse_varint);
+    let (remaining, page_index) = try_parse!(remaining, parse_varint);
+    let (remaining, page_offset) = try_parse!(remaining, parse_varint);
+    let (remaining, size) = try_parse!(remaining, parse_varint);
+    let (mut remaining, changes_size) = try_parse!(remaining, parse_varint);
+    let mut changes = Vec::new();
+    for _ in 0..changes_size {
+        let (rm, rid) = try_parse!(remaining, parse_optimized_identity);
+        let (rm, kind) = try_parse!(rm, be_i8);
+        let (rm, value) = try_parse!(rm, parse_varint);
+        let change = match kind {
+            0 => BagChange::Diff(value as i32),
+            1 => BagChange::Absolute(value as i32),
+            _ => {
+                return failure(
+                    remaining,
+                    format!("Unsupported ridbag change type {}", kind),
+                )
+            }
+        };
+        changes.push((rid, change));
+        remaining = rm;
+    }
+    let pointer = BagPointer::new(file_id, page_index, page_offset as i32);
+    Ok((
+        remaining,
+        RidBag::Tree(TreeBag::new(pointer, size as i32, changes)),
+    ))
+}
+
+named!(parse_bag_link<&[u8],ORecordID,DecodeError<'_>>,
     do_parse!(
-        size : parse_varint >>
-        bags : many_m_n!(0 as usize ,size as usize,parse_optimized_identity) >>
-        (RidBag::Embedded(bags))
+        cluster : be_i16 >>
+        position : be_i64 >>
+        (ORecordID::new(cluster, position))
     )
 );
 
-// // TODO read changes
-named!(pub parse_bags_tree<&[u8],RidBag>,
+named!(parse_bag_key<&[u8],(i8,ORecordID),DecodeError<'_>>,
     do_parse!(
-        _file_id : parse_varint >>
-        _page_index : parse_varint >>
-        _page_offset : parse_varint >>
-        bag_size : parse_varint >>
-        _changes_size : parse_varint >>
-        (RidBag::Tree(bag_size as i32))
+        serializer : be_i8 >>
+        key : parse_bag_link >>
+        (serializer, key)
     )
 );
+
+fn parse_bag_entries(input: &[u8]) -> DecodeResult<'_, Vec<(ORecordID, i32)>> {
+    let (mut remaining, size) = try_parse!(input, be_i32);
+    if size < 0 {
+        return failure(input, format!("Negative ridbag entries size {}", size));
+    }
+    let mut entries = Vec::new();
+    for _ in 0..size {
+        let (rm, entry) = try_parse!(remaining, pair!(parse_bag_link, be_i32));
+        entries.push(entry);
+        remaining = rm;
+    }
+    Ok((remaining, entries))
+}
@@ -1,3 +1,5 @@
 mod deserializer;
 mod serializer;
 pub struct Protocol37;
+
+pub(crate) use self::deserializer::{decode_bag_entries, decode_bag_key};
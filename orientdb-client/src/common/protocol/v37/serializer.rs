@@ -1,9 +1,12 @@
 use super::super::buffer::OBuffer;
 use super::Protocol37;
+use crate::common::protocol::constants;
 use crate::common::protocol::serializer::DocumentSerializer;
+use crate::common::types::bag::{BagChange, RidBag};
 use crate::common::types::document::ODocument;
 use crate::common::types::value::OValue;
 use crate::common::OrientResult;
+use std::convert::TryFrom;
 
 impl DocumentSerializer for Protocol37 {
     fn encode_document(doc: &ODocument) -> OrientResult<OBuffer> {
@@ -39,15 +42,26 @@ fn write_value<'a>(buf: &mut OBuffer, owner: &'a str, value: &'a OValue) -> Orie
             buf.write_bool(*v)?;
             Ok(())
         }
+        OValue::Null => Ok(()),
+        OValue::I8(v) => buf.put_i8(*v),
+        OValue::U8(v) => match i8::try_from(*v) {
+            Ok(v) => buf.put_i8(v),
+            Err(_) => buf.write_varint(i64::from(*v)),
+        },
         OValue::I16(v) => buf.write_varint(i64::from(*v)),
         OValue::I32(v) => buf.write_varint(i64::from(*v)),
         OValue::I64(v) => buf.write_varint(*v),
+        OValue::F32(v) => buf.put_f32(*v),
+        OValue::F64(v) => buf.put_f64(*v),
+        OValue::DateTime(v) => buf.write_varint(v.timestamp_millis()),
+        #[allow(deprecated)]
+        OValue::Date(v) => buf.write_varint(v.and_hms(0, 0, 0).timestamp() / 86400),
         OValue::Link(ref v) => {
             buf.write_varint(i64::from(v.cluster))?;
             buf.write_varint(v.position)?;
             Ok(())
         }
-        OValue::EmbeddedList(ref v) => {
+        OValue::EmbeddedList(ref v) | OValue::EmbeddedSet(ref v) => {
             buf.write_varint(v.len() as i64)?;
 
             for (idx, elem) in v.iter().enumerate() {
@@ -62,7 +76,7 @@ fn write_value<'a>(buf: &mut OBuffer, owner: &'a str, value: &'a OValue) -> Orie
             Ok(())
         }
 
-        OValue::LinkList(ref list) => {
+        OValue::LinkList(ref list) | OValue::LinkSet(ref list) => {
             buf.write_varint(list.links.len() as i64)?;
 
             for link in list.links.iter() {
@@ -78,12 +92,69 @@ fn write_value<'a>(buf: &mut OBuffer, owner: &'a str, value: &'a OValue) -> Orie
             }
             Ok(())
         }
+        OValue::Decimal(ref decimal) => {
+            buf.put_i32(decimal.scale())?;
+            buf.put_i32(decimal.unscaled().len() as i32)?;
+            buf.put_slice(decimal.unscaled())
+        }
+        OValue::Binary(ref bytes) => {
+            buf.write_varint(bytes.len() as i64)?;
+            buf.put_slice(bytes)
+        }
+        OValue::Custom(ref class_name, ref bytes) => {
+            buf.write_string(class_name)?;
+            buf.write_varint(bytes.len() as i64)?;
+            buf.put_slice(bytes)
+        }
+        OValue::LinkMap(ref links) => {
+            buf.write_varint(links.len() as i64)?;
+            for (k, link) in links {
+                buf.put_i8(constants::STRING)?;
+                buf.write_string(k)?;
+                buf.write_varint(i64::from(link.cluster))?;
+                buf.write_varint(link.position)?;
+            }
+            Ok(())
+        }
+        OValue::RidBag(RidBag::Embedded(ref links)) => {
+            // uuid of the bag, not used by embedded bags
+            buf.put_i64(-1)?;
+            buf.put_i64(-1)?;
+            buf.put_u8(1)?;
+            buf.write_varint(links.len() as i64)?;
+            for link in links {
+                buf.write_varint(i64::from(link.cluster))?;
+                buf.write_varint(link.position)?;
+            }
+            Ok(())
+        }
+        OValue::RidBag(RidBag::Tree(ref tree)) => {
+            buf.put_i64(-1)?;
+            buf.put_i64(-1)?;
+            buf.put_u8(2)?;
+            let pointer = tree.pointer();
+            buf.write_varint(pointer.file_id)?;
+            buf.write_varint(pointer.page_index)?;
+            buf.write_varint(i64::from(pointer.page_offset))?;
+            buf.write_varint(i64::from(tree.size()))?;
+            buf.write_varint(tree.changes().len() as i64)?;
+            for (link, change) in tree.changes() {
+                buf.write_varint(i64::from(link.cluster))?;
+                buf.write_varint(link.position)?;
+                let (kind, value) = match change {
+                    BagChange::Diff(value) => (0, value),
+                    BagChange::Absolute(value) => (1, value),
+                };
+                buf.put_i8(kind)?;
+                buf.write_varint(i64::from(*value))?;
+            }
+            Ok(())
+        }
         #[cfg(feature = "uuid")]
         OValue::Uuid(ref uuid) => {
             buf.write_string(&uuid.to_string())?;
             Ok(())
         }
-        _ => panic!("Field {} not supported", owner),
     }?;
     Ok(())
 }
@@ -104,9 +175,9 @@ mod tests {
         let mut doc = ODocument::new("Test");
 
         // Simple field
-        doc.set("age", 20 as i16);
+        doc.set("age", 20i16);
         doc.set("year", 1983);
-        doc.set("balance", 10000 as i64);
+        doc.set("balance", 10000i64);
         doc.set("confirmed", true);
         doc.set("name", String::from("John"));
 
@@ -177,4 +248,250 @@ mod tests {
             doc.get_raw("links")
         );
     }
+
+    #[test]
+    fn doc_decode_errors_test() {
+        use crate::common::protocol::buffer::OBuffer;
+        use crate::common::protocol::constants;
+        use crate::OrientError;
+
+        fn decode_error(buf: &OBuffer) -> String {
+            match Protocol37::decode_document(buf.as_slice()) {
+                Err(OrientError::Decoder(msg)) => msg,
+                r => panic!("Expected a decoder error, got {:?}", r),
+            }
+        }
+
+        let mut buf = OBuffer::new();
+        buf.write_string("Test").unwrap();
+        buf.write_varint(1).unwrap();
+        buf.write_string("age").unwrap();
+        buf.put_i8(99).unwrap();
+        assert_eq!(
+            "Error decoding document at byte 10 in field age: Unsupported field type 99",
+            decode_error(&buf)
+        );
+
+        let mut buf = OBuffer::new();
+        buf.write_string("Test").unwrap();
+        buf.write_varint(1).unwrap();
+        buf.write_string("embedded").unwrap();
+        buf.put_i8(constants::EMBEDDED).unwrap();
+        buf.write_string("").unwrap();
+        buf.write_varint(1).unwrap();
+        buf.write_string("name").unwrap();
+        buf.put_i8(constants::STRING).unwrap();
+        buf.write_varint(10).unwrap();
+        assert_eq!(
+            "Error decoding document at byte 25 in field embedded.name: Unexpected end of input",
+            decode_error(&buf)
+        );
+
+        let mut buf = OBuffer::new();
+        buf.write_string("Test").unwrap();
+        buf.write_varint(0).unwrap();
+        buf.put_i8(0).unwrap();
+        assert_eq!(
+            "Error decoding document at byte 6: 1 trailing bytes",
+            decode_error(&buf)
+        );
+    }
+
+    #[test]
+    fn bag_negative_size_test() {
+        use super::super::deserializer::decode_bag_entries;
+        use crate::common::protocol::buffer::OBuffer;
+        use crate::common::protocol::constants;
+        use crate::OrientError;
+
+        match decode_bag_entries(&(-1i32).to_be_bytes()) {
+            Err(OrientError::Decoder(msg)) => assert_eq!(
+                "Error decoding ridbag entries at byte 0: Negative ridbag entries size -1",
+                msg
+            ),
+            r => panic!("Expected a decoder error, got {:?}", r),
+        }
+
+        let mut buf = OBuffer::new();
+        buf.write_string("V").unwrap();
+        buf.write_varint(1).unwrap();
+        buf.write_string("in_E").unwrap();
+        buf.put_i8(constants::LINKBAG).unwrap();
+        buf.put_i64(-1).unwrap();
+        buf.put_i64(-1).unwrap();
+        buf.put_u8(1).unwrap();
+        buf.write_varint(-1).unwrap();
+        match Protocol37::decode_document(buf.as_slice()) {
+            Err(OrientError::Decoder(msg)) => assert!(msg.contains("Negative ridbag size -1")),
+            r => panic!("Expected a decoder error, got {:?}", r),
+        }
+    }
+
+    #[test]
+    fn doc_ser_tree_bag_test() {
+        use crate::common::types::bag::{BagChange, BagPointer, RidBag, TreeBag};
+
+        let bag = RidBag::Tree(TreeBag::new(
+            BagPointer::new(5, 3, 128),
+            10,
+            vec![
+                (ORecordID::new(10, 1), BagChange::Diff(-1)),
+                (ORecordID::new(10, 2), BagChange::Absolute(2)),
+            ],
+        ));
+        let mut doc = ODocument::new("V");
+        doc.set_raw("in_E", OValue::RidBag(bag.clone()));
+
+        let encoded = Protocol37::encode_document(&doc).unwrap();
+        let doc = Protocol37::decode_document(encoded.as_slice()).unwrap();
+        assert_eq!(Some(&OValue::RidBag(bag)), doc.get_raw("in_E"));
+    }
+
+    #[test]
+    fn doc_ser_unsigned_byte_test() {
+        let mut doc = ODocument::new("Test");
+        doc.set("byte", 100u8);
+
+        let encoded = Protocol37::encode_document(&doc).unwrap();
+        let doc = Protocol37::decode_document(encoded.as_slice()).unwrap();
+        assert_eq!(Some(&OValue::I8(100)), doc.get_raw("byte"));
+        assert_eq!(100u8, doc.get::<u8>("byte"));
+
+        // values above 127 do not fit a BYTE and are written as SHORT
+        let mut doc = ODocument::new("Test");
+        doc.set("byte", 200u8);
+        doc.set("bytes", vec![OValue::U8(1), OValue::U8(255)]);
+
+        let encoded = Protocol37::encode_document(&doc).unwrap();
+        let doc = Protocol37::decode_document(encoded.as_slice()).unwrap();
+        assert_eq!(Some(&OValue::I16(200)), doc.get_raw("byte"));
+        assert_eq!(200u8, doc.get::<u8>("byte"));
+        assert_eq!(
+            Some(&OValue::EmbeddedList(vec![OValue::I8(1), OValue::I16(255)])),
+            doc.get_raw("bytes")
+        );
+    }
+
+    #[test]
+    fn doc_ser_sets_test() {
+        use std::collections::{BTreeSet, HashSet};
+
+        let mut doc = ODocument::new("Test");
+        let links: HashSet<ORecordID> = vec![ORecordID::new(10, 1)].into_iter().collect();
+        let tags: BTreeSet<&str> = vec!["b", "a"].into_iter().collect();
+        doc.set("links", links);
+        doc.set("tags", tags);
+        doc.set("list", vec![ORecordID::new(10, 1)]);
+
+        let encoded = Protocol37::encode_document(&doc).unwrap();
+        let doc = Protocol37::decode_document(encoded.as_slice()).unwrap();
+        assert_eq!(
+            Some(&OValue::LinkSet(vec![ORecordID::new(10, 1)].into())),
+            doc.get_raw("links")
+        );
+        assert_eq!(
+            Some(&OValue::EmbeddedSet(vec!["a".into(), "b".into()])),
+            doc.get_raw("tags")
+        );
+        assert_eq!(
+            Some(&OValue::LinkList(vec![ORecordID::new(10, 1)].into())),
+            doc.get_raw("list")
+        );
+    }
+
+    mod round_trip {
+        use super::super::Protocol37;
+        use crate::common::protocol::deserializer::DocumentDeserializer;
+        use crate::common::protocol::serializer::DocumentSerializer;
+        use crate::common::types::bag::{BagChange, BagPointer, RidBag, TreeBag};
+        use crate::common::types::decimal::ODecimal;
+        use crate::common::types::document::ODocument;
+        use crate::common::types::rid::ORecordID;
+        use crate::common::types::value::OValue;
+        use chrono::TimeZone;
+        use chrono::Utc;
+        use proptest::collection::{hash_map, vec};
+        use proptest::prelude::*;
+
+        fn rid() -> impl Strategy<Value = ORecordID> {
+            (0..i16::MAX, 0..i64::MAX).prop_map(|(c, p)| ORecordID::new(c, p))
+        }
+
+        fn document(value: impl Strategy<Value = OValue>) -> impl Strategy<Value = ODocument> {
+            ("[A-Za-z]{0,8}", hash_map("[a-z]{1,8}", value, 0..4)).prop_map(|(class, fields)| {
+                let mut doc = ODocument::new(class);
+                for (k, v) in fields {
+                    doc.set_raw(k, v);
+                }
+                doc
+            })
+        }
+
+        // Every variant the deserializer produces when reading a document
+        fn value() -> impl Strategy<Value = OValue> {
+            let leaf = prop_oneof![
+                Just(OValue::Null),
+                any::<String>().prop_map(OValue::String),
+                any::<bool>().prop_map(OValue::Boolean),
+                any::<i8>().prop_map(OValue::I8),
+                any::<i16>().prop_map(OValue::I16),
+                any::<i32>().prop_map(OValue::I32),
+                any::<i64>().prop_map(OValue::I64),
+                proptest::num::f32::NORMAL.prop_map(OValue::F32),
+                proptest::num::f64::NORMAL.prop_map(OValue::F64),
+                (-100_000_000_000_000i64..100_000_000_000_000)
+                    .prop_map(|ms| OValue::DateTime(Utc.timestamp_millis_opt(ms).unwrap())),
+                #[allow(deprecated)]
+                (-1_000_000i64..1_000_000).prop_map(|days| OValue::Date(
+                    Utc.timestamp_opt(days * 86400, 0).unwrap().date()
+                )),
+                rid().prop_map(OValue::Link),
+                vec(rid(), 0..4).prop_map(|links| OValue::LinkList(links.into())),
+                vec(rid(), 0..4).prop_map(|links| OValue::LinkSet(links.into())),
+                vec(rid(), 0..4).prop_map(|links| OValue::RidBag(RidBag::Embedded(links))),
+                (
+                    any::<(i64, i64, i32, i32)>(),
+                    vec((rid(), any::<bool>(), any::<i32>()), 0..4)
+                )
+                    .prop_map(|((file, page, offset, size), changes)| {
+                        let changes = changes
+                            .into_iter()
+                            .map(|(rid, absolute, value)| {
+                                let change = if absolute {
+                                    BagChange::Absolute(value)
+                                } else {
+                                    BagChange::Diff(value)
+                                };
+                                (rid, change)
+                            })
+                            .collect();
+                        let pointer = BagPointer::new(file, page, offset);
+                        OValue::RidBag(RidBag::Tree(TreeBag::new(pointer, size, changes)))
+                    }),
+                (vec(any::<u8>(), 0..20), any::<i32>())
+                    .prop_map(|(unscaled, scale)| OValue::Decimal(ODecimal::new(unscaled, scale))),
+                vec(any::<u8>(), 0..20).prop_map(OValue::Binary),
+                ("[a-z.]{1,16}", vec(any::<u8>(), 0..20))
+                    .prop_map(|(class_name, bytes)| OValue::Custom(class_name, bytes)),
+                hash_map("[a-z]{1,8}", rid(), 0..4).prop_map(OValue::LinkMap),
+            ];
+            leaf.prop_recursive(3, 32, 4, |inner| {
+                prop_oneof![
+                    vec(inner.clone(), 0..4).prop_map(OValue::EmbeddedList),
+                    vec(inner.clone(), 0..4).prop_map(OValue::EmbeddedSet),
+                    hash_map("[a-z]{1,8}", inner.clone(), 0..4).prop_map(OValue::EmbeddedMap),
+                    document(inner).prop_map(OValue::Document),
+                ]
+            })
+        }
+
+        proptest! {
+            #[test]
+            fn doc_round_trip(doc in document(value())) {
+                let encoded = Protocol37::encode_document(&doc).unwrap();
+                let decoded = Protocol37::decode_document(encoded.as_slice()).unwrap();
+                prop_assert_eq!(doc, decoded);
+            }
+        }
+    }
 }
@@ -0,0 +1,215 @@
+use crate::common::protocol::messages::response::Header;
+use crate::OrientError;
+use std::fmt::{self, Debug};
+use std::sync::{Arc, RwLock};
+
+/// Decides whether a session reported as expired by the server is opened again,
+/// after its token expired or the server restarted.
+///
+/// When the session is opened again the failed request is sent once more.
+pub trait ReopenPolicy: Debug + Send + Sync {
+    /// Called with the database of the session and the error of the server,
+    /// before the session is opened again. Returns `false` to veto the reconnect,
+    /// the request then fails with `error`
+    fn reopen(&self, db: &str, error: &OrientError) -> bool;
+}
+
+/// Always open the expired sessions again
+#[derive(Debug, Default)]
+pub struct AlwaysReopen;
+
+impl ReopenPolicy for AlwaysReopen {
+    fn reopen(&self, _db: &str, _error: &OrientError) -> bool {
+        true
+    }
+}
+
+/// Never open the expired sessions again, their requests fail with the error of the server
+#[derive(Debug, Default)]
+pub struct NeverReopen;
+
+impl ReopenPolicy for NeverReopen {
+    fn reopen(&self, _db: &str, _error: &OrientError) -> bool {
+        false
+    }
+}
+
+/// The id and the token sent with the requests of a session
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Credentials {
+    pub(crate) session_id: i32,
+    pub(crate) token: Option<Vec<u8>>,
+}
+
+impl Credentials {
+    pub(crate) fn new(session_id: i32, token: Option<Vec<u8>>) -> Credentials {
+        Credentials { session_id, token }
+    }
+}
+
+/// The credentials of a session, replaced when the session is opened again
+/// and shared with its result sets to follow the renewals of the token
+#[derive(Debug)]
+pub(crate) struct SessionCredentials(RwLock<Credentials>);
+
+impl SessionCredentials {
+    pub(crate) fn new(credentials: Credentials) -> SessionCredentials {
+        SessionCredentials(RwLock::new(credentials))
+    }
+
+    pub(crate) fn get(&self) -> Credentials {
+        self.0.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    pub(crate) fn set(&self, credentials: Credentials) {
+        *self.0.write().unwrap_or_else(|e| e.into_inner()) = credentials;
+    }
+
+    /// Adopt the token renewed by the server in the `header` of a response to a request
+    /// sent as `session_id`, unless the session was opened again since
+    pub(crate) fn renew(&self, session_id: i32, header: &Header) {
+        if let Some(token) = header.token.as_ref().filter(|token| !token.is_empty()) {
+            let mut current = self.0.write().unwrap_or_else(|e| e.into_inner());
+            if current.session_id == session_id && current.token.as_ref() != Some(token) {
+                current.token = Some(token.clone());
+            }
+        }
+    }
+
+    /// Replace the `expired` credentials by `fresh` ones. When another caller already
+    /// replaced them, theirs are kept and returned
+    pub(crate) fn replace(&self, expired: &Credentials, fresh: Credentials) -> Credentials {
+        let mut current = self.0.write().unwrap_or_else(|e| e.into_inner());
+        if *current == *expired {
+            *current = fresh;
+        }
+        current.clone()
+    }
+}
+
+/// How a session was opened, to open it again when it expires
+#[derive(Clone)]
+pub(crate) struct Reopen {
+    pub(crate) db: String,
+    pub(crate) user: String,
+    pub(crate) password: String,
+    policy: Arc<dyn ReopenPolicy>,
+}
+
+impl Reopen {
+    pub(crate) fn new(
+        db: &str,
+        user: &str,
+        password: &str,
+        policy: Arc<dyn ReopenPolicy>,
+    ) -> Reopen {
+        Reopen {
+            db: String::from(db),
+            user: String::from(user),
+            password: String::from(password),
+            policy,
+        }
+    }
+
+    /// Whether a request that failed with `error` is sent again on a new session
+    pub(crate) fn allows(&self, error: &OrientError) -> bool {
+        error.is_session_expired() && self.policy.reopen(&self.db, error)
+    }
+}
+
+impl Debug for Reopen {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Reopen")
+            .field("db", &self.db)
+            .field("user", &self.user)
+            .field("policy", &self.policy)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AlwaysReopen, Credentials, NeverReopen, Reopen, SessionCredentials};
+    use crate::common::protocol::messages::response::{Header, Status};
+    use crate::common::types::error::{OError, RequestError};
+    use crate::OrientError;
+    use std::sync::Arc;
+
+    fn server_error(err_type: &str, err_msg: &str) -> OrientError {
+        let mut err = RequestError::new();
+        err.add_error(OError::new(String::from(err_type), String::from(err_msg)));
+        err.into()
+    }
+
+    #[test]
+    fn reopen_expired_sessions() {
+        let expired = server_error(
+            "com.orientechnologies.orient.enterprise.channel.binary.OTokenSecurityException",
+            "The token provided is expired",
+        );
+        // The session id of a session without token after a restart of the server
+        let unknown = server_error(
+            "com.orientechnologies.common.io.OIOException",
+            "Found unknown session 12",
+        );
+        let io = server_error(
+            "com.orientechnologies.common.io.OIOException",
+            "Error on reading the file",
+        );
+        let other = server_error(
+            "com.orientechnologies.orient.core.exception.OCommandExecutionException",
+            "failed",
+        );
+
+        let reopen = Reopen::new("db", "admin", "admin", Arc::new(AlwaysReopen));
+        assert!(reopen.allows(&expired));
+        assert!(reopen.allows(&unknown));
+        assert!(!reopen.allows(&io));
+        assert!(!reopen.allows(&other));
+
+        let veto = Reopen::new("db", "admin", "admin", Arc::new(NeverReopen));
+        assert!(!veto.allows(&expired));
+    }
+
+    fn header(session_id: i32, token: Option<Vec<u8>>) -> Header {
+        Header {
+            status: Status::OK,
+            client_id: None,
+            session_id,
+            token,
+            op: 30,
+        }
+    }
+
+    #[test]
+    fn renew_token() {
+        let credentials = SessionCredentials::new(Credentials::new(1, Some(vec![1])));
+
+        // An empty token tells the token was not renewed
+        credentials.renew(1, &header(1, Some(vec![])));
+        credentials.renew(1, &header(1, None));
+        assert_eq!(Some(vec![1]), credentials.get().token);
+
+        credentials.renew(1, &header(1, Some(vec![2])));
+        assert_eq!(Credentials::new(1, Some(vec![2])), credentials.get());
+
+        // A late response of a session opened again is ignored
+        credentials.set(Credentials::new(3, Some(vec![3])));
+        credentials.renew(1, &header(1, Some(vec![4])));
+        assert_eq!(Credentials::new(3, Some(vec![3])), credentials.get());
+    }
+
+    #[test]
+    fn replace_credentials_once() {
+        let expired = Credentials::new(1, Some(vec![1]));
+        let credentials = SessionCredentials::new(expired.clone());
+
+        let fresh = credentials.replace(&expired, Credentials::new(2, Some(vec![2])));
+        assert_eq!(2, fresh.session_id);
+
+        // A caller that saw the same expired credentials keeps the new ones
+        let kept = credentials.replace(&expired, Credentials::new(3, Some(vec![3])));
+        assert_eq!(fresh, kept);
+        assert_eq!(fresh, credentials.get());
+    }
+}
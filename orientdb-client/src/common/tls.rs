@@ -0,0 +1,206 @@
+use crate::{OrientError, OrientResult};
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::crypto::{ring, verify_tls12_signature, verify_tls13_signature, CryptoProvider};
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer, ServerName, UnixTime};
+use rustls::{ClientConfig, DigitallySignedStruct, RootCertStore, SignatureScheme};
+use std::convert::TryFrom;
+use std::fmt;
+use std::sync::Arc;
+
+/// Settings of the connections to the SSL binary listener of the server.
+///
+/// Certificates and keys are PEM encoded, they are parsed when the client connects.
+#[derive(Clone, Default)]
+pub struct TlsOptions {
+    pub(crate) ca_roots: Vec<Vec<u8>>,
+    pub(crate) client_auth: Option<(Vec<u8>, Vec<u8>)>,
+    pub(crate) server_name: Option<String>,
+    pub(crate) insecure: bool,
+}
+
+impl fmt::Debug for TlsOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TlsOptions")
+            .field("ca_roots", &self.ca_roots.len())
+            .field("client_auth", &self.client_auth.is_some())
+            .field("server_name", &self.server_name)
+            .field("insecure", &self.insecure)
+            .finish()
+    }
+}
+
+impl TlsOptions {
+    pub(crate) fn connector(&self) -> OrientResult<TlsConnector> {
+        let provider = Arc::new(ring::default_provider());
+        let builder = ClientConfig::builder_with_provider(provider.clone())
+            .with_safe_default_protocol_versions()
+            .map_err(tls_error)?;
+
+        let builder = if self.insecure {
+            builder
+                .dangerous()
+                .with_custom_certificate_verifier(Arc::new(NoVerification(provider)))
+        } else {
+            let mut roots = RootCertStore::empty();
+            if self.ca_roots.is_empty() {
+                roots.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
+            }
+            for pem in &self.ca_roots {
+                for cert in certificates(pem, "CA")? {
+                    roots.add(cert).map_err(tls_error)?;
+                }
+            }
+            builder.with_root_certificates(roots)
+        };
+
+        let config = match &self.client_auth {
+            Some((chain, key)) => {
+                let chain = certificates(chain, "client")?;
+                let key = PrivateKeyDer::from_pem_slice(key).map_err(tls_error)?;
+                builder
+                    .with_client_auth_cert(chain, key)
+                    .map_err(tls_error)?
+            }
+            None => builder.with_no_client_auth(),
+        };
+
+        Ok(TlsConnector {
+            config: Arc::new(config),
+            server_name: self.server_name.clone(),
+        })
+    }
+}
+
+/// The client configuration shared by every connection of a client
+#[derive(Clone, Debug)]
+pub(crate) struct TlsConnector {
+    pub(crate) config: Arc<ClientConfig>,
+    server_name: Option<String>,
+}
+
+impl TlsConnector {
+    /// Name checked against the certificate of `host`, unless one was configured
+    pub(crate) fn server_name(&self, host: &str) -> OrientResult<ServerName<'static>> {
+        let name = self.server_name.as_deref().unwrap_or(host);
+        ServerName::try_from(name.to_string()).map_err(tls_error)
+    }
+}
+
+// The certificates of a PEM, which must hold at least one
+fn certificates(pem: &[u8], name: &str) -> OrientResult<Vec<CertificateDer<'static>>> {
+    let certs = CertificateDer::pem_slice_iter(pem)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(tls_error)?;
+    if certs.is_empty() {
+        return Err(OrientError::Tls(format!(
+            "No certificate found in the {} PEM",
+            name
+        )));
+    }
+    Ok(certs)
+}
+
+pub(crate) fn tls_error<E: fmt::Display>(e: E) -> OrientError {
+    OrientError::Tls(e.to_string())
+}
+
+// Accepts any certificate, only meant for tests against self-signed servers
+#[derive(Debug)]
+struct NoVerification(Arc<CryptoProvider>);
+
+impl ServerCertVerifier for NoVerification {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls12_signature(
+            message,
+            cert,
+            dss,
+            &self.0.signature_verification_algorithms,
+        )
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls13_signature(
+            message,
+            cert,
+            dss,
+            &self.0.signature_verification_algorithms,
+        )
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.0.signature_verification_algorithms.supported_schemes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TlsOptions;
+    use crate::OrientError;
+
+    #[test]
+    fn connector_with_default_roots() {
+        let connector = TlsOptions::default().connector().unwrap();
+        assert!(connector.server_name("db.example.com").is_ok());
+        assert!(connector.server_name("not a host").is_err());
+    }
+
+    #[test]
+    fn connector_with_server_name() {
+        let options = TlsOptions {
+            server_name: Some(String::from("db.example.com")),
+            insecure: true,
+            ..Default::default()
+        };
+        let connector = options.connector().unwrap();
+        assert!(connector.server_name("not a host").is_ok());
+    }
+
+    #[test]
+    fn connector_with_invalid_ca() {
+        let options = TlsOptions {
+            ca_roots: vec![
+                b"-----BEGIN CERTIFICATE-----\nnot base64\n-----END CERTIFICATE-----\n".to_vec(),
+            ],
+            ..Default::default()
+        };
+        assert!(matches!(options.connector(), Err(OrientError::Tls(_))));
+    }
+
+    #[test]
+    fn connector_without_certificates() {
+        let options = TlsOptions {
+            ca_roots: vec![b"not a certificate".to_vec()],
+            ..Default::default()
+        };
+        assert!(matches!(options.connector(), Err(OrientError::Tls(_))));
+
+        let options = TlsOptions {
+            client_auth: Some((vec![], vec![])),
+            insecure: true,
+            ..Default::default()
+        };
+        assert!(matches!(options.connector(), Err(OrientError::Tls(_))));
+    }
+}
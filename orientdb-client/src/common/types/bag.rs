@@ -1,7 +1,264 @@
 use crate::common::types::rid::ORecordID;
+use std::collections::{HashMap, VecDeque};
 
 #[derive(Debug, PartialEq, Clone)]
 pub enum RidBag {
     Embedded(Vec<ORecordID>),
-    Tree(i32),
+    Tree(TreeBag),
+}
+
+impl RidBag {
+    /// Iterate the links of the bag, the content of tree bags is fetched with the session
+    pub fn iter<'session, S>(&self, session: &'session S) -> S::Iter
+    where
+        S: BagSession<'session>,
+    {
+        session.bag_iter(self)
+    }
+}
+
+/// Session that can fetch the content of tree bags
+pub trait BagSession<'session> {
+    type Iter;
+
+    fn bag_iter(&'session self, bag: &RidBag) -> Self::Iter;
+}
+
+/// Ridbag stored by the server in an SBTree, only the pointer to the tree is sent.
+/// Use `RidBag::iter` with a session to fetch its content
+#[derive(Debug, PartialEq, Clone)]
+pub struct TreeBag {
+    pub(crate) pointer: BagPointer,
+    pub(crate) size: i32,
+    pub(crate) changes: Vec<(ORecordID, BagChange)>,
+}
+
+impl TreeBag {
+    pub fn new(pointer: BagPointer, size: i32, changes: Vec<(ORecordID, BagChange)>) -> TreeBag {
+        TreeBag {
+            pointer,
+            size,
+            changes,
+        }
+    }
+
+    /// Number of links in the bag
+    pub fn size(&self) -> i32 {
+        self.size
+    }
+
+    pub fn pointer(&self) -> &BagPointer {
+        &self.pointer
+    }
+
+    /// Changes not yet applied to the tree
+    pub fn changes(&self) -> &[(ORecordID, BagChange)] {
+        &self.changes
+    }
+}
+
+/// Location of the SBTree of a bag on the server
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct BagPointer {
+    pub file_id: i64,
+    pub page_index: i64,
+    pub page_offset: i32,
+}
+
+impl BagPointer {
+    pub fn new(file_id: i64, page_index: i64, page_offset: i32) -> BagPointer {
+        BagPointer {
+            file_id,
+            page_index,
+            page_offset,
+        }
+    }
+}
+
+/// Change to the occurrences of a link in a tree bag
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum BagChange {
+    Diff(i32),
+    Absolute(i32),
+}
+
+impl BagChange {
+    fn apply(self, count: i32) -> i32 {
+        match self {
+            BagChange::Diff(diff) => count + diff,
+            BagChange::Absolute(value) => value,
+        }
+    }
+}
+
+// Links fetched per request when iterating a tree bag
+pub(crate) const BAG_PAGE_SIZE: i32 = 128;
+
+#[derive(Clone)]
+pub(crate) enum BagFetch {
+    FirstKey(BagPointer),
+    EntriesMajor(BagPointer, ORecordID, bool),
+}
+
+pub(crate) enum BagPage {
+    FirstKey(Option<ORecordID>),
+    Entries(Vec<(ORecordID, i32)>),
+}
+
+/// Walks the pages of a bag, the session specific iterators send the requests
+pub(crate) struct BagCursor {
+    pointer: Option<BagPointer>,
+    next: Option<(ORecordID, bool)>,
+    started: bool,
+    changes: HashMap<ORecordID, BagChange>,
+    buffer: VecDeque<ORecordID>,
+}
+
+impl BagCursor {
+    pub(crate) fn new(bag: &RidBag) -> BagCursor {
+        match bag {
+            RidBag::Embedded(links) => BagCursor {
+                pointer: None,
+                next: None,
+                started: true,
+                changes: HashMap::new(),
+                buffer: links.iter().cloned().collect(),
+            },
+            RidBag::Tree(tree) => BagCursor {
+                pointer: Some(tree.pointer.clone()),
+                next: None,
+                started: false,
+                changes: tree.changes.iter().cloned().collect(),
+                buffer: VecDeque::new(),
+            },
+        }
+    }
+
+    pub(crate) fn pop(&mut self) -> Option<ORecordID> {
+        self.buffer.pop_front()
+    }
+
+    /// The request to send to fill the buffer, `None` when the bag is over
+    pub(crate) fn fetch(&self) -> Option<BagFetch> {
+        let pointer = self.pointer.clone()?;
+        match &self.next {
+            _ if !self.started => Some(BagFetch::FirstKey(pointer)),
+            Some((key, inclusive)) => {
+                Some(BagFetch::EntriesMajor(pointer, key.clone(), *inclusive))
+            }
+            None => None,
+        }
+    }
+
+    pub(crate) fn receive(&mut self, page: BagPage) {
+        match page {
+            BagPage::FirstKey(key) => self.first_key(key),
+            BagPage::Entries(entries) => self.entries(entries),
+        }
+    }
+
+    pub(crate) fn first_key(&mut self, key: Option<ORecordID>) {
+        self.started = true;
+        match key {
+            Some(key) => self.next = Some((key, true)),
+            None => self.finish(),
+        }
+    }
+
+    pub(crate) fn entries(&mut self, entries: Vec<(ORecordID, i32)>) {
+        match entries.last() {
+            Some((last, _)) => self.next = Some((last.clone(), false)),
+            None => return self.finish(),
+        }
+        for (rid, count) in entries {
+            let count = match self.changes.remove(&rid) {
+                Some(change) => change.apply(count),
+                None => count,
+            };
+            for _ in 0..count {
+                self.buffer.push_back(rid.clone());
+            }
+        }
+    }
+
+    // Links added on the client and not yet in the tree come last
+    fn finish(&mut self) {
+        self.pointer = None;
+        self.next = None;
+        for (rid, change) in self.changes.drain() {
+            for _ in 0..change.apply(0) {
+                self.buffer.push_back(rid.clone());
+            }
+        }
+    }
+
+    pub(crate) fn fail(&mut self) {
+        self.pointer = None;
+        self.next = None;
+        self.buffer.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BagChange, BagCursor, BagFetch, BagPointer, RidBag, TreeBag};
+    use crate::common::types::rid::ORecordID;
+
+    fn drain(cursor: &mut BagCursor) -> Vec<ORecordID> {
+        std::iter::from_fn(|| cursor.pop()).collect()
+    }
+
+    #[test]
+    fn cursor_embedded_bag() {
+        let bag = RidBag::Embedded(vec![ORecordID::new(10, 1), ORecordID::new(10, 2)]);
+        let mut cursor = BagCursor::new(&bag);
+        assert!(cursor.fetch().is_none());
+        assert_eq!(
+            vec![ORecordID::new(10, 1), ORecordID::new(10, 2)],
+            drain(&mut cursor)
+        );
+    }
+
+    #[test]
+    fn cursor_tree_bag() {
+        let bag = RidBag::Tree(TreeBag::new(
+            BagPointer::new(1, 2, 3),
+            4,
+            vec![
+                (ORecordID::new(10, 2), BagChange::Diff(-1)),
+                (ORecordID::new(10, 5), BagChange::Absolute(1)),
+            ],
+        ));
+        let mut cursor = BagCursor::new(&bag);
+
+        match cursor.fetch() {
+            Some(BagFetch::FirstKey(pointer)) => assert_eq!(BagPointer::new(1, 2, 3), pointer),
+            _ => panic!("Expected a first key request"),
+        }
+        cursor.first_key(Some(ORecordID::new(10, 1)));
+
+        match cursor.fetch() {
+            Some(BagFetch::EntriesMajor(_, key, inclusive)) => {
+                assert_eq!(ORecordID::new(10, 1), key);
+                assert!(inclusive);
+            }
+            _ => panic!("Expected an entries request"),
+        }
+        cursor.entries(vec![(ORecordID::new(10, 1), 2), (ORecordID::new(10, 2), 1)]);
+        assert_eq!(
+            vec![ORecordID::new(10, 1), ORecordID::new(10, 1)],
+            drain(&mut cursor)
+        );
+
+        match cursor.fetch() {
+            Some(BagFetch::EntriesMajor(_, key, inclusive)) => {
+                assert_eq!(ORecordID::new(10, 2), key);
+                assert!(!inclusive);
+            }
+            _ => panic!("Expected an entries request"),
+        }
+        cursor.entries(vec![]);
+        assert!(cursor.fetch().is_none());
+        assert_eq!(vec![ORecordID::new(10, 5)], drain(&mut cursor));
+    }
 }
@@ -0,0 +1,602 @@
+//! Deserialize `OResult`, `ODocument` and `OValue` into types implementing `serde::Deserialize`.
+//!
+//! Documents and embedded maps are seen as maps, lists and sets as sequences.
+//! Links are seen as strings in the `#cluster:position` form.
+//! Enums are read from a string for unit variants or from a single entry map for the others.
+
+use crate::common::types::bag::RidBag;
+use crate::common::types::document::ODocument;
+use crate::common::types::result::OResult;
+use crate::common::types::rid::ORecordID;
+use crate::common::types::value::OValue;
+use crate::common::{OrientError, OrientResult};
+use serde::de::value::{MapDeserializer, SeqDeserializer};
+use serde::de::{self, Deserialize, IntoDeserializer, Visitor};
+use serde::forward_to_deserialize_any;
+use std::convert::TryFrom;
+use std::fmt;
+
+pub(crate) const RID_TOKEN: &str = "$orientdb::ORecordID";
+
+impl de::Error for OrientError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        OrientError::Conversion(msg.to_string())
+    }
+}
+
+/// Deserialize a `T` from a value
+pub fn from_value<'de, T>(value: &'de OValue) -> OrientResult<T>
+where
+    T: Deserialize<'de>,
+{
+    T::deserialize(ValueDeserializer::new(value))
+}
+
+/// Deserialize a `T` from the fields of a document
+pub fn from_document<'de, T>(doc: &'de ODocument) -> OrientResult<T>
+where
+    T: Deserialize<'de>,
+{
+    T::deserialize(FieldsDeserializer::new(Box::new(doc.fields())))
+}
+
+/// Deserialize a `T` from the fields of a result
+pub fn from_result<'de, T>(result: &'de OResult) -> OrientResult<T>
+where
+    T: Deserialize<'de>,
+{
+    T::deserialize(FieldsDeserializer::new(Box::new(result.fields())))
+}
+
+type Fields<'de> = Box<dyn Iterator<Item = (&'de String, &'de OValue)> + 'de>;
+
+fn conversion<T>(value: &OValue, ty: &str) -> OrientResult<T> {
+    Err(OrientError::Conversion(format!(
+        "Cannot deserialize {:?} as {}",
+        value, ty
+    )))
+}
+
+/// Deserializer over a borrowed `OValue`
+pub struct ValueDeserializer<'de> {
+    value: &'de OValue,
+}
+
+impl<'de> ValueDeserializer<'de> {
+    pub fn new(value: &'de OValue) -> ValueDeserializer<'de> {
+        ValueDeserializer { value }
+    }
+}
+
+impl<'de> IntoDeserializer<'de, OrientError> for ValueDeserializer<'de> {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+impl<'de> de::Deserializer<'de> for ValueDeserializer<'de> {
+    type Error = OrientError;
+
+    fn deserialize_any<V>(self, visitor: V) -> OrientResult<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        match self.value {
+            OValue::Null => visitor.visit_unit(),
+            OValue::String(v) => visitor.visit_borrowed_str(v),
+            OValue::Boolean(v) => visitor.visit_bool(*v),
+            OValue::I8(v) => visitor.visit_i8(*v),
+            OValue::U8(v) => visitor.visit_u8(*v),
+            OValue::I16(v) => visitor.visit_i16(*v),
+            OValue::I32(v) => visitor.visit_i32(*v),
+            OValue::I64(v) => visitor.visit_i64(*v),
+            OValue::F32(v) => visitor.visit_f32(*v),
+            OValue::F64(v) => visitor.visit_f64(*v),
+            OValue::DateTime(v) => visitor.visit_string(v.to_rfc3339()),
+            OValue::Date(v) => visitor.visit_string(v.naive_utc().to_string()),
+            OValue::Document(doc) => visitor.visit_map(FieldsAccess::new(Box::new(doc.fields()))),
+            OValue::Link(rid) => RidDeserializer(rid).deserialize_any(visitor),
+            OValue::LinkList(links) | OValue::LinkSet(links) => {
+                visit_seq(links.links.iter().map(RidDeserializer), visitor)
+            }
+            OValue::RidBag(RidBag::Embedded(links)) => {
+                visit_seq(links.iter().map(RidDeserializer), visitor)
+            }
+            OValue::RidBag(RidBag::Tree(_)) => Err(OrientError::Conversion(String::from(
+                "Cannot deserialize a tree ridbag, iterate it with a session",
+            ))),
+            OValue::EmbeddedList(values) | OValue::EmbeddedSet(values) => {
+                visit_seq(values.iter().map(ValueDeserializer::new), visitor)
+            }
+            OValue::EmbeddedMap(map) => visitor.visit_map(FieldsAccess::new(Box::new(map.iter()))),
+            OValue::LinkMap(map) => {
+                let entries = map.iter().map(|(k, v)| (k.as_str(), RidDeserializer(v)));
+                let mut access = MapDeserializer::new(entries);
+                let value = visitor.visit_map(&mut access)?;
+                access.end()?;
+                Ok(value)
+            }
+            OValue::Decimal(v) => visitor.visit_string(v.to_string()),
+            OValue::Binary(v) | OValue::Custom(_, v) => visitor.visit_borrowed_bytes(v),
+            #[cfg(feature = "uuid")]
+            OValue::Uuid(v) => visitor.visit_string(v.to_string()),
+        }
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> OrientResult<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        match self.value {
+            OValue::Null => visitor.visit_none(),
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> OrientResult<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> OrientResult<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        match self.value {
+            OValue::String(variant) => visitor.visit_enum(variant.as_str().into_deserializer()),
+            OValue::EmbeddedMap(map) if map.len() == 1 => {
+                visitor.visit_enum(EnumAccess::new(Box::new(map.iter())))
+            }
+            OValue::Document(doc) if doc.len() == 1 => {
+                visitor.visit_enum(EnumAccess::new(Box::new(doc.fields())))
+            }
+            _ => conversion(self.value, "enum"),
+        }
+    }
+
+    fn deserialize_i128<V>(self, visitor: V) -> OrientResult<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_any(visitor)
+    }
+
+    fn deserialize_u128<V>(self, visitor: V) -> OrientResult<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_any(visitor)
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 u8 u16 u32 u64 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple
+        tuple_struct map struct identifier ignored_any
+    }
+}
+
+fn visit_seq<'de, I, D, V>(iter: I, visitor: V) -> OrientResult<V::Value>
+where
+    I: Iterator<Item = D>,
+    D: IntoDeserializer<'de, OrientError>,
+    V: Visitor<'de>,
+{
+    let mut access = SeqDeserializer::new(iter);
+    let value = visitor.visit_seq(&mut access)?;
+    access.end()?;
+    Ok(value)
+}
+
+/// Deserializer over the fields of a document or a result, seen as a map
+struct FieldsDeserializer<'de> {
+    fields: Fields<'de>,
+}
+
+impl<'de> FieldsDeserializer<'de> {
+    fn new(fields: Fields<'de>) -> FieldsDeserializer<'de> {
+        FieldsDeserializer { fields }
+    }
+}
+
+impl<'de> de::Deserializer<'de> for FieldsDeserializer<'de> {
+    type Error = OrientError;
+
+    fn deserialize_any<V>(self, visitor: V) -> OrientResult<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_map(FieldsAccess::new(self.fields))
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> OrientResult<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> OrientResult<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+struct FieldsAccess<'de> {
+    fields: Fields<'de>,
+    value: Option<&'de OValue>,
+}
+
+impl<'de> FieldsAccess<'de> {
+    fn new(fields: Fields<'de>) -> FieldsAccess<'de> {
+        FieldsAccess {
+            fields,
+            value: None,
+        }
+    }
+}
+
+impl<'de> de::MapAccess<'de> for FieldsAccess<'de> {
+    type Error = OrientError;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> OrientResult<Option<K::Value>>
+    where
+        K: de::DeserializeSeed<'de>,
+    {
+        match self.fields.next() {
+            Some((key, value)) => {
+                self.value = Some(value);
+                seed.deserialize(de::value::BorrowedStrDeserializer::<OrientError>::new(key))
+                    .map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> OrientResult<V::Value>
+    where
+        V: de::DeserializeSeed<'de>,
+    {
+        match self.value.take() {
+            Some(value) => seed.deserialize(ValueDeserializer::new(value)),
+            None => Err(OrientError::Conversion(String::from(
+                "Value requested before its key",
+            ))),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        match self.fields.size_hint() {
+            (lower, Some(upper)) if lower == upper => Some(upper),
+            _ => None,
+        }
+    }
+}
+
+struct EnumAccess<'de> {
+    fields: Fields<'de>,
+}
+
+impl<'de> EnumAccess<'de> {
+    fn new(fields: Fields<'de>) -> EnumAccess<'de> {
+        EnumAccess { fields }
+    }
+}
+
+impl<'de> de::EnumAccess<'de> for EnumAccess<'de> {
+    type Error = OrientError;
+    type Variant = VariantAccess<'de>;
+
+    fn variant_seed<V>(mut self, seed: V) -> OrientResult<(V::Value, VariantAccess<'de>)>
+    where
+        V: de::DeserializeSeed<'de>,
+    {
+        match self.fields.next() {
+            Some((variant, value)) => {
+                let variant = seed.deserialize(
+                    de::value::BorrowedStrDeserializer::<OrientError>::new(variant),
+                )?;
+                Ok((variant, VariantAccess { value }))
+            }
+            None => Err(OrientError::Conversion(String::from(
+                "Cannot deserialize an enum from an empty map",
+            ))),
+        }
+    }
+}
+
+struct VariantAccess<'de> {
+    value: &'de OValue,
+}
+
+impl<'de> de::VariantAccess<'de> for VariantAccess<'de> {
+    type Error = OrientError;
+
+    fn unit_variant(self) -> OrientResult<()> {
+        match self.value {
+            OValue::Null => Ok(()),
+            _ => conversion(self.value, "unit variant"),
+        }
+    }
+
+    fn newtype_variant_seed<T>(self, seed: T) -> OrientResult<T::Value>
+    where
+        T: de::DeserializeSeed<'de>,
+    {
+        seed.deserialize(ValueDeserializer::new(self.value))
+    }
+
+    fn tuple_variant<V>(self, _len: usize, visitor: V) -> OrientResult<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        de::Deserializer::deserialize_seq(ValueDeserializer::new(self.value), visitor)
+    }
+
+    fn struct_variant<V>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> OrientResult<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        de::Deserializer::deserialize_map(ValueDeserializer::new(self.value), visitor)
+    }
+}
+
+/// Deserializer over a link, seen as a `#cluster:position` string
+struct RidDeserializer<'de>(&'de ORecordID);
+
+impl<'de> IntoDeserializer<'de, OrientError> for RidDeserializer<'de> {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+impl<'de> de::Deserializer<'de> for RidDeserializer<'de> {
+    type Error = OrientError;
+
+    fn deserialize_any<V>(self, visitor: V) -> OrientResult<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_string(self.0.to_string())
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> OrientResult<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> OrientResult<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+impl<'de> Deserialize<'de> for ORecordID {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        struct RidVisitor;
+
+        impl<'de> Visitor<'de> for RidVisitor {
+            type Value = ORecordID;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a record id in the #cluster:position form")
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<ORecordID, E> {
+                ORecordID::try_from(v).map_err(E::custom)
+            }
+
+            fn visit_newtype_struct<D>(self, deserializer: D) -> Result<ORecordID, D::Error>
+            where
+                D: de::Deserializer<'de>,
+            {
+                deserializer.deserialize_str(self)
+            }
+        }
+
+        deserializer.deserialize_newtype_struct(RID_TOKEN, RidVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{from_document, from_result, from_value};
+    use crate::common::types::decimal::ODecimal;
+    use crate::common::types::document::ODocument;
+    use crate::common::types::result::OResult;
+    use crate::common::types::rid::ORecordID;
+    use crate::common::types::value::OValue;
+    use serde::Deserialize;
+    use std::collections::HashMap;
+    use std::convert::TryFrom;
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Address {
+        street: String,
+        number: Option<i32>,
+    }
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    enum Role {
+        Admin,
+        Guest(String),
+        User { level: i64 },
+    }
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Person {
+        name: String,
+        age: i32,
+        nick: Option<String>,
+        address: Address,
+        tags: Vec<String>,
+        scores: HashMap<String, i64>,
+        roles: Vec<Role>,
+        friend: ORecordID,
+    }
+
+    #[test]
+    fn de_nested_document() {
+        let mut address = ODocument::new("Address");
+        address.set("street", "Main Street");
+        address.set_raw("number", OValue::Null);
+
+        let mut scores = HashMap::new();
+        scores.insert(String::from("math"), OValue::I64(10));
+
+        let mut user = HashMap::new();
+        user.insert(String::from("level"), OValue::I64(3));
+        let mut role = HashMap::new();
+        role.insert(String::from("User"), OValue::EmbeddedMap(user));
+        let mut guest = HashMap::new();
+        guest.insert(String::from("Guest"), OValue::String(String::from("x")));
+
+        let mut doc = ODocument::new("Person");
+        doc.set("name", "John");
+        doc.set("age", 30);
+        doc.set_raw("nick", OValue::Null);
+        doc.set("address", address);
+        doc.set("tags", vec!["a", "b"]);
+        doc.set_raw("scores", OValue::EmbeddedMap(scores));
+        doc.set_raw(
+            "roles",
+            OValue::EmbeddedList(vec![
+                OValue::String(String::from("Admin")),
+                OValue::EmbeddedMap(guest),
+                OValue::EmbeddedMap(role),
+            ]),
+        );
+        doc.set("friend", ORecordID::new(10, 2));
+
+        let person: Person = from_document(&doc).unwrap();
+        let mut scores = HashMap::new();
+        scores.insert(String::from("math"), 10);
+        assert_eq!(
+            Person {
+                name: String::from("John"),
+                age: 30,
+                nick: None,
+                address: Address {
+                    street: String::from("Main Street"),
+                    number: None,
+                },
+                tags: vec![String::from("a"), String::from("b")],
+                scores,
+                roles: vec![
+                    Role::Admin,
+                    Role::Guest(String::from("x")),
+                    Role::User { level: 3 }
+                ],
+                friend: ORecordID::new(10, 2),
+            },
+            person
+        );
+    }
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Record {
+        #[serde(rename = "@rid")]
+        id: ORecordID,
+        #[serde(rename = "@class")]
+        class: String,
+        #[serde(rename = "@version")]
+        version: i32,
+        street: String,
+    }
+
+    #[test]
+    fn de_result_metadata() {
+        let mut doc = ODocument::new("Address");
+        doc.set_record_id(ORecordID::new(10, 1));
+        doc.set_version(3);
+        doc.set("street", "Main Street");
+
+        let result = OResult::try_from((1, doc)).unwrap();
+        assert_eq!(
+            Record {
+                id: ORecordID::new(10, 1),
+                class: String::from("Address"),
+                version: 3,
+                street: String::from("Main Street"),
+            },
+            from_result(&result).unwrap()
+        );
+
+        // A field shadows the metadata entry of the same name
+        let mut doc = ODocument::new("Address");
+        doc.set_record_id(ORecordID::new(10, 1));
+        doc.set("street", "Main Street");
+        doc.set("@version", 7);
+        let result = OResult::try_from((1, doc)).unwrap();
+        assert_eq!(7, from_result::<Record>(&result).unwrap().version);
+    }
+
+    #[test]
+    fn de_values() {
+        assert_eq!(
+            "#10:2",
+            from_value::<String>(&OValue::Link(ORecordID::new(10, 2))).unwrap()
+        );
+        assert_eq!(
+            vec![ORecordID::new(10, 1)],
+            from_value::<Vec<ORecordID>>(&OValue::LinkList(vec![ORecordID::new(10, 1)].into()))
+                .unwrap()
+        );
+        assert_eq!(
+            "-1.05",
+            from_value::<String>(&OValue::Decimal(ODecimal::new(vec![0x97], 2))).unwrap()
+        );
+        assert_eq!(
+            "1500",
+            from_value::<String>(&OValue::Decimal(ODecimal::new(vec![15], -2))).unwrap()
+        );
+        assert!(from_value::<i32>(&OValue::String(String::from("1"))).is_err());
+    }
+}
@@ -0,0 +1,298 @@
+use crate::common::types::value::{FromOValue, IntoOValue, OValue};
+use crate::common::{OrientError, OrientResult};
+use std::fmt;
+
+#[cfg(feature = "rust_decimal")]
+use std::convert::TryFrom;
+
+/// Arbitrary precision decimal as stored by the server.
+///
+/// The value is `unscaled * 10^-scale`, with `unscaled` a big-endian two's complement integer.
+/// Enable the `rust_decimal` or `bigdecimal` feature to convert it to a numeric type
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub struct ODecimal {
+    unscaled: Vec<u8>,
+    scale: i32,
+}
+
+impl ODecimal {
+    pub fn new(unscaled: Vec<u8>, scale: i32) -> ODecimal {
+        ODecimal {
+            unscaled: minimal(unscaled),
+            scale,
+        }
+    }
+
+    pub fn unscaled(&self) -> &[u8] {
+        &self.unscaled
+    }
+
+    pub fn scale(&self) -> i32 {
+        self.scale
+    }
+}
+
+// Drop the leading bytes that only repeat the sign
+fn minimal(mut bytes: Vec<u8>) -> Vec<u8> {
+    let redundant = bytes
+        .windows(2)
+        .take_while(|w| (w[0] == 0x00 && w[1] < 0x80) || (w[0] == 0xFF && w[1] >= 0x80))
+        .count();
+    bytes.drain(..redundant);
+    if bytes.is_empty() {
+        bytes.push(0);
+    }
+    bytes
+}
+
+// Padding zeros written in plain notation, an untrusted scale beyond it
+// switches to the exponent notation of the server's `BigDecimal::toString`
+const MAX_PADDING: i64 = 64;
+
+// Plain notation, every digit of the unscaled value is kept
+impl fmt::Display for ODecimal {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let negative = self.unscaled[0] >= 0x80;
+        let mut magnitude = self.unscaled.clone();
+        if negative {
+            // two's complement negation
+            let mut carry = true;
+            for byte in magnitude.iter_mut().rev() {
+                let (value, overflow) = (!*byte).overflowing_add(carry as u8);
+                *byte = value;
+                carry = overflow;
+            }
+        }
+        let mut digits = Vec::new();
+        while magnitude.iter().any(|b| *b != 0) {
+            let mut remainder = 0u32;
+            for byte in magnitude.iter_mut() {
+                let current = (remainder << 8) | u32::from(*byte);
+                *byte = (current / 10) as u8;
+                remainder = current % 10;
+            }
+            digits.push(b'0' + remainder as u8);
+        }
+        if digits.is_empty() {
+            digits.push(b'0');
+        }
+        digits.reverse();
+        let digits = std::str::from_utf8(&digits).map_err(|_| fmt::Error)?;
+        if negative {
+            f.write_str("-")?;
+        }
+        let scale = i64::from(self.scale);
+        // digits before the decimal point, negative when zeros follow the point
+        let point = digits.len() as i64 - scale;
+        if scale <= 0 && -scale <= MAX_PADDING {
+            write!(f, "{}{:0<width$}", digits, "", width = -scale as usize)
+        } else if scale > 0 && point > 0 {
+            let (int, frac) = digits.split_at(point as usize);
+            write!(f, "{}.{}", int, frac)
+        } else if scale > 0 && -point <= MAX_PADDING {
+            write!(f, "0.{:0>width$}", digits, width = scale as usize)
+        } else {
+            let (first, rest) = digits.split_at(1);
+            f.write_str(first)?;
+            if !rest.is_empty() {
+                write!(f, ".{}", rest)?;
+            }
+            write!(f, "E{:+}", point - 1)
+        }
+    }
+}
+
+impl From<i64> for ODecimal {
+    fn from(value: i64) -> ODecimal {
+        ODecimal::new(value.to_be_bytes().to_vec(), 0)
+    }
+}
+
+impl IntoOValue for ODecimal {
+    fn into_ovalue(&self) -> OValue {
+        OValue::Decimal(self.clone())
+    }
+}
+
+impl FromOValue for ODecimal {
+    fn from_value(ty: &OValue) -> OrientResult<Self>
+    where
+        Self: Sized,
+    {
+        match ty {
+            OValue::Decimal(val) => Ok(val.clone()),
+            _ => Err(OrientError::Conversion(format!(
+                "Cannot convert {:?} to ODecimal",
+                ty
+            ))),
+        }
+    }
+}
+
+#[cfg(feature = "rust_decimal")]
+impl From<rust_decimal::Decimal> for ODecimal {
+    fn from(value: rust_decimal::Decimal) -> ODecimal {
+        ODecimal::new(
+            value.mantissa().to_be_bytes().to_vec(),
+            value.scale() as i32,
+        )
+    }
+}
+
+#[cfg(feature = "rust_decimal")]
+impl TryFrom<&ODecimal> for rust_decimal::Decimal {
+    type Error = OrientError;
+
+    fn try_from(value: &ODecimal) -> OrientResult<Self> {
+        let error = || OrientError::Conversion(format!("Cannot convert {:?} to Decimal", value));
+        if value.unscaled.len() > 16 {
+            return Err(error());
+        }
+        let fill = if value.unscaled[0] >= 0x80 {
+            0xFF
+        } else {
+            0x00
+        };
+        let mut bytes = [fill; 16];
+        bytes[16 - value.unscaled.len()..].copy_from_slice(&value.unscaled);
+        let mut mantissa = i128::from_be_bytes(bytes);
+        let mut scale = value.scale;
+        while scale < 0 {
+            mantissa = mantissa.checked_mul(10).ok_or_else(error)?;
+            scale += 1;
+        }
+        rust_decimal::Decimal::try_from_i128_with_scale(mantissa, scale as u32).map_err(|_| error())
+    }
+}
+
+#[cfg(feature = "rust_decimal")]
+impl IntoOValue for rust_decimal::Decimal {
+    fn into_ovalue(&self) -> OValue {
+        OValue::Decimal(ODecimal::from(*self))
+    }
+}
+
+#[cfg(feature = "rust_decimal")]
+impl FromOValue for rust_decimal::Decimal {
+    fn from_value(ty: &OValue) -> OrientResult<Self>
+    where
+        Self: Sized,
+    {
+        match ty {
+            OValue::Decimal(val) => rust_decimal::Decimal::try_from(val),
+            _ => Err(OrientError::Conversion(format!(
+                "Cannot convert {:?} to Decimal",
+                ty
+            ))),
+        }
+    }
+}
+
+#[cfg(feature = "bigdecimal")]
+impl From<&bigdecimal::BigDecimal> for ODecimal {
+    fn from(value: &bigdecimal::BigDecimal) -> ODecimal {
+        let (_, exponent) = value.as_bigint_and_exponent();
+        // The server keeps the scale in an i32, round what does not fit
+        let clamped = exponent.clamp(i64::from(i32::MIN), i64::from(i32::MAX));
+        let (unscaled, scale) = value.with_scale(clamped).as_bigint_and_exponent();
+        ODecimal::new(unscaled.to_signed_bytes_be(), scale as i32)
+    }
+}
+
+#[cfg(feature = "bigdecimal")]
+impl From<&ODecimal> for bigdecimal::BigDecimal {
+    fn from(value: &ODecimal) -> bigdecimal::BigDecimal {
+        let unscaled = bigdecimal::num_bigint::BigInt::from_signed_bytes_be(&value.unscaled);
+        bigdecimal::BigDecimal::new(unscaled, i64::from(value.scale))
+    }
+}
+
+#[cfg(feature = "bigdecimal")]
+impl IntoOValue for bigdecimal::BigDecimal {
+    fn into_ovalue(&self) -> OValue {
+        OValue::Decimal(ODecimal::from(self))
+    }
+}
+
+#[cfg(feature = "bigdecimal")]
+impl FromOValue for bigdecimal::BigDecimal {
+    fn from_value(ty: &OValue) -> OrientResult<Self>
+    where
+        Self: Sized,
+    {
+        match ty {
+            OValue::Decimal(val) => Ok(bigdecimal::BigDecimal::from(val)),
+            _ => Err(OrientError::Conversion(format!(
+                "Cannot convert {:?} to BigDecimal",
+                ty
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ODecimal;
+
+    #[test]
+    fn decimal_minimal_bytes() {
+        assert_eq!(&[0], ODecimal::from(0).unscaled());
+        assert_eq!(&[0x7F], ODecimal::from(127).unscaled());
+        assert_eq!(&[0x00, 0x80], ODecimal::from(128).unscaled());
+        assert_eq!(&[0xFF], ODecimal::from(-1).unscaled());
+        assert_eq!(&[0xFF, 0x7F], ODecimal::from(-129).unscaled());
+        assert_eq!(ODecimal::new(vec![0, 0, 1], 2), ODecimal::new(vec![1], 2));
+    }
+
+    #[test]
+    fn decimal_display() {
+        assert_eq!("0", ODecimal::from(0).to_string());
+        assert_eq!("-1.05", ODecimal::new(vec![0x97], 2).to_string());
+        assert_eq!("0.007", ODecimal::new(vec![7], 3).to_string());
+        assert_eq!("1500", ODecimal::new(vec![15], -2).to_string());
+        assert_eq!("0.0000001", ODecimal::new(vec![1], 7).to_string());
+        assert_eq!("1.5E+66", ODecimal::new(vec![15], -65).to_string());
+        assert_eq!(
+            "1.5E-2147483646",
+            ODecimal::new(vec![15], i32::MAX).to_string()
+        );
+        assert_eq!(
+            "-1E+2147483648",
+            ODecimal::new(vec![0xFF], i32::MIN).to_string()
+        );
+        assert_eq!("-9223372036854775808", ODecimal::from(i64::MIN).to_string());
+        assert_eq!(
+            "340282366920938463463374607431768211456",
+            ODecimal::new(vec![1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0], 0).to_string()
+        );
+    }
+
+    #[cfg(feature = "rust_decimal")]
+    #[test]
+    fn decimal_rust_decimal_conversion() {
+        use std::convert::TryFrom;
+
+        let value = rust_decimal::Decimal::new(-123456, 3);
+        let decimal = ODecimal::from(value);
+        assert_eq!(3, decimal.scale());
+        assert_eq!(value, rust_decimal::Decimal::try_from(&decimal).unwrap());
+
+        let scaled = ODecimal::new(vec![5], -2);
+        assert_eq!(
+            rust_decimal::Decimal::new(500, 0),
+            rust_decimal::Decimal::try_from(&scaled).unwrap()
+        );
+        assert!(rust_decimal::Decimal::try_from(&ODecimal::new(vec![1; 17], 0)).is_err());
+    }
+
+    #[cfg(feature = "bigdecimal")]
+    #[test]
+    fn decimal_bigdecimal_conversion() {
+        use std::str::FromStr;
+
+        let value = bigdecimal::BigDecimal::from_str("-98765432109876543210.0123").unwrap();
+        let decimal = ODecimal::from(&value);
+        assert_eq!(4, decimal.scale());
+        assert_eq!(value, bigdecimal::BigDecimal::from(&decimal));
+    }
+}
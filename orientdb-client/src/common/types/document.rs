@@ -67,9 +67,15 @@ impl ODocument {
         }
     }
 
+    pub fn record_id(&self) -> &ORecordID {
+        &self.record_id
+    }
     pub fn set_record_id(&mut self, record_id: ORecordID) {
         self.record_id = record_id;
     }
+    pub fn version(&self) -> i32 {
+        self.version
+    }
     pub fn set_version(&mut self, version: i32) {
         self.version = version
     }
@@ -129,7 +135,7 @@ impl ODocument {
 
     pub fn get_str(&self, name: &str) -> Option<&str> {
         match self.fields.get(name) {
-            Some(&OValue::String(ref v)) => Some(v),
+            Some(OValue::String(v)) => Some(v),
             Some(_) => None,
             None => None,
         }
@@ -149,12 +155,36 @@ impl ODocument {
         }
     }
 
-    pub fn iter(&self) -> Iter<String, OValue> {
+    pub fn iter(&self) -> Iter<'_, String, OValue> {
         Iter {
             inner: self.fields.iter(),
         }
     }
 
+    #[cfg(feature = "serde")]
+    pub(crate) fn fields(&self) -> hash_map::Iter<'_, String, OValue> {
+        self.fields.iter()
+    }
+
+    /// Build a document from a type implementing `serde::Serialize`,
+    /// the class of the document is the name of the struct
+    #[cfg(feature = "serde")]
+    pub fn from_serialize<T>(value: &T) -> OrientResult<ODocument>
+    where
+        T: serde::Serialize + ?Sized,
+    {
+        crate::common::types::ser::to_document(value)
+    }
+
+    /// Deserialize the fields of the document into a type implementing `serde::Deserialize`
+    #[cfg(feature = "serde")]
+    pub fn deserialize<'de, T>(&'de self) -> OrientResult<T>
+    where
+        T: serde::Deserialize<'de>,
+    {
+        crate::common::types::de::from_document(self)
+    }
+
     pub fn len(&self) -> usize {
         self.fields.len()
     }
@@ -164,6 +194,41 @@ impl ODocument {
     }
 }
 
+/// Conversion to a document, used to save records and bind named parameters
+#[allow(clippy::wrong_self_convention)]
+pub trait IntoDocument: Send + Sync {
+    fn into_document(&self) -> ODocument;
+}
+
+impl IntoDocument for ODocument {
+    fn into_document(&self) -> ODocument {
+        self.clone()
+    }
+}
+
+// Named parameters given as pairs of name and value
+impl<T> IntoDocument for [(&str, &T)]
+where
+    T: IntoOValue + ?Sized,
+{
+    fn into_document(&self) -> ODocument {
+        let mut doc = ODocument::empty();
+        for (name, value) in self {
+            doc.set_raw(*name, value.into_ovalue());
+        }
+        doc
+    }
+}
+
+impl<T, const N: usize> IntoDocument for [(&str, &T); N]
+where
+    T: IntoOValue + ?Sized,
+{
+    fn into_document(&self) -> ODocument {
+        self[..].into_document()
+    }
+}
+
 pub struct Iter<'a, K: 'a, V: 'a> {
     inner: hash_map::Iter<'a, K, V>,
 }
@@ -204,7 +269,7 @@ mod tests {
             String::from("John"),
             doc.get_checked::<String>("name").unwrap()
         );
-        assert_eq!(true, doc.get_checked("confirmed").unwrap());
+        assert!(doc.get_checked::<bool>("confirmed").unwrap());
 
         let checked: OrientResult<bool> = doc.get_checked("confirmed1");
 
@@ -219,7 +284,7 @@ mod tests {
 
         assert_eq!(20, doc.get("age"));
         assert_eq!(String::from("John"), doc.get::<String>("name"));
-        assert_eq!(true, doc.get("confirmed"));
+        assert!(doc.get::<bool>("confirmed"));
     }
 
     #[test]
@@ -249,7 +314,7 @@ mod tests {
 
         let mut doc = ODocument::new("Test");
         let uuid = Uuid::new_v4();
-        doc.set("uuid", uuid.clone());
+        doc.set("uuid", uuid);
 
         assert_eq!(Some(&OValue::Uuid(uuid)), doc.get_raw("uuid"));
     }
@@ -2,6 +2,7 @@ use std::error;
 use std::fmt;
 use std::io;
 use std::string::FromUtf8Error;
+use std::time::Duration;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -10,6 +11,8 @@ pub enum OrientError {
     Io(#[from] io::Error),
     #[error("Request error: {}", .0.errors[0].err_msg)]
     Request(RequestError),
+    #[error("Concurrent modification: {}", .0.errors[0].err_msg)]
+    ConcurrentModification(RequestError),
     #[error("Protocol error: {0}")]
     Protocol(String),
     #[error("Decoder error: {0}")]
@@ -24,11 +27,57 @@ pub enum OrientError {
     Conversion(String),
     #[error("Generic error: {0}")]
     Generic(String),
+    #[error("Live query buffer overflow, the subscriber was disconnected")]
+    LiveOverflow,
+    #[error("Timed out {0}")]
+    Timeout(String),
+    #[cfg(feature = "tls")]
+    #[error("TLS error: {0}")]
+    Tls(String),
     #[cfg(feature = "async")]
     #[error("Channel send error: {0}")]
     ChannelSend(#[from] futures::channel::mpsc::SendError),
 }
 
+impl From<RequestError> for OrientError {
+    fn from(err: RequestError) -> OrientError {
+        // Code 3 is reported by the server for MVCC conflicts
+        if err.code == 3 {
+            OrientError::ConcurrentModification(err)
+        } else {
+            OrientError::Request(err)
+        }
+    }
+}
+
+impl OrientError {
+    // Errors that tell the server cannot be reached
+    pub(crate) fn is_connection_error(&self) -> bool {
+        matches!(
+            self,
+            OrientError::Io(_) | OrientError::Pool(_) | OrientError::Timeout(_)
+        )
+    }
+
+    // Errors that tell the server does not know the session anymore,
+    // its token expired or was issued before a restart, or the session id
+    // of a session without token was lost by a restart
+    pub(crate) fn is_session_expired(&self) -> bool {
+        match self {
+            OrientError::Request(err) => err.errors.iter().any(|e| {
+                e.err_type.ends_with("OTokenSecurityException")
+                    || (e.err_type.ends_with("OIOException")
+                        && e.err_msg.starts_with("Found unknown session"))
+            }),
+            _ => false,
+        }
+    }
+
+    pub(crate) fn timeout(what: &str, after: Duration) -> OrientError {
+        OrientError::Timeout(format!("{} after {:?}", what, after))
+    }
+}
+
 #[derive(Default)]
 pub struct RequestError {
     pub session_id: i32,
@@ -1,4 +1,5 @@
 use crate::types::OResult;
+use std::time::SystemTime;
 
 #[derive(Debug)]
 pub enum LiveResult {
@@ -6,3 +7,55 @@ pub enum LiveResult {
     Updated((OResult, OResult)),
     Deleted(OResult),
 }
+
+/// An event of a live query, along with the live query it belongs to
+/// and the time its push was received
+#[derive(Debug)]
+pub struct LiveEvent {
+    monitor_id: i32,
+    received_at: SystemTime,
+    result: LiveResult,
+}
+
+impl LiveEvent {
+    pub(crate) fn new(monitor_id: i32, received_at: SystemTime, result: LiveResult) -> LiveEvent {
+        LiveEvent {
+            monitor_id,
+            received_at,
+            result,
+        }
+    }
+
+    /// Id of the live query on the server
+    pub fn monitor_id(&self) -> i32 {
+        self.monitor_id
+    }
+
+    /// When the client received the push carrying the event, not when the change
+    /// happened on the server. Events pushed together share the same time
+    pub fn received_at(&self) -> SystemTime {
+        self.received_at
+    }
+
+    pub fn result(&self) -> &LiveResult {
+        &self.result
+    }
+
+    pub fn into_result(self) -> LiveResult {
+        self.result
+    }
+}
+
+/// What a live query does with an event when the buffer of its subscriber is full
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OverflowPolicy {
+    /// Wait for the subscriber to make room, stalling the connection of the live query
+    #[default]
+    Block,
+    /// Drop the oldest buffered event to make room for the new one
+    DropOldest,
+    /// Drop the new event
+    DropNewest,
+    /// End the subscription with `OrientError::LiveOverflow` after the buffered events
+    Disconnect,
+}
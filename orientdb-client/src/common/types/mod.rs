@@ -1,13 +1,23 @@
 pub mod bag;
+#[cfg(feature = "serde")]
+pub mod de;
+pub mod decimal;
 pub mod document;
 pub mod error;
 pub mod live;
 pub mod projection;
+pub mod push;
 pub mod result;
 pub mod rid;
+#[cfg(feature = "serde")]
+pub mod ser;
+pub mod transaction;
 pub mod value;
 
-pub use self::document::ODocument;
-pub use self::live::LiveResult;
+pub use self::decimal::ODecimal;
+pub use self::document::{IntoDocument, ODocument};
+pub use self::live::{LiveEvent, LiveResult, OverflowPolicy};
 pub use self::projection::Projection;
+pub use self::push::{PushEvent, PushKind};
 pub use self::result::OResult;
+pub use self::transaction::TransactionResult;
@@ -1,16 +1,19 @@
 use crate::common::types::value::OValue;
 use std::collections::HashMap;
-use std::ops::{Deref, DerefMut};
+use std::ops::Deref;
 
 #[derive(Debug, PartialEq, Clone, Default)]
 pub struct Projection {
     fields: HashMap<String, OValue>,
+    // Columns in the order sent by the server
+    columns: Vec<String>,
+    metadata: HashMap<String, OValue>,
 }
 
 impl Projection {
     pub fn as_str(&self, name: &str) -> Option<&str> {
         match self.fields.get(name) {
-            Some(&OValue::String(ref v)) => Some(v),
+            Some(OValue::String(v)) => Some(v),
             Some(_) => None,
             None => None,
         }
@@ -19,6 +22,35 @@ impl Projection {
     pub fn take_map(self) -> HashMap<String, OValue> {
         self.fields
     }
+
+    /// Add a column at the end of the projection
+    pub fn push(&mut self, column: String, value: OValue) {
+        if self.fields.insert(column.clone(), value).is_none() {
+            self.columns.push(column);
+        }
+    }
+
+    pub fn metadata(&self) -> &HashMap<String, OValue> {
+        &self.metadata
+    }
+
+    /// Set a metadata entry of the projection, e.g. `@rid` or `@version`
+    pub fn set_metadata(&mut self, name: String, value: OValue) {
+        self.metadata.insert(name, value);
+    }
+
+    pub(crate) fn take_metadata(&mut self) -> HashMap<String, OValue> {
+        std::mem::take(&mut self.metadata)
+    }
+
+    /// Value of the column at `index`, in the order of the projection
+    pub fn get_at(&self, index: usize) -> Option<&OValue> {
+        self.columns
+            .iter()
+            .filter(|column| self.fields.contains_key(*column))
+            .nth(index)
+            .and_then(|column| self.fields.get(column))
+    }
 }
 
 impl Deref for Projection {
@@ -28,9 +60,3 @@ impl Deref for Projection {
         &self.fields
     }
 }
-
-impl DerefMut for Projection {
-    fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut self.fields
-    }
-}
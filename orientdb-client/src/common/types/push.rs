@@ -0,0 +1,79 @@
+use crate::types::rid::ORecordID;
+use crate::types::ODocument;
+use std::collections::HashMap;
+
+/// Kinds of database changes the server can push to a subscribed session
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PushKind {
+    StorageConfiguration,
+    Schema,
+    IndexManager,
+    Functions,
+    Sequences,
+}
+
+impl PushKind {
+    // Code of the kind in the subscribe request
+    pub(crate) fn code(&self) -> i8 {
+        match self {
+            PushKind::StorageConfiguration => 3,
+            PushKind::Schema => 4,
+            PushKind::IndexManager => 5,
+            PushKind::Functions => 6,
+            PushKind::Sequences => 7,
+        }
+    }
+}
+
+/// A change pushed by the server after a subscription with `PushKind`
+#[derive(Debug)]
+pub enum PushEvent {
+    StorageConfiguration(Box<StorageConfiguration>),
+    /// The new schema record
+    Schema(ODocument),
+    /// The new index manager record
+    IndexManager(ODocument),
+    /// Functions changed, they have to be loaded again
+    Functions,
+    /// Sequences changed, they have to be loaded again
+    Sequences,
+}
+
+impl PushEvent {
+    pub fn kind(&self) -> PushKind {
+        match self {
+            PushEvent::StorageConfiguration(_) => PushKind::StorageConfiguration,
+            PushEvent::Schema(_) => PushKind::Schema,
+            PushEvent::IndexManager(_) => PushKind::IndexManager,
+            PushEvent::Functions => PushKind::Functions,
+            PushEvent::Sequences => PushKind::Sequences,
+        }
+    }
+}
+
+/// Configuration of the database storage
+#[derive(Debug)]
+pub struct StorageConfiguration {
+    pub date_format: String,
+    pub date_time_format: String,
+    pub name: String,
+    pub version: i32,
+    pub directory: String,
+    pub properties: HashMap<String, String>,
+    pub schema_record_id: ORecordID,
+    pub index_manager_record_id: ORecordID,
+    pub cluster_selection: String,
+    pub conflict_strategy: String,
+    pub validation_enabled: bool,
+    pub locale_language: String,
+    pub minimum_clusters: i32,
+    pub strict_sql: bool,
+    pub charset: String,
+    pub time_zone: String,
+    pub locale_country: String,
+    pub record_serializer: String,
+    pub record_serializer_version: i32,
+    pub binary_format_version: i32,
+    /// Ids and names of the clusters
+    pub clusters: Vec<(i32, String)>,
+}
@@ -1,9 +1,14 @@
 use super::document::ODocument;
 use super::projection::Projection;
+use crate::common::types::rid::ORecordID;
 use crate::common::types::value::{FromOValue, OValue};
 use crate::common::{OrientError, OrientResult};
+#[cfg(feature = "serde")]
+use std::collections::hash_map;
+use std::collections::HashMap;
+use std::convert::TryFrom;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum ResultType {
     Document(ODocument),
     Projection(Projection),
@@ -23,18 +28,33 @@ impl ResultType {
             ResultType::Projection(ref p) => p.as_str(name),
         }
     }
+
+    pub fn get_at(&self, index: usize) -> Option<&OValue> {
+        match self {
+            ResultType::Document(_) => None,
+            ResultType::Projection(ref p) => p.get_at(index),
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    pub(crate) fn fields(&self) -> hash_map::Iter<'_, String, OValue> {
+        match self {
+            ResultType::Document(ref d) => d.fields(),
+            ResultType::Projection(ref p) => p.iter(),
+        }
+    }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct OResult {
     inner: ResultType,
+    // @rid, @class and @version of documents, the metadata section of projections
+    metadata: HashMap<String, OValue>,
 }
 
 impl OResult {
     pub fn empty() -> OResult {
-        OResult {
-            inner: ResultType::Projection(Projection::default()),
-        }
+        OResult::from(Projection::default())
     }
 
     pub fn get_checked<T>(&self, name: &str) -> OrientResult<T>
@@ -51,7 +71,7 @@ impl OResult {
     where
         T: FromOValue,
     {
-        let value = self.get_raw(name).unwrap_or_else(|| &OValue::Null);
+        let value = self.get_raw(name).unwrap_or(&OValue::Null);
         T::from_value(value)
     }
 
@@ -64,26 +84,112 @@ impl OResult {
             Err(err) => panic!("Error : {:?}", err),
         }
     }
+    /// Value of a field, falling back to the metadata of the result
     pub fn get_raw(&self, name: &str) -> Option<&OValue> {
-        self.inner.get(name)
+        self.inner.get(name).or_else(|| self.metadata.get(name))
+    }
+
+    /// Column at `index` in projection order. Documents have no column order
+    /// so positional access always fails on them.
+    pub fn get_at<T>(&self, index: usize) -> OrientResult<T>
+    where
+        T: FromOValue,
+    {
+        match self.get_raw_at(index) {
+            Some(val) => T::from_value(val),
+            None => Err(OrientError::Field(format!("Column {} not found.", index))),
+        }
+    }
+
+    pub fn get_raw_at(&self, index: usize) -> Option<&OValue> {
+        self.inner.get_at(index)
+    }
+
+    /// Record id of the document, or the `@rid` of the projection
+    pub fn rid(&self) -> Option<&ORecordID> {
+        match self.get_raw("@rid") {
+            Some(OValue::Link(rid)) => Some(rid),
+            _ => None,
+        }
+    }
+
+    /// Class of the document, or the `@class` of the projection
+    pub fn class_name(&self) -> Option<&str> {
+        match self.get_raw("@class") {
+            Some(OValue::String(class)) => Some(class),
+            _ => None,
+        }
+    }
+
+    /// Version of the document, or the `@version` of the projection
+    pub fn version(&self) -> Option<i32> {
+        match self.get_raw("@version") {
+            Some(OValue::I32(version)) => Some(*version),
+            _ => None,
+        }
+    }
+
+    pub fn metadata(&self) -> &HashMap<String, OValue> {
+        &self.metadata
+    }
+
+    /// Fields of the result followed by the metadata not shadowed by a field
+    #[cfg(feature = "serde")]
+    pub(crate) fn fields(&self) -> impl Iterator<Item = (&String, &OValue)> {
+        let metadata = self
+            .metadata
+            .iter()
+            .filter(move |(name, _)| self.inner.get(name).is_none());
+        self.inner.fields().chain(metadata)
+    }
+
+    /// Deserialize the result into a type implementing `serde::Deserialize`
+    #[cfg(feature = "serde")]
+    pub fn deserialize<'de, T>(&'de self) -> OrientResult<T>
+    where
+        T: serde::Deserialize<'de>,
+    {
+        crate::common::types::de::from_result(self)
     }
 }
 
-impl From<(i8, ODocument)> for OResult {
-    fn from(val: (i8, ODocument)) -> Self {
+impl TryFrom<(i8, ODocument)> for OResult {
+    type Error = OrientError;
+
+    fn try_from(val: (i8, ODocument)) -> OrientResult<Self> {
         match val.0 {
-            1 | 2 | 3 => OResult {
-                inner: ResultType::Document(val.1),
-            },
-            _ => panic!("Unsupported record type {}", val.0),
+            1..=3 => {
+                let doc = val.1;
+                let mut metadata = HashMap::new();
+                if !doc.record_id().is_new() {
+                    metadata.insert(String::from("@rid"), OValue::Link(doc.record_id().clone()));
+                }
+                if !doc.class_name().is_empty() {
+                    metadata.insert(
+                        String::from("@class"),
+                        OValue::String(String::from(doc.class_name())),
+                    );
+                }
+                metadata.insert(String::from("@version"), OValue::I32(doc.version()));
+                Ok(OResult {
+                    inner: ResultType::Document(doc),
+                    metadata,
+                })
+            }
+            _ => Err(OrientError::Decoder(format!(
+                "Unsupported record type {}",
+                val.0
+            ))),
         }
     }
 }
 
 impl From<Projection> for OResult {
-    fn from(projection: Projection) -> Self {
+    fn from(mut projection: Projection) -> Self {
+        let metadata = projection.take_metadata();
         OResult {
             inner: ResultType::Projection(projection),
+            metadata,
         }
     }
 }
@@ -102,3 +208,34 @@ impl FromResult for OResult {
         Ok(result)
     }
 }
+
+macro_rules! tuple_from_result {
+    ($($name:ident : $index:tt),+) => {
+        impl<$($name),+> FromResult for ($($name,)+)
+        where
+            $($name: FromOValue,)+
+        {
+            fn from_result(result: OResult) -> OrientResult<Self>
+            where
+                Self: Sized,
+            {
+                // missing columns read as null, like missing fields
+                let null = OValue::Null;
+                Ok(($($name::from_value(result.get_raw_at($index).unwrap_or(&null))?,)+))
+            }
+        }
+    };
+}
+
+tuple_from_result!(A: 0);
+tuple_from_result!(A: 0, B: 1);
+tuple_from_result!(A: 0, B: 1, C: 2);
+tuple_from_result!(A: 0, B: 1, C: 2, D: 3);
+tuple_from_result!(A: 0, B: 1, C: 2, D: 3, E: 4);
+tuple_from_result!(A: 0, B: 1, C: 2, D: 3, E: 4, F: 5);
+tuple_from_result!(A: 0, B: 1, C: 2, D: 3, E: 4, F: 5, G: 6);
+tuple_from_result!(A: 0, B: 1, C: 2, D: 3, E: 4, F: 5, G: 6, H: 7);
+tuple_from_result!(A: 0, B: 1, C: 2, D: 3, E: 4, F: 5, G: 6, H: 7, I: 8);
+tuple_from_result!(A: 0, B: 1, C: 2, D: 3, E: 4, F: 5, G: 6, H: 7, I: 8, J: 9);
+tuple_from_result!(A: 0, B: 1, C: 2, D: 3, E: 4, F: 5, G: 6, H: 7, I: 8, J: 9, K: 10);
+tuple_from_result!(A: 0, B: 1, C: 2, D: 3, E: 4, F: 5, G: 6, H: 7, I: 8, J: 9, K: 10, L: 11);
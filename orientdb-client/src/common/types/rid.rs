@@ -1,5 +1,9 @@
+use crate::common::{OrientError, OrientResult};
+use std::convert::TryFrom;
+use std::fmt;
+
 /// Document ID
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct ORecordID {
     pub cluster: i16,
     pub position: i64,
@@ -15,4 +19,33 @@ impl ORecordID {
             position: -1,
         }
     }
+
+    /// A record id is new until the server assigns it a cluster position
+    pub fn is_new(&self) -> bool {
+        self.position < 0
+    }
+}
+
+impl fmt::Display for ORecordID {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "#{}:{}", self.cluster, self.position)
+    }
+}
+
+/// Parse a record id in the `#cluster:position` form
+impl TryFrom<&str> for ORecordID {
+    type Error = OrientError;
+
+    fn try_from(value: &str) -> OrientResult<ORecordID> {
+        let error = || OrientError::Conversion(format!("Cannot parse {:?} as a record id", value));
+        let (cluster, position) = value
+            .strip_prefix('#')
+            .unwrap_or(value)
+            .split_once(':')
+            .ok_or_else(error)?;
+        Ok(ORecordID::new(
+            cluster.parse().map_err(|_| error())?,
+            position.parse().map_err(|_| error())?,
+        ))
+    }
 }
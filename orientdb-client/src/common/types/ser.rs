@@ -0,0 +1,582 @@
+//! Serialize types implementing `serde::Serialize` into `ODocument` and `OValue`.
+//!
+//! Structs become embedded documents, maps become embedded maps, sequences and tuples
+//! become embedded lists. Unit variants are written as strings and the other variants
+//! as a single entry map, the same way they are read back by the `de` module.
+
+use super::de::RID_TOKEN;
+use crate::common::types::bag::RidBag;
+use crate::common::types::document::ODocument;
+use crate::common::types::result::OResult;
+use crate::common::types::rid::ORecordID;
+use crate::common::types::value::{EmbeddedMap, OValue};
+use crate::common::{OrientError, OrientResult};
+use serde::ser::{self, Serialize, SerializeMap, SerializeSeq};
+use std::convert::TryFrom;
+use std::fmt;
+
+impl ser::Error for OrientError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        OrientError::Conversion(msg.to_string())
+    }
+}
+
+/// Serialize a `T` into a value
+pub fn to_value<T>(value: &T) -> OrientResult<OValue>
+where
+    T: Serialize + ?Sized,
+{
+    value.serialize(ValueSerializer::new())
+}
+
+/// Serialize a `T` into a document, the class of the document is the name of the struct
+pub fn to_document<T>(value: &T) -> OrientResult<ODocument>
+where
+    T: Serialize + ?Sized,
+{
+    match value.serialize(ValueSerializer { named: true })? {
+        OValue::Document(doc) => Ok(doc),
+        OValue::EmbeddedMap(map) => {
+            let mut doc = ODocument::empty();
+            for (name, value) in map {
+                doc.set_raw(name, value);
+            }
+            Ok(doc)
+        }
+        value => Err(OrientError::Conversion(format!(
+            "Cannot convert {:?} to a document",
+            value
+        ))),
+    }
+}
+
+/// Serializer producing an `OValue`
+#[derive(Default)]
+pub struct ValueSerializer {
+    // Only the root document takes the class from the struct name, embedded ones have no class
+    named: bool,
+}
+
+impl ValueSerializer {
+    pub fn new() -> ValueSerializer {
+        ValueSerializer::default()
+    }
+}
+
+impl ser::Serializer for ValueSerializer {
+    type Ok = OValue;
+    type Error = OrientError;
+
+    type SerializeSeq = SeqSerializer;
+    type SerializeTuple = SeqSerializer;
+    type SerializeTupleStruct = SeqSerializer;
+    type SerializeTupleVariant = VariantSerializer<SeqSerializer>;
+    type SerializeMap = MapSerializer;
+    type SerializeStruct = StructSerializer;
+    type SerializeStructVariant = VariantSerializer<StructSerializer>;
+
+    fn serialize_bool(self, v: bool) -> OrientResult<OValue> {
+        Ok(OValue::Boolean(v))
+    }
+
+    fn serialize_i8(self, v: i8) -> OrientResult<OValue> {
+        Ok(OValue::I8(v))
+    }
+
+    fn serialize_i16(self, v: i16) -> OrientResult<OValue> {
+        Ok(OValue::I16(v))
+    }
+
+    fn serialize_i32(self, v: i32) -> OrientResult<OValue> {
+        Ok(OValue::I32(v))
+    }
+
+    fn serialize_i64(self, v: i64) -> OrientResult<OValue> {
+        Ok(OValue::I64(v))
+    }
+
+    fn serialize_u8(self, v: u8) -> OrientResult<OValue> {
+        Ok(OValue::U8(v))
+    }
+
+    fn serialize_u16(self, v: u16) -> OrientResult<OValue> {
+        Ok(OValue::I32(i32::from(v)))
+    }
+
+    fn serialize_u32(self, v: u32) -> OrientResult<OValue> {
+        Ok(OValue::I64(i64::from(v)))
+    }
+
+    fn serialize_u64(self, v: u64) -> OrientResult<OValue> {
+        i64::try_from(v).map(OValue::I64).map_err(|_| {
+            OrientError::Conversion(format!("Cannot serialize {}, it does not fit an i64", v))
+        })
+    }
+
+    fn serialize_f32(self, v: f32) -> OrientResult<OValue> {
+        Ok(OValue::F32(v))
+    }
+
+    fn serialize_f64(self, v: f64) -> OrientResult<OValue> {
+        Ok(OValue::F64(v))
+    }
+
+    fn serialize_char(self, v: char) -> OrientResult<OValue> {
+        Ok(OValue::String(v.to_string()))
+    }
+
+    fn serialize_str(self, v: &str) -> OrientResult<OValue> {
+        Ok(OValue::String(String::from(v)))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> OrientResult<OValue> {
+        Ok(OValue::Binary(v.to_vec()))
+    }
+
+    fn serialize_none(self) -> OrientResult<OValue> {
+        Ok(OValue::Null)
+    }
+
+    fn serialize_some<T>(self, value: &T) -> OrientResult<OValue>
+    where
+        T: Serialize + ?Sized,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> OrientResult<OValue> {
+        Ok(OValue::Null)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> OrientResult<OValue> {
+        Ok(OValue::Null)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> OrientResult<OValue> {
+        Ok(OValue::String(String::from(variant)))
+    }
+
+    fn serialize_newtype_struct<T>(self, name: &'static str, value: &T) -> OrientResult<OValue>
+    where
+        T: Serialize + ?Sized,
+    {
+        match value.serialize(self)? {
+            OValue::String(rid) if name == RID_TOKEN => {
+                ORecordID::try_from(rid.as_str()).map(OValue::Link)
+            }
+            value => Ok(value),
+        }
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> OrientResult<OValue>
+    where
+        T: Serialize + ?Sized,
+    {
+        let mut map = EmbeddedMap::new();
+        map.insert(
+            String::from(variant),
+            value.serialize(ValueSerializer::new())?,
+        );
+        Ok(OValue::EmbeddedMap(map))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> OrientResult<SeqSerializer> {
+        Ok(SeqSerializer {
+            values: Vec::with_capacity(len.unwrap_or(0)),
+        })
+    }
+
+    fn serialize_tuple(self, len: usize) -> OrientResult<SeqSerializer> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> OrientResult<SeqSerializer> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> OrientResult<VariantSerializer<SeqSerializer>> {
+        Ok(VariantSerializer {
+            variant,
+            inner: self.serialize_seq(Some(len))?,
+        })
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> OrientResult<MapSerializer> {
+        Ok(MapSerializer {
+            map: EmbeddedMap::new(),
+            key: None,
+        })
+    }
+
+    fn serialize_struct(self, name: &'static str, _len: usize) -> OrientResult<StructSerializer> {
+        let class_name = if self.named { name } else { "" };
+        Ok(StructSerializer {
+            doc: ODocument::new(class_name),
+        })
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> OrientResult<VariantSerializer<StructSerializer>> {
+        Ok(VariantSerializer {
+            variant,
+            inner: StructSerializer {
+                doc: ODocument::empty(),
+            },
+        })
+    }
+}
+
+pub struct SeqSerializer {
+    values: Vec<OValue>,
+}
+
+impl ser::SerializeSeq for SeqSerializer {
+    type Ok = OValue;
+    type Error = OrientError;
+
+    fn serialize_element<T>(&mut self, value: &T) -> OrientResult<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.values.push(value.serialize(ValueSerializer::new())?);
+        Ok(())
+    }
+
+    fn end(self) -> OrientResult<OValue> {
+        Ok(OValue::EmbeddedList(self.values))
+    }
+}
+
+impl ser::SerializeTuple for SeqSerializer {
+    type Ok = OValue;
+    type Error = OrientError;
+
+    fn serialize_element<T>(&mut self, value: &T) -> OrientResult<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> OrientResult<OValue> {
+        SerializeSeq::end(self)
+    }
+}
+
+impl ser::SerializeTupleStruct for SeqSerializer {
+    type Ok = OValue;
+    type Error = OrientError;
+
+    fn serialize_field<T>(&mut self, value: &T) -> OrientResult<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> OrientResult<OValue> {
+        SerializeSeq::end(self)
+    }
+}
+
+pub struct MapSerializer {
+    map: EmbeddedMap,
+    key: Option<String>,
+}
+
+impl ser::SerializeMap for MapSerializer {
+    type Ok = OValue;
+    type Error = OrientError;
+
+    fn serialize_key<T>(&mut self, key: &T) -> OrientResult<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        match key.serialize(ValueSerializer::new())? {
+            OValue::String(key) => {
+                self.key = Some(key);
+                Ok(())
+            }
+            key => Err(OrientError::Conversion(format!(
+                "Map keys must be strings, found {:?}",
+                key
+            ))),
+        }
+    }
+
+    fn serialize_value<T>(&mut self, value: &T) -> OrientResult<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        match self.key.take() {
+            Some(key) => {
+                self.map
+                    .insert(key, value.serialize(ValueSerializer::new())?);
+                Ok(())
+            }
+            None => Err(OrientError::Conversion(String::from(
+                "Map value serialized before its key",
+            ))),
+        }
+    }
+
+    fn end(self) -> OrientResult<OValue> {
+        Ok(OValue::EmbeddedMap(self.map))
+    }
+}
+
+pub struct StructSerializer {
+    doc: ODocument,
+}
+
+impl ser::SerializeStruct for StructSerializer {
+    type Ok = OValue;
+    type Error = OrientError;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> OrientResult<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.doc
+            .set_raw(key, value.serialize(ValueSerializer::new())?);
+        Ok(())
+    }
+
+    fn end(self) -> OrientResult<OValue> {
+        Ok(OValue::Document(self.doc))
+    }
+}
+
+pub struct VariantSerializer<S> {
+    variant: &'static str,
+    inner: S,
+}
+
+impl<S> VariantSerializer<S> {
+    fn wrap(variant: &'static str, value: OValue) -> OValue {
+        let mut map = EmbeddedMap::new();
+        map.insert(String::from(variant), value);
+        OValue::EmbeddedMap(map)
+    }
+}
+
+impl ser::SerializeTupleVariant for VariantSerializer<SeqSerializer> {
+    type Ok = OValue;
+    type Error = OrientError;
+
+    fn serialize_field<T>(&mut self, value: &T) -> OrientResult<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        SerializeSeq::serialize_element(&mut self.inner, value)
+    }
+
+    fn end(self) -> OrientResult<OValue> {
+        let value = SerializeSeq::end(self.inner)?;
+        Ok(Self::wrap(self.variant, value))
+    }
+}
+
+impl ser::SerializeStructVariant for VariantSerializer<StructSerializer> {
+    type Ok = OValue;
+    type Error = OrientError;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> OrientResult<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        ser::SerializeStruct::serialize_field(&mut self.inner, key, value)
+    }
+
+    fn end(self) -> OrientResult<OValue> {
+        let value = ser::SerializeStruct::end(self.inner)?;
+        Ok(Self::wrap(self.variant, value))
+    }
+}
+
+impl Serialize for ORecordID {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        serializer.serialize_newtype_struct(RID_TOKEN, &self.to_string())
+    }
+}
+
+impl Serialize for OValue {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        match self {
+            OValue::Null => serializer.serialize_none(),
+            OValue::String(v) => serializer.serialize_str(v),
+            OValue::Boolean(v) => serializer.serialize_bool(*v),
+            OValue::I8(v) => serializer.serialize_i8(*v),
+            OValue::U8(v) => serializer.serialize_u8(*v),
+            OValue::I16(v) => serializer.serialize_i16(*v),
+            OValue::I32(v) => serializer.serialize_i32(*v),
+            OValue::I64(v) => serializer.serialize_i64(*v),
+            OValue::F32(v) => serializer.serialize_f32(*v),
+            OValue::F64(v) => serializer.serialize_f64(*v),
+            OValue::DateTime(v) => serializer.serialize_str(&v.to_rfc3339()),
+            OValue::Date(v) => serializer.serialize_str(&v.naive_utc().to_string()),
+            OValue::Document(doc) => doc.serialize(serializer),
+            OValue::Link(rid) => rid.serialize(serializer),
+            OValue::LinkList(links) | OValue::LinkSet(links) => {
+                serializer.collect_seq(links.links.iter())
+            }
+            OValue::RidBag(RidBag::Embedded(links)) => serializer.collect_seq(links.iter()),
+            OValue::RidBag(RidBag::Tree(_)) => Err(ser::Error::custom(
+                "Cannot serialize a tree ridbag, iterate it with a session",
+            )),
+            OValue::EmbeddedList(values) | OValue::EmbeddedSet(values) => {
+                serializer.collect_seq(values.iter())
+            }
+            OValue::EmbeddedMap(map) => serializer.collect_map(map.iter()),
+            OValue::LinkMap(map) => serializer.collect_map(map.iter()),
+            OValue::Decimal(v) => serializer.serialize_str(&v.to_string()),
+            OValue::Binary(v) | OValue::Custom(_, v) => serializer.serialize_bytes(v),
+            #[cfg(feature = "uuid")]
+            OValue::Uuid(v) => serializer.serialize_str(&v.to_string()),
+        }
+    }
+}
+
+impl Serialize for ODocument {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        let mut map = serializer.serialize_map(Some(self.len()))?;
+        for (name, value) in self.iter() {
+            map.serialize_entry(name, value)?;
+        }
+        map.end()
+    }
+}
+
+impl Serialize for OResult {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        serializer.collect_map(self.fields())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{to_document, to_value};
+    use crate::common::types::de::from_document;
+    use crate::common::types::document::ODocument;
+    use crate::common::types::rid::ORecordID;
+    use crate::common::types::value::OValue;
+    use serde::{Deserialize, Serialize};
+    use std::collections::HashMap;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    enum Shape {
+        Empty,
+        Circle(f64),
+        Rect { w: i32, h: i32 },
+        Pair(i32, i32),
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Inner {
+        value: u8,
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Outer {
+        name: String,
+        inner: Inner,
+        list: Vec<i64>,
+        map: HashMap<String, bool>,
+        shapes: Vec<Shape>,
+        link: ORecordID,
+        missing: Option<i32>,
+    }
+
+    #[test]
+    fn ser_round_trip() {
+        let mut map = HashMap::new();
+        map.insert(String::from("a"), true);
+        let outer = Outer {
+            name: String::from("John"),
+            inner: Inner { value: 5 },
+            list: vec![1, 2],
+            map,
+            shapes: vec![
+                Shape::Empty,
+                Shape::Circle(1.5),
+                Shape::Rect { w: 1, h: 2 },
+                Shape::Pair(3, 4),
+            ],
+            link: ORecordID::new(10, 1),
+            missing: None,
+        };
+
+        let doc = to_document(&outer).unwrap();
+        assert_eq!("Outer", doc.class_name());
+        assert_eq!(
+            Some(&OValue::Link(ORecordID::new(10, 1))),
+            doc.get_raw("link")
+        );
+        assert_eq!(Some(&OValue::Null), doc.get_raw("missing"));
+        match doc.get_raw("inner") {
+            Some(OValue::Document(inner)) => {
+                assert_eq!("", inner.class_name());
+                assert_eq!(Some(&OValue::U8(5)), inner.get_raw("value"));
+            }
+            value => panic!("Expected an embedded document, got {:?}", value),
+        }
+
+        let decoded: Outer = from_document(&doc).unwrap();
+        assert_eq!(outer, decoded);
+    }
+
+    #[test]
+    fn ser_values() {
+        assert_eq!(OValue::I64(3), to_value(&3u32).unwrap());
+        assert!(to_value(&u64::MAX).is_err());
+        assert_eq!(
+            OValue::EmbeddedList(vec![OValue::I32(1), OValue::String(String::from("a"))]),
+            to_value(&(1, "a")).unwrap()
+        );
+
+        let mut map = HashMap::new();
+        map.insert(1, 2);
+        assert!(to_value(&map).is_err());
+
+        let doc = ODocument::builder().set("name", "John").build();
+        assert_eq!(doc, to_document(&doc).unwrap());
+    }
+}
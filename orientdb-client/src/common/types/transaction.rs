@@ -0,0 +1,185 @@
+use crate::common::protocol::messages::request::RecordOperation;
+use crate::common::protocol::messages::response;
+use crate::common::types::document::ODocument;
+use crate::common::types::rid::ORecordID;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicI32, Ordering};
+
+static TX_ID: AtomicI32 = AtomicI32::new(1);
+
+pub(crate) fn next_tx_id() -> i32 {
+    TX_ID.fetch_add(1, Ordering::SeqCst)
+}
+
+/// Record operations buffered on the client until the transaction is committed.
+///
+/// New records get a temporary id with a negative cluster position
+/// that is replaced by the server on commit.
+#[derive(Debug)]
+pub(crate) struct TransactionLog {
+    next_position: i64,
+    operations: Vec<RecordOperation>,
+}
+
+impl TransactionLog {
+    pub(crate) fn new() -> TransactionLog {
+        TransactionLog {
+            // -1 is the position of a record never saved
+            next_position: -2,
+            operations: vec![],
+        }
+    }
+
+    pub(crate) fn save(&mut self, doc: &ODocument) -> ORecordID {
+        let rid = doc.record_id().clone();
+        if let Some(operation) = self.operations.iter_mut().find(|op| op.rid() == &rid) {
+            match operation {
+                RecordOperation::Create(content) => *content = doc.clone(),
+                _ => *operation = RecordOperation::Update(doc.clone()),
+            }
+            return rid;
+        }
+        if rid.is_new() {
+            let temp = ORecordID::new(rid.cluster, self.next_position);
+            self.next_position -= 1;
+            let mut content = doc.clone();
+            content.set_record_id(temp.clone());
+            self.operations.push(RecordOperation::Create(content));
+            temp
+        } else {
+            self.operations.push(RecordOperation::Update(doc.clone()));
+            rid
+        }
+    }
+
+    pub(crate) fn delete(&mut self, rid: ORecordID, version: i32) {
+        match self.operations.iter().position(|op| op.rid() == &rid) {
+            Some(idx) => {
+                if let RecordOperation::Create(_) = self.operations[idx] {
+                    self.operations.remove(idx);
+                } else {
+                    self.operations[idx] = RecordOperation::Delete(rid, version);
+                }
+            }
+            None => self.operations.push(RecordOperation::Delete(rid, version)),
+        }
+    }
+
+    pub(crate) fn take(&mut self) -> Vec<RecordOperation> {
+        std::mem::take(&mut self.operations)
+    }
+}
+
+/// Outcome of a committed transaction
+#[derive(Debug, Default)]
+pub struct TransactionResult {
+    created: HashMap<ORecordID, ORecordID>,
+    versions: HashMap<ORecordID, i32>,
+}
+
+impl TransactionResult {
+    pub(crate) fn new(
+        begin: response::BeginTransaction,
+        commit: response::CommitTransaction,
+    ) -> TransactionResult {
+        // The server may already replace the temporary ids on begin
+        let mut assigned: HashMap<ORecordID, ORecordID> = begin
+            .updated_ids
+            .into_iter()
+            .map(|(temp, current)| (current, temp))
+            .collect();
+        let created = commit
+            .created
+            .into_iter()
+            .map(|(current, created)| (assigned.remove(&current).unwrap_or(current), created))
+            .collect();
+        TransactionResult {
+            created,
+            versions: commit.updated.into_iter().collect(),
+        }
+    }
+
+    /// The final id of a record created in the transaction, given its temporary id
+    pub fn created_rid(&self, temp: &ORecordID) -> Option<&ORecordID> {
+        self.created.get(temp)
+    }
+
+    /// The version of a record created or updated in the transaction
+    pub fn version(&self, rid: &ORecordID) -> Option<i32> {
+        let rid = self.created.get(rid).unwrap_or(rid);
+        self.versions.get(rid).copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{TransactionLog, TransactionResult};
+    use crate::common::protocol::messages::request::RecordOperation;
+    use crate::common::protocol::messages::response;
+    use crate::common::types::document::ODocument;
+    use crate::common::types::rid::ORecordID;
+
+    #[test]
+    fn log_assigns_temporary_ids() {
+        let mut log = TransactionLog::new();
+        let first = log.save(&ODocument::new("V"));
+        let second = log.save(&ODocument::new("V"));
+
+        assert_eq!(ORecordID::new(-1, -2), first);
+        assert_eq!(ORecordID::new(-1, -3), second);
+        assert_eq!(2, log.take().len());
+    }
+
+    #[test]
+    fn log_merges_operations_on_same_record() {
+        let mut log = TransactionLog::new();
+        let mut doc = ODocument::new("V");
+        let temp = log.save(&doc);
+        doc.set_record_id(temp.clone());
+        doc.set("name", "John");
+        assert_eq!(temp, log.save(&doc));
+
+        let mut stored = ODocument::new("V");
+        stored.set_record_id(ORecordID::new(10, 1));
+        log.save(&stored);
+        log.delete(ORecordID::new(10, 1), 1);
+
+        let operations = log.take();
+        assert_eq!(2, operations.len());
+        match &operations[0] {
+            RecordOperation::Create(content) => assert_eq!("John", content.get::<String>("name")),
+            op => panic!("Expected a create operation, got {:?}", op),
+        }
+        assert_eq!(
+            RecordOperation::Delete(ORecordID::new(10, 1), 1),
+            operations[1]
+        );
+
+        let mut log = TransactionLog::new();
+        let temp = log.save(&ODocument::new("V"));
+        log.delete(temp, 0);
+        assert!(log.take().is_empty());
+    }
+
+    #[test]
+    fn result_maps_temporary_ids() {
+        let result = TransactionResult::new(
+            response::BeginTransaction::new(
+                1,
+                vec![(ORecordID::new(-1, -2), ORecordID::new(9, -2))],
+            ),
+            response::CommitTransaction::new(
+                vec![(ORecordID::new(9, -2), ORecordID::new(9, 0))],
+                vec![(ORecordID::new(9, 0), 1), (ORecordID::new(10, 1), 3)],
+            ),
+        );
+
+        assert_eq!(
+            Some(&ORecordID::new(9, 0)),
+            result.created_rid(&ORecordID::new(-1, -2))
+        );
+        assert_eq!(Some(1), result.version(&ORecordID::new(-1, -2)));
+        assert_eq!(Some(3), result.version(&ORecordID::new(10, 1)));
+        assert_eq!(None, result.version(&ORecordID::new(11, 1)));
+    }
+}
@@ -1,8 +1,12 @@
 use chrono::offset;
-use std::collections::HashMap;
+use chrono::NaiveDate;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::convert::TryFrom;
+use std::hash::{BuildHasher, Hash};
 
 use crate::common::protocol::constants;
 use crate::common::types::bag::RidBag;
+use crate::common::types::decimal::ODecimal;
 use crate::common::types::document::ODocument;
 use crate::common::types::rid::ORecordID;
 use crate::common::{OrientError, OrientResult};
@@ -11,10 +15,12 @@ use crate::common::{OrientError, OrientResult};
 use uuid::Uuid;
 
 pub type DateTime = chrono::DateTime<offset::Utc>;
+#[allow(deprecated)]
 pub type Date = chrono::Date<offset::Utc>;
 pub type EmbeddedMap = HashMap<String, OValue>;
 pub type EmbeddedList = Vec<OValue>;
 pub type EmbeddedSet = Vec<OValue>;
+pub type LinkMap = HashMap<String, ORecordID>;
 
 #[derive(Debug, PartialEq, Clone)]
 pub struct LinkList {
@@ -49,6 +55,11 @@ pub enum OValue {
     EmbeddedList(EmbeddedList),
     EmbeddedSet(EmbeddedSet),
     RidBag(RidBag),
+    Decimal(ODecimal),
+    Binary(Vec<u8>),
+    /// Class name and java serialized bytes of a CUSTOM field
+    Custom(String, Vec<u8>),
+    LinkMap(LinkMap),
     #[cfg(feature = "uuid")]
     Uuid(Uuid),
 }
@@ -59,11 +70,29 @@ impl<'a> From<&'a str> for OValue {
     }
 }
 
+#[allow(clippy::wrong_self_convention)]
 pub trait IntoOValue: Send + Sync {
     fn into_ovalue(&self) -> OValue;
+
+    /// The value of a list of `Self`, an EMBEDDEDLIST unless the type has a dedicated one
+    fn into_list(values: &[Self]) -> OValue
+    where
+        Self: Sized,
+    {
+        embedded_list(values)
+    }
+
+    /// The value of a set of `Self`, an EMBEDDEDSET unless the type has a dedicated one
+    fn into_set<'a, I>(values: I) -> OValue
+    where
+        Self: Sized + 'a,
+        I: Iterator<Item = &'a Self>,
+    {
+        OValue::EmbeddedSet(values.map(|v| v.into_ovalue()).collect())
+    }
 }
 
-impl<'a> IntoOValue for &'a str {
+impl IntoOValue for &str {
     fn into_ovalue(&self) -> OValue {
         OValue::String(String::from(*self))
     }
@@ -99,25 +128,117 @@ impl IntoOValue for i16 {
     }
 }
 
+impl IntoOValue for i8 {
+    fn into_ovalue(&self) -> OValue {
+        OValue::I8(*self)
+    }
+}
+
+impl IntoOValue for u8 {
+    fn into_ovalue(&self) -> OValue {
+        OValue::U8(*self)
+    }
+
+    // Lists of bytes are stored as BINARY
+    fn into_list(values: &[Self]) -> OValue {
+        OValue::Binary(values.to_vec())
+    }
+}
+
+impl IntoOValue for f32 {
+    fn into_ovalue(&self) -> OValue {
+        OValue::F32(*self)
+    }
+}
+
+impl IntoOValue for f64 {
+    fn into_ovalue(&self) -> OValue {
+        OValue::F64(*self)
+    }
+}
+
+impl IntoOValue for DateTime {
+    fn into_ovalue(&self) -> OValue {
+        OValue::DateTime(*self)
+    }
+}
+
+impl IntoOValue for Date {
+    fn into_ovalue(&self) -> OValue {
+        OValue::Date(*self)
+    }
+}
+
+impl IntoOValue for NaiveDate {
+    #[allow(deprecated)]
+    fn into_ovalue(&self) -> OValue {
+        OValue::Date(Date::from_utc(*self, offset::Utc))
+    }
+}
+
+impl IntoOValue for OValue {
+    fn into_ovalue(&self) -> OValue {
+        self.clone()
+    }
+}
+
+impl<T> IntoOValue for Option<T>
+where
+    T: IntoOValue,
+{
+    fn into_ovalue(&self) -> OValue {
+        match self {
+            Some(value) => value.into_ovalue(),
+            None => OValue::Null,
+        }
+    }
+}
+
 impl IntoOValue for ORecordID {
     fn into_ovalue(&self) -> OValue {
         OValue::Link((*self).clone())
     }
+
+    fn into_list(values: &[Self]) -> OValue {
+        OValue::LinkList(values.to_vec().into())
+    }
+
+    fn into_set<'a, I>(values: I) -> OValue
+    where
+        I: Iterator<Item = &'a Self>,
+    {
+        OValue::LinkSet(values.cloned().collect::<Vec<_>>().into())
+    }
+}
+
+fn embedded_list<T: IntoOValue>(values: &[T]) -> OValue {
+    OValue::EmbeddedList(values.iter().map(|v| v.into_ovalue()).collect())
 }
 
-impl IntoOValue for EmbeddedMap {
+impl<T> IntoOValue for Vec<T>
+where
+    T: IntoOValue,
+{
     fn into_ovalue(&self) -> OValue {
-        let value = (*self).clone();
-        OValue::EmbeddedMap(value)
+        T::into_list(self)
     }
 }
 
-impl<T> IntoOValue for Vec<T>
+impl<T, S: std::hash::BuildHasher + Send + Sync> IntoOValue for HashSet<T, S>
 where
     T: IntoOValue,
 {
     fn into_ovalue(&self) -> OValue {
-        OValue::EmbeddedList(self.iter().map(|v| v.into_ovalue()).collect())
+        T::into_set(self.iter())
+    }
+}
+
+impl<T> IntoOValue for BTreeSet<T>
+where
+    T: IntoOValue,
+{
+    fn into_ovalue(&self) -> OValue {
+        T::into_set(self.iter())
     }
 }
 
@@ -134,6 +255,19 @@ where
     }
 }
 
+impl<T> IntoOValue for BTreeMap<String, T>
+where
+    T: IntoOValue,
+{
+    fn into_ovalue(&self) -> OValue {
+        let map: HashMap<String, OValue> = self
+            .iter()
+            .map(|(k, v)| (k.clone(), v.into_ovalue()))
+            .collect();
+        OValue::EmbeddedMap(map)
+    }
+}
+
 impl IntoOValue for ODocument {
     fn into_ovalue(&self) -> OValue {
         OValue::Document(self.clone())
@@ -146,10 +280,16 @@ impl IntoOValue for LinkList {
     }
 }
 
+impl IntoOValue for RidBag {
+    fn into_ovalue(&self) -> OValue {
+        OValue::RidBag(self.clone())
+    }
+}
+
 #[cfg(feature = "uuid")]
 impl IntoOValue for Uuid {
     fn into_ovalue(&self) -> OValue {
-        OValue::Uuid(self.clone())
+        OValue::Uuid(*self)
     }
 }
 impl OValue {
@@ -167,10 +307,22 @@ impl OValue {
             OValue::EmbeddedList(_) => constants::EMBEDDEDLIST,
             OValue::EmbeddedSet(_) => constants::EMBEDDEDSET,
             OValue::LinkList(_) => constants::LINKLIST,
+            OValue::LinkSet(_) => constants::LINKSET,
+            OValue::Null => constants::NULL,
+            // there is no unsigned byte on the server, values above 127 are written as SHORT
+            OValue::U8(v) if *v > i8::MAX as u8 => constants::SHORT,
+            OValue::U8(_) => constants::BYTE,
+            OValue::F32(_) => constants::FLOAT,
+            OValue::F64(_) => constants::DOUBLE,
+            OValue::DateTime(_) => constants::DATETIME,
+            OValue::Date(_) => constants::DATE,
+            OValue::RidBag(_) => constants::LINKBAG,
+            OValue::Decimal(_) => constants::DECIMAL,
+            OValue::Binary(_) => constants::BINARY,
+            OValue::Custom(_, _) => constants::CUSTOM,
+            OValue::LinkMap(_) => constants::LINKMAP,
             #[cfg(feature = "uuid")]
             OValue::Uuid(_) => constants::STRING,
-
-            _ => panic!("Type id not supported {:?}", self),
         }
     }
 }
@@ -179,38 +331,112 @@ pub trait FromOValue {
     fn from_value(ty: &OValue) -> OrientResult<Self>
     where
         Self: Sized;
-}
 
-impl FromOValue for i32 {
-    fn from_value(ty: &OValue) -> OrientResult<Self>
+    /// Like `from_value`, but refuse conversions that can lose information,
+    /// e.g. `f64` to `f32` or `i64` to `i32`. Widening conversions are allowed.
+    fn from_value_strict(ty: &OValue) -> OrientResult<Self>
     where
         Self: Sized,
     {
-        match ty {
-            OValue::I32(val) => Ok(*val),
-            _ => Err(OrientError::Conversion(format!(
-                "Cannot convert {:?} to i32",
-                ty
-            ))),
-        }
+        Self::from_value(ty)
     }
 }
 
-impl FromOValue for i64 {
+/// Read a value with `FromOValue::from_value_strict`
+#[derive(Debug, PartialEq, Clone)]
+pub struct Strict<T>(pub T);
+
+impl<T: FromOValue> FromOValue for Strict<T> {
     fn from_value(ty: &OValue) -> OrientResult<Self>
     where
         Self: Sized,
     {
-        match ty {
-            OValue::I64(val) => Ok(*val),
-            _ => Err(OrientError::Conversion(format!(
-                "Cannot convert {:?} to i64",
-                ty
-            ))),
-        }
+        T::from_value_strict(ty).map(Strict)
     }
 }
 
+fn conversion_error<T>(ty: &OValue, target: &str) -> OrientResult<T> {
+    Err(OrientError::Conversion(format!(
+        "Cannot convert {:?} to {}",
+        ty, target
+    )))
+}
+
+fn integer(ty: &OValue) -> Option<i64> {
+    match ty {
+        OValue::I8(val) => Some(i64::from(*val)),
+        OValue::U8(val) => Some(i64::from(*val)),
+        OValue::I16(val) => Some(i64::from(*val)),
+        OValue::I32(val) => Some(i64::from(*val)),
+        OValue::I64(val) => Some(*val),
+        _ => None,
+    }
+}
+
+// Widening conversions from the listed variants always succeed, narrowing
+// ones only when the value fits and never in strict mode.
+macro_rules! from_integer {
+    ($ty:ty, [$($widen:ident),+]) => {
+        impl FromOValue for $ty {
+            fn from_value(ty: &OValue) -> OrientResult<Self>
+            where
+                Self: Sized,
+            {
+                match integer(ty).map(<$ty>::try_from) {
+                    Some(Ok(val)) => Ok(val),
+                    _ => conversion_error(ty, stringify!($ty)),
+                }
+            }
+
+            fn from_value_strict(ty: &OValue) -> OrientResult<Self>
+            where
+                Self: Sized,
+            {
+                match ty {
+                    $(OValue::$widen(val) => Ok(<$ty>::from(*val)),)+
+                    _ => conversion_error(ty, stringify!($ty)),
+                }
+            }
+        }
+    };
+}
+
+from_integer!(i8, [I8]);
+from_integer!(u8, [U8]);
+from_integer!(i16, [I8, U8, I16]);
+from_integer!(i32, [I8, U8, I16, I32]);
+from_integer!(i64, [I8, U8, I16, I32, I64]);
+
+// Same as integers, lossy variants are rounded to the closest value.
+macro_rules! from_float {
+    ($ty:ty, [$($widen:ident),+], [$($lossy:ident),+]) => {
+        impl FromOValue for $ty {
+            fn from_value(ty: &OValue) -> OrientResult<Self>
+            where
+                Self: Sized,
+            {
+                match ty {
+                    $(OValue::$lossy(val) => Ok(*val as $ty),)+
+                    _ => Self::from_value_strict(ty),
+                }
+            }
+
+            fn from_value_strict(ty: &OValue) -> OrientResult<Self>
+            where
+                Self: Sized,
+            {
+                match ty {
+                    $(OValue::$widen(val) => Ok(<$ty>::from(*val)),)+
+                    _ => conversion_error(ty, stringify!($ty)),
+                }
+            }
+        }
+    };
+}
+
+from_float!(f32, [F32, I8, U8, I16], [F64, I32, I64]);
+from_float!(f64, [F32, F64, I8, U8, I16, I32], [I64]);
+
 impl FromOValue for String {
     fn from_value(ty: &OValue) -> OrientResult<Self>
     where
@@ -241,21 +467,217 @@ impl FromOValue for ODocument {
     }
 }
 
-impl FromOValue for EmbeddedMap {
+impl FromOValue for OValue {
+    fn from_value(ty: &OValue) -> OrientResult<Self>
+    where
+        Self: Sized,
+    {
+        Ok(ty.clone())
+    }
+}
+
+impl FromOValue for DateTime {
+    fn from_value(ty: &OValue) -> OrientResult<Self>
+    where
+        Self: Sized,
+    {
+        match ty {
+            OValue::DateTime(val) => Ok(*val),
+            _ => conversion_error(ty, "DateTime"),
+        }
+    }
+}
+
+impl FromOValue for Date {
     fn from_value(ty: &OValue) -> OrientResult<Self>
     where
         Self: Sized,
     {
         match ty {
-            OValue::EmbeddedMap(val) => Ok(val.clone()),
-            _ => Err(OrientError::Conversion(format!(
-                "Cannot convert {:?} to EmbeddedMap",
-                ty
-            ))),
+            OValue::Date(val) => Ok(*val),
+            _ => conversion_error(ty, "Date"),
         }
     }
 }
 
+impl FromOValue for NaiveDate {
+    fn from_value(ty: &OValue) -> OrientResult<Self>
+    where
+        Self: Sized,
+    {
+        match ty {
+            OValue::Date(val) => Ok(val.naive_utc()),
+            _ => conversion_error(ty, "NaiveDate"),
+        }
+    }
+}
+
+impl FromOValue for ORecordID {
+    fn from_value(ty: &OValue) -> OrientResult<Self>
+    where
+        Self: Sized,
+    {
+        match ty {
+            OValue::Link(val) => Ok(val.clone()),
+            _ => conversion_error(ty, "ORecordID"),
+        }
+    }
+}
+
+impl FromOValue for LinkList {
+    fn from_value(ty: &OValue) -> OrientResult<Self>
+    where
+        Self: Sized,
+    {
+        match ty {
+            OValue::LinkList(val) | OValue::LinkSet(val) => Ok(val.clone()),
+            _ => conversion_error(ty, "LinkList"),
+        }
+    }
+}
+
+impl FromOValue for RidBag {
+    fn from_value(ty: &OValue) -> OrientResult<Self>
+    where
+        Self: Sized,
+    {
+        match ty {
+            OValue::RidBag(val) => Ok(val.clone()),
+            _ => conversion_error(ty, "RidBag"),
+        }
+    }
+}
+
+// Collections are read element by element, links and bytes are wrapped
+// as single values first.
+fn from_elements<T, C, F>(ty: &OValue, target: &str, convert: F) -> OrientResult<C>
+where
+    C: std::iter::FromIterator<T>,
+    F: Fn(&OValue) -> OrientResult<T>,
+{
+    match ty {
+        OValue::EmbeddedList(values) | OValue::EmbeddedSet(values) => {
+            values.iter().map(convert).collect()
+        }
+        OValue::LinkList(links) | OValue::LinkSet(links) => links
+            .links
+            .iter()
+            .map(|rid| convert(&OValue::Link(rid.clone())))
+            .collect(),
+        OValue::Binary(bytes) => bytes.iter().map(|b| convert(&OValue::U8(*b))).collect(),
+        _ => conversion_error(ty, target),
+    }
+}
+
+fn from_entries<T, C, F>(ty: &OValue, target: &str, convert: F) -> OrientResult<C>
+where
+    C: std::iter::FromIterator<(String, T)>,
+    F: Fn(&OValue) -> OrientResult<T>,
+{
+    match ty {
+        OValue::EmbeddedMap(values) => values
+            .iter()
+            .map(|(k, v)| Ok((k.clone(), convert(v)?)))
+            .collect(),
+        OValue::LinkMap(links) => links
+            .iter()
+            .map(|(k, rid)| Ok((k.clone(), convert(&OValue::Link(rid.clone()))?)))
+            .collect(),
+        _ => conversion_error(ty, target),
+    }
+}
+
+impl<T: FromOValue> FromOValue for Vec<T> {
+    fn from_value(ty: &OValue) -> OrientResult<Self>
+    where
+        Self: Sized,
+    {
+        from_elements(ty, "Vec", T::from_value)
+    }
+
+    fn from_value_strict(ty: &OValue) -> OrientResult<Self>
+    where
+        Self: Sized,
+    {
+        from_elements(ty, "Vec", T::from_value_strict)
+    }
+}
+
+impl<T, S> FromOValue for HashSet<T, S>
+where
+    T: FromOValue + Eq + Hash,
+    S: BuildHasher + Default,
+{
+    fn from_value(ty: &OValue) -> OrientResult<Self>
+    where
+        Self: Sized,
+    {
+        from_elements(ty, "HashSet", T::from_value)
+    }
+
+    fn from_value_strict(ty: &OValue) -> OrientResult<Self>
+    where
+        Self: Sized,
+    {
+        from_elements(ty, "HashSet", T::from_value_strict)
+    }
+}
+
+impl<T> FromOValue for BTreeSet<T>
+where
+    T: FromOValue + Ord,
+{
+    fn from_value(ty: &OValue) -> OrientResult<Self>
+    where
+        Self: Sized,
+    {
+        from_elements(ty, "BTreeSet", T::from_value)
+    }
+
+    fn from_value_strict(ty: &OValue) -> OrientResult<Self>
+    where
+        Self: Sized,
+    {
+        from_elements(ty, "BTreeSet", T::from_value_strict)
+    }
+}
+
+impl<T, S> FromOValue for HashMap<String, T, S>
+where
+    T: FromOValue,
+    S: BuildHasher + Default,
+{
+    fn from_value(ty: &OValue) -> OrientResult<Self>
+    where
+        Self: Sized,
+    {
+        from_entries(ty, "HashMap", T::from_value)
+    }
+
+    fn from_value_strict(ty: &OValue) -> OrientResult<Self>
+    where
+        Self: Sized,
+    {
+        from_entries(ty, "HashMap", T::from_value_strict)
+    }
+}
+
+impl<T: FromOValue> FromOValue for BTreeMap<String, T> {
+    fn from_value(ty: &OValue) -> OrientResult<Self>
+    where
+        Self: Sized,
+    {
+        from_entries(ty, "BTreeMap", T::from_value)
+    }
+
+    fn from_value_strict(ty: &OValue) -> OrientResult<Self>
+    where
+        Self: Sized,
+    {
+        from_entries(ty, "BTreeMap", T::from_value_strict)
+    }
+}
+
 impl FromOValue for bool {
     fn from_value(ty: &OValue) -> OrientResult<Self>
     where
@@ -264,7 +686,7 @@ impl FromOValue for bool {
         match ty {
             OValue::Boolean(val) => Ok(*val),
             _ => Err(OrientError::Conversion(format!(
-                "Cannot convert {:?} to &str",
+                "Cannot convert {:?} to bool",
                 ty
             ))),
         }
@@ -300,4 +722,100 @@ impl<T: FromOValue> FromOValue for Option<T> {
             _ => Ok(Some(T::from_value(ty)?)),
         }
     }
+
+    fn from_value_strict(ty: &OValue) -> OrientResult<Self>
+    where
+        Self: Sized,
+    {
+        match ty {
+            OValue::Null => Ok(None),
+            _ => Ok(Some(T::from_value_strict(ty)?)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FromOValue, IntoOValue, LinkList, OValue, Strict};
+    use crate::common::types::rid::ORecordID;
+    use chrono::NaiveDate;
+    use std::collections::{BTreeMap, HashMap, HashSet};
+
+    #[test]
+    fn from_value_numbers() {
+        assert_eq!(10i64, i64::from_value(&OValue::I16(10)).unwrap());
+        assert_eq!(10i32, i32::from_value(&OValue::I64(10)).unwrap());
+        assert!(i32::from_value(&OValue::I64(i64::MAX)).is_err());
+        assert!(u8::from_value(&OValue::I8(-1)).is_err());
+        assert_eq!(1.5f64, f64::from_value(&OValue::F32(1.5)).unwrap());
+        assert_eq!(1.5f32, f32::from_value(&OValue::F64(1.5)).unwrap());
+        assert!(i32::from_value(&OValue::F32(1.0)).is_err());
+
+        assert_eq!(Strict(10i64), Strict::from_value(&OValue::I16(10)).unwrap());
+        assert!(Strict::<i32>::from_value(&OValue::I64(10)).is_err());
+        assert!(Strict::<f32>::from_value(&OValue::F64(1.5)).is_err());
+        assert!(
+            Strict::<Vec<i16>>::from_value(&OValue::EmbeddedList(vec![OValue::I32(1)])).is_err()
+        );
+        assert_eq!(
+            Strict(Some(vec![1i32])),
+            Strict::from_value(&OValue::EmbeddedList(vec![OValue::I16(1)])).unwrap()
+        );
+    }
+
+    #[test]
+    fn from_value_collections() {
+        let rid = ORecordID::new(10, 1);
+        assert_eq!(
+            vec![rid.clone()],
+            Vec::<ORecordID>::from_value(&OValue::LinkList(LinkList::from(vec![rid.clone()])))
+                .unwrap()
+        );
+        assert_eq!(
+            vec![1u8, 2],
+            Vec::<u8>::from_value(&OValue::Binary(vec![1, 2])).unwrap()
+        );
+
+        let mut links = HashMap::new();
+        links.insert(String::from("a"), rid.clone());
+        let mut expected = BTreeMap::new();
+        expected.insert(String::from("a"), rid.clone());
+        assert_eq!(
+            expected,
+            BTreeMap::<String, ORecordID>::from_value(&OValue::LinkMap(links)).unwrap()
+        );
+
+        let links = vec![rid.clone(), ORecordID::new(10, 2)];
+        let list = links.into_ovalue();
+        assert_eq!(OValue::LinkList(LinkList::from(links.clone())), list);
+        assert_eq!(links, Vec::<ORecordID>::from_value(&list).unwrap());
+        let set: HashSet<ORecordID> = links.iter().cloned().collect();
+        assert_eq!(
+            set,
+            HashSet::<ORecordID>::from_value(&set.into_ovalue()).unwrap()
+        );
+        assert_eq!(
+            OValue::LinkSet(LinkList::from(vec![])),
+            HashSet::<ORecordID>::new().into_ovalue()
+        );
+
+        let date = NaiveDate::from_ymd_opt(2020, 1, 2).unwrap();
+        assert_eq!(date, NaiveDate::from_value(&date.into_ovalue()).unwrap());
+    }
+
+    #[test]
+    fn into_value() {
+        assert_eq!(OValue::Binary(vec![1, 2]), vec![1u8, 2].into_ovalue());
+        assert_eq!(OValue::Binary(vec![]), Vec::<u8>::new().into_ovalue());
+        assert_eq!(
+            OValue::EmbeddedList(vec![OValue::U8(1), OValue::U8(2)]),
+            vec![OValue::U8(1), OValue::U8(2)].into_ovalue()
+        );
+        assert_eq!(OValue::Null, None::<i32>.into_ovalue());
+        assert_eq!(OValue::F32(1.5), Some(1.5f32).into_ovalue());
+        assert_eq!(
+            OValue::EmbeddedList(vec![OValue::I8(1)]),
+            vec![1i8].into_ovalue()
+        );
+    }
 }
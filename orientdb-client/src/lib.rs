@@ -59,5 +59,5 @@ pub mod aio {
 
 #[cfg(feature = "sugar")]
 pub mod derive {
-    pub use orientdb_macro::FromResult;
+    pub use orientdb_macro::{FromResult, IntoDocument};
 }
@@ -1,9 +1,10 @@
 use super::network::cluster::SyncConnection;
 use super::network::cluster::{Cluster, Server};
 use crate::common::protocol::messages::request::{
-    Close, Connect, CreateDB, DropDB, ExistDB, MsgHeader, Open, ServerQuery,
+    Close, Connect, CreateDB, DropDB, ExistDB, MsgHeader, Open,
 };
 use crate::common::protocol::messages::response;
+use crate::common::session::{Credentials, Reopen, ReopenPolicy};
 use crate::common::ConnectionOptions;
 use crate::sync::server_statement::ServerStatement;
 use crate::sync::session::{OSession, SessionPool, SessionPoolManager};
@@ -23,18 +24,30 @@ impl OrientDB {
     pub fn connect<T: Into<ConnectionOptions>>(options: T) -> OrientResult<OrientDB> {
         let opts = options.into();
 
-        let addr: SocketAddr = format!("{}:{}", opts.host, opts.port)
-            .to_socket_addrs()?
-            .next()
-            .expect("Cannot parse socket address");
-
-        let cluster = Cluster::builder()
-            .add_server(addr)
+        let mut builder = Cluster::builder()
             .pool_max(opts.pool_size)
-            .build();
+            .strategy(opts.strategy.clone())
+            .pool_timeout(opts.pool_timeout)
+            .timeouts(opts.timeouts());
+
+        for (host, port) in opts.addresses() {
+            let addr: SocketAddr = format!("{}:{}", host, port)
+                .to_socket_addrs()?
+                .next()
+                .expect("Cannot parse socket address");
+            builder = builder.add_server(host, addr);
+        }
+
+        #[cfg(feature = "tls")]
+        if let Some(tls) = &opts.tls {
+            builder = builder.tls(tls.connector()?);
+        }
+
+        let cluster = builder.build()?;
 
         let internal = OrientDBClientInternal {
             cluster: Arc::new(cluster),
+            reopen: opts.reopen.clone(),
         };
 
         Ok(OrientDB { internal })
@@ -44,6 +57,7 @@ impl OrientDB {
 #[derive(Clone)]
 pub struct OrientDBClientInternal {
     cluster: Arc<Cluster>,
+    reopen: Arc<dyn ReopenPolicy>,
 }
 
 impl Deref for OrientDB {
@@ -67,8 +81,7 @@ impl OrientDBClientInternal {
         password: &str,
         size: Option<u32>,
     ) -> OrientResult<SessionPool> {
-        let server = self.cluster.select();
-        SessionPoolManager::new(self.clone(), server, db_name, user, password).managed(size)
+        SessionPoolManager::new(self.clone(), db_name, user, password).managed(size)
     }
     pub fn session(&self, db_name: &str, user: &str, password: &str) -> OrientResult<OSession> {
         self._session(db_name, user, password, false)
@@ -91,16 +104,15 @@ impl OrientDBClientInternal {
         password: &str,
         pooled: bool,
     ) -> OrientResult<OSession> {
-        let mut conn = server.connection()?;
-
-        let response: response::Open = conn
-            .send(Open::new(db_name, user, password).into())?
-            .payload();
+        let (server, mut response) = self
+            .cluster
+            .send(&server, Open::new(db_name, user, password).into())?;
+        let response: response::Open = response.payload();
 
         Ok(OSession::new(
             -1,
-            response.session_id,
-            response.token,
+            Credentials::new(response.session_id, response.token),
+            Reopen::new(db_name, user, password, self.reopen.clone()),
             self.cluster.clone(),
             server,
             pooled,
@@ -195,7 +207,7 @@ impl OrientDBClientInternal {
         user: &str,
         password: &str,
         query: &str,
-    ) -> OrientResult<ServerStatement> {
+    ) -> OrientResult<ServerStatement<'_>> {
         Ok(ServerStatement::new(
             self,
             user.to_string(),
@@ -204,14 +216,27 @@ impl OrientDBClientInternal {
         ))
     }
 
-    pub(crate) fn run(&self, stmt: ServerStatement) -> OrientResult<impl ResultSet> {
-        let user = stmt.user.clone();
-        let pwd = stmt.password.clone();
-        self.run_as_admin(&user, &pwd, move |session, conn| {
-            let response: response::ServerQuery = conn
-                .send(stmt.into_query(session.session_id, session.token).into())?
-                .payload();
-            Ok(ServerResultSet::new(response))
-        })
+    pub(crate) fn run(&self, stmt: ServerStatement<'_>) -> OrientResult<impl ResultSet> {
+        let (mut conn, server) = self.cluster.connection()?;
+        let admin: response::Connect = conn
+            .send(Connect::new(&stmt.user, &stmt.password).into())?
+            .payload();
+        let timeout = stmt.timeout;
+        let query = stmt.into_query(admin.session_id, admin.token.clone());
+        let page_size = query.page_size;
+        match conn.send_with_timeout(query.into(), timeout) {
+            Ok(mut response) => Ok(ServerResultSet::new(
+                server,
+                response.payload(),
+                admin.session_id,
+                admin.token,
+                page_size,
+                timeout,
+            )),
+            Err(e) => {
+                conn.send_and_forget(Close::new(admin.session_id, admin.token).into())?;
+                Err(e)
+            }
+        }
     }
 }
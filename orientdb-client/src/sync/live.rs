@@ -0,0 +1,138 @@
+use super::network::conn::Connection;
+
+use crate::common::live::LiveQueue;
+use crate::common::protocol::messages::request::UnsubscribeLiveQuery;
+use crate::common::protocol::messages::response::{LiveQueryResult, Status};
+use crate::common::session::{Credentials, SessionCredentials};
+use crate::types::LiveEvent;
+
+use crate::OrientResult;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+
+// Writes the unsubscribe request once, either from the unsubscriber
+// or when the results are dropped, with the current credentials of the session
+struct Subscription {
+    monitor_id: i32,
+    credentials: Arc<SessionCredentials>,
+    active: Arc<AtomicBool>,
+    conn: Connection,
+}
+
+impl Subscription {
+    fn unsubscribe(&mut self) -> OrientResult<()> {
+        if !self.active.swap(false, Ordering::SeqCst) {
+            return Ok(());
+        }
+        let Credentials { session_id, token } = self.credentials.get();
+        let unsubscribe = UnsubscribeLiveQuery::new(session_id, token, self.monitor_id);
+        self.conn.send_and_forget(unsubscribe.into())
+    }
+}
+
+pub struct Unsubscriber {
+    subscription: Subscription,
+}
+
+impl Unsubscriber {
+    /// Stop the live query, the results end once the server confirms it
+    pub fn unsubscribe(mut self) -> OrientResult<()> {
+        self.subscription.unsubscribe()
+    }
+}
+
+/// Events of a live query. Dropping the results unsubscribes the live query.
+pub struct LiveResults {
+    queue: Arc<LiveQueue>,
+    subscription: Subscription,
+}
+
+impl LiveResults {
+    /// Number of events dropped because the buffer of the results was full
+    pub fn dropped(&self) -> u64 {
+        self.queue.dropped()
+    }
+}
+
+impl Iterator for LiveResults {
+    type Item = OrientResult<LiveEvent>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.queue.recv_blocking()
+    }
+}
+
+impl Drop for LiveResults {
+    fn drop(&mut self) {
+        self.queue.disconnect();
+        let _ = self.subscription.unsubscribe();
+    }
+}
+
+pub(crate) fn subscription(
+    conn: &Connection,
+    monitor_id: i32,
+    credentials: Arc<SessionCredentials>,
+    queue: Arc<LiveQueue>,
+) -> OrientResult<(Unsubscriber, LiveResults)> {
+    let active = Arc::new(AtomicBool::new(true));
+    let unsubscriber = Unsubscriber {
+        subscription: Subscription {
+            monitor_id,
+            credentials: credentials.clone(),
+            active: active.clone(),
+            conn: conn.try_clone()?,
+        },
+    };
+    let results = LiveResults {
+        queue,
+        subscription: Subscription {
+            monitor_id,
+            credentials,
+            active,
+            conn: conn.try_clone()?,
+        },
+    };
+    Ok((unsubscriber, results))
+}
+
+/// Read the pushes of the live query `monitor_id` from `conn` on a dedicated
+/// thread, until the live query ends or its connection fails
+pub(crate) fn listen(
+    mut conn: Connection,
+    monitor_id: i32,
+    queue: Arc<LiveQueue>,
+) -> OrientResult<()> {
+    conn.wait_pushes()?;
+    thread::Builder::new()
+        .name(format!("orientdb-live-query-{}", monitor_id))
+        .spawn(move || {
+            loop {
+                match conn.receive() {
+                    Ok(mut response) if response.header.status == Status::PUSH => {
+                        let result: LiveQueryResult = response.payload();
+                        if result.monitor_id != monitor_id {
+                            continue;
+                        }
+                        let ended = result.ended;
+                        let delivered = result
+                            .into_events()
+                            .all(|event| queue.push_blocking(Ok(event)));
+                        if ended || !delivered {
+                            break;
+                        }
+                    }
+                    // The response to the unsubscribe request
+                    Ok(_) => {}
+                    Err(e) => {
+                        queue.fail(e);
+                        break;
+                    }
+                }
+            }
+            queue.close();
+            let _ = conn.close();
+        })?;
+    Ok(())
+}
@@ -0,0 +1,82 @@
+use super::live::{LiveResults, Unsubscriber};
+use super::session::OSession;
+use crate::common::live::{LiveQueue, DEFAULT_BUFFER};
+use crate::common::protocol::messages::request::LiveQuery;
+use crate::common::types::value::{IntoOValue, OValue};
+use crate::types::OverflowPolicy;
+use crate::OrientResult;
+use std::collections::HashMap;
+
+/// A live query of the sync session.
+///
+/// There is no resilient mode as on the async session: when the connection of the
+/// live query fails, the results yield the error and end.
+pub struct LiveStatement<'a> {
+    session: &'a OSession,
+    stm: String,
+    params: HashMap<String, OValue>,
+    named: bool,
+    buffer: usize,
+    overflow: OverflowPolicy,
+}
+
+impl<'a> LiveStatement<'a> {
+    pub(crate) fn new(session: &'a OSession, stm: String) -> LiveStatement<'a> {
+        LiveStatement {
+            session,
+            stm,
+            params: HashMap::new(),
+            named: true,
+            buffer: DEFAULT_BUFFER,
+            overflow: OverflowPolicy::default(),
+        }
+    }
+
+    pub fn positional(mut self, params: &[&dyn IntoOValue]) -> Self {
+        let mut p = HashMap::new();
+        for (i, elem) in params.iter().enumerate() {
+            p.insert(i.to_string(), elem.into_ovalue());
+        }
+        self.params = p;
+        self.named = false;
+        self
+    }
+    pub fn named(mut self, params: &[(&str, &dyn IntoOValue)]) -> Self {
+        self.params = params
+            .iter()
+            .map(|(k, v)| (String::from(*k), v.into_ovalue()))
+            .collect();
+
+        self.named = true;
+        self
+    }
+
+    /// Number of events buffered for the results, 10 by default
+    pub fn buffer(mut self, buffer: usize) -> Self {
+        self.buffer = buffer;
+        self
+    }
+
+    /// What to do with events when the buffer is full, `OverflowPolicy::Block` by default
+    pub fn overflow(mut self, overflow: OverflowPolicy) -> Self {
+        self.overflow = overflow;
+        self
+    }
+
+    pub fn run(self) -> OrientResult<(Unsubscriber, LiveResults)> {
+        let queue = LiveQueue::new(self.buffer, self.overflow);
+        self.session.live_run(self.into(), queue)
+    }
+}
+
+impl<'a> From<LiveStatement<'a>> for LiveQuery {
+    fn from(x: LiveStatement) -> LiveQuery {
+        LiveQuery::new(
+            x.session.session_id(),
+            x.session.token(),
+            x.stm,
+            x.params,
+            x.named,
+        )
+    }
+}
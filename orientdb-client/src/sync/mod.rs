@@ -1,7 +1,11 @@
 pub mod client;
+pub mod live;
+pub mod live_statement;
 pub mod network;
 pub(crate) mod protocol;
+pub mod push;
 pub mod server_statement;
 pub mod session;
 pub mod statement;
+pub mod transaction;
 pub mod types;
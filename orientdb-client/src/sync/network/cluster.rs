@@ -2,14 +2,22 @@ use super::conn::Connection;
 
 use r2d2::{ManageConnection, Pool, PooledConnection};
 
+use crate::common::cluster::{Endpoint, FirstAvailable, SelectionStrategy, Timeouts, Transport};
+use crate::common::protocol::messages::{Request, Response};
+#[cfg(feature = "tls")]
+use crate::common::tls::TlsConnector;
 use crate::{OrientError, OrientResult};
+use std::io;
 use std::net::SocketAddr;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::time::Duration;
 
 pub type SyncConnection = PooledConnection<ServerConnectionManager>;
 
 pub struct Cluster {
     servers: Vec<Arc<Server>>,
+    strategy: Arc<dyn SelectionStrategy>,
 }
 
 impl Cluster {
@@ -18,31 +26,103 @@ impl Cluster {
     }
 
     pub(crate) fn connection(&self) -> OrientResult<(SyncConnection, Arc<Server>)> {
-        let conn = self.servers[0].connection()?;
-        Ok((conn, self.servers[0].clone()))
+        let server = self.select();
+        match server.connection() {
+            Ok(conn) => Ok((conn, server)),
+            Err(e) => {
+                for other in self.failover(&server) {
+                    if let Ok(conn) = other.connection() {
+                        return Ok((conn, other.clone()));
+                    }
+                }
+                Err(e)
+            }
+        }
     }
 
     pub(crate) fn select(&self) -> Arc<Server> {
-        self.servers[0].clone()
+        let healthy: Vec<bool> = self.servers.iter().map(|s| s.is_healthy()).collect();
+        // When every server is marked as failed give all of them another chance
+        let idx = self
+            .strategy
+            .select(&healthy)
+            .or_else(|| self.strategy.select(&vec![true; healthy.len()]))
+            .unwrap_or(0);
+        self.servers[idx].clone()
+    }
+
+    /// Send a request to `server`. Idempotent requests that do not belong to a session
+    /// are sent again to the other servers when `server` cannot be reached, the other
+    /// servers do not know the sessions opened on `server`.
+    /// Returns the server that answered along with the response
+    pub(crate) fn send(
+        &self,
+        server: &Arc<Server>,
+        request: Request,
+    ) -> OrientResult<(Arc<Server>, Response)> {
+        self.send_with_timeout(server, request, None)
+    }
+
+    /// Like `send`, waiting at most `timeout` for each response
+    /// instead of the request timeout of the connections
+    pub(crate) fn send_with_timeout(
+        &self,
+        server: &Arc<Server>,
+        request: Request,
+        timeout: Option<Duration>,
+    ) -> OrientResult<(Arc<Server>, Response)> {
+        let retry = if request.is_idempotent() && request.is_sessionless() {
+            Some(request.clone())
+        } else {
+            None
+        };
+        let mut error = match server.send(request, timeout) {
+            Err(e) if retry.is_some() && e.is_connection_error() => e,
+            result => return result.map(|response| (server.clone(), response)),
+        };
+        if let Some(request) = retry {
+            for other in self.failover(server) {
+                match other.send(request.clone(), timeout) {
+                    Err(e) if e.is_connection_error() => error = e,
+                    result => return result.map(|response| (other.clone(), response)),
+                }
+            }
+        }
+        Err(error)
+    }
+
+    fn failover<'a>(&'a self, failed: &'a Arc<Server>) -> impl Iterator<Item = &'a Arc<Server>> {
+        self.servers
+            .iter()
+            .filter(move |s| !Arc::ptr_eq(s, failed) && s.is_healthy())
     }
 }
 pub struct ClusterBuilder {
     pool_max: u32,
-    servers: Vec<SocketAddr>,
+    pool_timeout: Option<Duration>,
+    servers: Vec<(String, SocketAddr)>,
+    strategy: Arc<dyn SelectionStrategy>,
+    transport: Transport,
 }
 
 impl ClusterBuilder {
-    pub fn build(self) -> Cluster {
-        let pool_max = self.pool_max;
+    pub fn build(self) -> OrientResult<Cluster> {
         let servers = self
             .servers
-            .into_iter()
-            .map(|s| {
-                // handle unreachable servers
-                Arc::new(Server::new(s, pool_max).unwrap())
+            .iter()
+            .map(|(host, address)| {
+                let endpoint = self.transport.endpoint(host, *address)?;
+                Ok(Arc::new(Server::new(
+                    endpoint,
+                    self.pool_max,
+                    self.pool_timeout,
+                )))
             })
-            .collect();
-        Cluster { servers }
+            .collect::<OrientResult<_>>()?;
+        Ok(Cluster {
+            servers,
+            strategy: self.strategy,
+        })
     }
 
     pub fn pool_max(mut self, pool_max: u32) -> Self {
@@ -50,8 +130,31 @@ impl ClusterBuilder {
         self
     }
 
-    pub fn add_server<T: Into<SocketAddr>>(mut self, address: T) -> Self {
-        self.servers.push(address.into());
+    /// Time to wait for a pooled connection, the default of `r2d2` when `None`
+    pub(crate) fn pool_timeout(mut self, pool_timeout: Option<Duration>) -> Self {
+        self.pool_timeout = pool_timeout;
+        self
+    }
+
+    pub(crate) fn timeouts(mut self, timeouts: Timeouts) -> Self {
+        self.transport.timeouts = timeouts;
+        self
+    }
+
+    /// Add the server `host` resolved to `address`
+    pub fn add_server<T: Into<SocketAddr>>(mut self, host: &str, address: T) -> Self {
+        self.servers.push((String::from(host), address.into()));
+        self
+    }
+
+    pub fn strategy(mut self, strategy: Arc<dyn SelectionStrategy>) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    #[cfg(feature = "tls")]
+    pub(crate) fn tls(mut self, connector: TlsConnector) -> Self {
+        self.transport.tls = Some(connector);
         self
     }
 }
@@ -59,28 +162,87 @@ impl Default for ClusterBuilder {
     fn default() -> ClusterBuilder {
         ClusterBuilder {
             pool_max: 20,
+            pool_timeout: None,
             servers: vec![],
+            strategy: Arc::new(FirstAvailable),
+            transport: Transport::default(),
         }
     }
 }
 pub struct Server {
+    endpoint: Endpoint,
     pool: Pool<ServerConnectionManager>,
+    // Probes a failed server before waiting on the pool
+    manager: ServerConnectionManager,
+    // Cleared when a connection fails, set again by the pool when a connection is
+    // established or passes its check
+    healthy: Arc<AtomicBool>,
 }
 
 impl Server {
-    fn new(address: SocketAddr, pool_max: u32) -> OrientResult<Server> {
-        let manager = ServerConnectionManager { address };
-        let pool = Pool::builder().max_size(pool_max).build(manager)?;
+    fn new(endpoint: Endpoint, pool_max: u32, pool_timeout: Option<Duration>) -> Server {
+        let healthy = Arc::new(AtomicBool::new(true));
+        let manager = ServerConnectionManager {
+            endpoint: endpoint.clone(),
+            healthy: healthy.clone(),
+        };
+        let mut builder = Pool::builder().max_size(pool_max);
+        if let Some(timeout) = pool_timeout {
+            builder = builder.connection_timeout(timeout);
+        }
+        // Unreachable servers are marked as failed on the first connection attempt
+        let pool = builder.build_unchecked(manager.clone());
 
-        Ok(Server { pool })
+        Server {
+            endpoint,
+            pool,
+            manager,
+            healthy,
+        }
+    }
+
+    pub(crate) fn is_healthy(&self) -> bool {
+        self.healthy.load(Ordering::SeqCst)
     }
 
     pub(crate) fn connection(&self) -> OrientResult<PooledConnection<ServerConnectionManager>> {
-        self.pool.get().map_err(OrientError::from)
+        // The pool keeps trying to connect until its timeout, a server that failed
+        // is probed first so that it fails right away when still unreachable
+        if !self.is_healthy() {
+            self.manager.connect()?;
+        }
+        self.pool.get().map_err(|e| {
+            if self.is_healthy() {
+                OrientError::Timeout(format!("waiting for a pooled connection ({})", e))
+            } else {
+                // the pool could not establish a connection
+                OrientError::Io(io::Error::new(
+                    io::ErrorKind::NotConnected,
+                    format!("connecting to {} ({})", self.endpoint.address, e),
+                ))
+            }
+        })
+    }
+
+    /// A connection outside of the pool, owned by the caller
+    pub(crate) fn dedicated_connection(&self) -> OrientResult<Connection> {
+        Connection::connect_to(&self.endpoint)
+    }
+
+    fn send(&self, request: Request, timeout: Option<Duration>) -> OrientResult<Response> {
+        let result = self.connection()?.send_with_timeout(request, timeout);
+        if let Err(e) = &result {
+            if e.is_connection_error() {
+                self.healthy.store(false, Ordering::SeqCst);
+            }
+        }
+        result
     }
 }
+#[derive(Clone)]
 pub struct ServerConnectionManager {
-    address: SocketAddr,
+    endpoint: Endpoint,
+    healthy: Arc<AtomicBool>,
 }
 
 impl ManageConnection for ServerConnectionManager {
@@ -88,14 +250,80 @@ impl ManageConnection for ServerConnectionManager {
     type Error = OrientError;
 
     fn connect(&self) -> OrientResult<Connection> {
-        Connection::connect(&self.address)
+        let conn = Connection::connect_to(&self.endpoint);
+        self.healthy.store(conn.is_ok(), Ordering::SeqCst);
+        conn
     }
 
-    fn is_valid(&self, _conn: &mut Connection) -> OrientResult<()> {
+    fn is_valid(&self, conn: &mut Connection) -> OrientResult<()> {
+        conn.check()?;
+        self.healthy.store(true, Ordering::SeqCst);
         Ok(())
     }
 
-    fn has_broken(&self, _conn: &mut Connection) -> bool {
-        false
+    fn has_broken(&self, conn: &mut Connection) -> bool {
+        conn.is_broken()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Server;
+    use crate::common::cluster::Endpoint;
+    use crate::OrientError;
+    use std::io::Write;
+    use std::net::TcpListener;
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    // Answers the protocol version to every connection, keeping them open
+    fn accept(listener: TcpListener) {
+        thread::spawn(move || {
+            let mut sockets = vec![];
+            for socket in listener.incoming() {
+                let mut socket = socket.unwrap();
+                socket.write_all(&37i16.to_be_bytes()).unwrap();
+                sockets.push(socket);
+            }
+        });
+    }
+
+    #[test]
+    fn server_health() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        drop(listener);
+
+        let server = Server::new(
+            Endpoint::plain(address),
+            1,
+            Some(Duration::from_millis(500)),
+        );
+        assert!(matches!(server.connection(), Err(OrientError::Io(_))));
+        assert!(!server.is_healthy());
+
+        // A failed server is probed instead of waiting for the pool timeout
+        let started = Instant::now();
+        assert!(matches!(server.connection(), Err(OrientError::Io(_))));
+        assert!(started.elapsed() < Duration::from_millis(500));
+
+        // The server is marked as healthy again once a connection is established,
+        // by the probe or by the pool retrying in the background
+        accept(TcpListener::bind(address).unwrap());
+        assert!(server.connection().is_ok());
+        assert!(server.is_healthy());
+
+        // A busy pool times out without marking the server as failed
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let server = Server::new(
+            Endpoint::plain(listener.local_addr().unwrap()),
+            1,
+            Some(Duration::from_millis(200)),
+        );
+        accept(listener);
+        let busy = server.connection().unwrap();
+        assert!(matches!(server.connection(), Err(OrientError::Timeout(_))));
+        assert!(server.is_healthy());
+        drop(busy);
     }
 }
@@ -1,23 +1,49 @@
+use super::stream::Stream;
+use crate::common::cluster::{Endpoint, Timeouts};
 use crate::common::protocol::messages::request::HandShake;
 use crate::common::protocol::messages::{Request, Response};
 use crate::sync::protocol::WiredProtocol;
-use crate::OrientResult;
+use crate::{OrientError, OrientResult};
 use byteorder::{BigEndian, ReadBytesExt};
-use std::io::Write;
-use std::net::Shutdown;
-use std::net::{SocketAddr, TcpStream};
+use std::io::{self, Read, Write};
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
 
 pub struct Connection {
-    stream: TcpStream,
+    stream: Stream,
     protocol: WiredProtocol,
+    // The kind of push subscribed by the last request, to decode its response
+    subscription: Option<i8>,
+    timeouts: Timeouts,
+    // Set by the errors that leave the stream in an unknown state
+    broken: bool,
 }
 
 impl Connection {
-    pub fn connect(addr: &SocketAddr) -> OrientResult<Self> {
-        let mut stream = TcpStream::connect(addr)?;
-        let p = stream.read_i16::<BigEndian>()?;
+    pub fn connect(address: &SocketAddr) -> OrientResult<Self> {
+        Connection::connect_to(&Endpoint::plain(*address))
+    }
+
+    pub(crate) fn connect_to(endpoint: &Endpoint) -> OrientResult<Self> {
+        let timeouts = endpoint.timeouts;
+        let mut stream = Stream::connect(endpoint)?;
+        let p = match stream.read_i16::<BigEndian>() {
+            Err(e) if is_timeout(&e) => {
+                return Err(OrientError::timeout(
+                    "reading the protocol version",
+                    timeouts.io.unwrap_or_default(),
+                ))
+            }
+            result => result?,
+        };
         let protocol = WiredProtocol::from_version(p)?;
-        let conn = Connection { stream, protocol };
+        let conn = Connection {
+            stream,
+            protocol,
+            subscription: None,
+            timeouts,
+            broken: false,
+        };
         conn.handshake()
     }
 
@@ -32,17 +58,260 @@ impl Connection {
     }
 
     pub fn close(&mut self) -> OrientResult<()> {
-        self.stream.shutdown(Shutdown::Both)?;
-        Ok(())
+        self.stream.shutdown()
     }
 
     pub fn send_and_forget(&mut self, request: Request) -> OrientResult<()> {
         let buf = self.protocol.encode(request)?;
-        self.stream.write_all(buf.as_slice())?;
-        Ok(())
+        match self.stream.write_all(buf.as_slice()) {
+            Err(e) if is_timeout(&e) => {
+                let after = self.timeouts.io.unwrap_or_default();
+                Err(self.timed_out("writing the request", after))
+            }
+            Err(e) => {
+                self.broken = true;
+                Err(e.into())
+            }
+            Ok(()) => Ok(()),
+        }
     }
     pub fn send(&mut self, request: Request) -> OrientResult<Response> {
+        self.send_with_timeout(request, None)
+    }
+
+    /// Send a request waiting at most `timeout` for its response,
+    /// the request timeout of the connection when `None`
+    pub(crate) fn send_with_timeout(
+        &mut self,
+        request: Request,
+        timeout: Option<Duration>,
+    ) -> OrientResult<Response> {
+        let deadline = timeout
+            .or(self.timeouts.request)
+            .map(|timeout| (Instant::now() + timeout, timeout));
+        self.subscription = request.subscription();
         self.send_and_forget(request)?;
-        self.protocol.decode(&mut self.stream)
+        match deadline {
+            Some((deadline, timeout)) => self.receive_before(deadline, timeout),
+            None => self.receive(),
+        }
+    }
+
+    /// Read the next response or push from the server
+    pub(crate) fn receive(&mut self) -> OrientResult<Response> {
+        match self.protocol.decode(&mut self.stream, self.subscription) {
+            Err(OrientError::Io(e)) if is_timeout(&e) => {
+                let after = self.timeouts.io.unwrap_or_default();
+                Err(self.timed_out("reading the response", after))
+            }
+            result => self.track(result),
+        }
+    }
+
+    fn receive_before(&mut self, deadline: Instant, timeout: Duration) -> OrientResult<Response> {
+        let mut reader = Deadline {
+            stream: &mut self.stream,
+            deadline,
+            io: self.timeouts.io,
+        };
+        let result = self.protocol.decode(&mut reader, self.subscription);
+        let restored = self.stream.set_read_timeout(self.timeouts.io);
+        match result {
+            Err(OrientError::Io(e)) if is_timeout(&e) => {
+                if Instant::now() >= deadline {
+                    Err(self.timed_out("waiting for the response", timeout))
+                } else {
+                    let after = self.timeouts.io.unwrap_or_default();
+                    Err(self.timed_out("reading the response", after))
+                }
+            }
+            result => {
+                restored?;
+                self.track(result)
+            }
+        }
+    }
+
+    // Errors reported by the server are read whole, any other error may leave
+    // the rest of the message to the next request
+    fn track(&mut self, result: OrientResult<Response>) -> OrientResult<Response> {
+        match result {
+            Ok(_) | Err(OrientError::Request(_)) | Err(OrientError::ConcurrentModification(_)) => {}
+            Err(_) => {
+                self.broken = true;
+                let _ = self.stream.shutdown();
+            }
+        }
+        result
+    }
+
+    /// Whether an error left the connection unusable
+    pub(crate) fn is_broken(&self) -> bool {
+        self.broken
+    }
+
+    /// Check without a round trip that the connection can still be used
+    pub(crate) fn check(&mut self) -> OrientResult<()> {
+        if !self.broken && self.stream.is_closed() {
+            self.broken = true;
+        }
+        if self.broken {
+            return Err(OrientError::Io(io::Error::new(
+                io::ErrorKind::NotConnected,
+                "The connection is broken",
+            )));
+        }
+        Ok(())
+    }
+
+    // The rest of a message cut by a timeout would be read by the next
+    // request, the connection cannot be used anymore
+    fn timed_out(&mut self, what: &str, after: Duration) -> OrientError {
+        self.broken = true;
+        let _ = self.stream.shutdown();
+        OrientError::timeout(what, after)
+    }
+
+    /// Wait for the reads without limit, for the connections that only receive pushes
+    pub(crate) fn wait_pushes(&self) -> OrientResult<()> {
+        Ok(self.stream.set_read_timeout(None)?)
+    }
+
+    /// Another handle on the same socket, to read pushes on a thread
+    /// while requests are written on the other one
+    pub(crate) fn try_clone(&self) -> OrientResult<Connection> {
+        Ok(Connection {
+            stream: self.stream.try_clone()?,
+            protocol: self.protocol.clone(),
+            subscription: None,
+            timeouts: self.timeouts,
+            broken: self.broken,
+        })
+    }
+}
+
+// Bounds the reads of a response by the deadline of its request
+struct Deadline<'a> {
+    stream: &'a mut Stream,
+    deadline: Instant,
+    io: Option<Duration>,
+}
+
+impl Read for Deadline<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let remaining = self.deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return Err(io::ErrorKind::TimedOut.into());
+        }
+        let timeout = self.io.map_or(remaining, |io| io.min(remaining));
+        self.stream.set_read_timeout(Some(timeout))?;
+        self.stream.read(buf)
+    }
+}
+
+// Sockets report an expired read or write timeout with either kind
+fn is_timeout(e: &io::Error) -> bool {
+    matches!(
+        e.kind(),
+        io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Connection;
+    use crate::common::cluster::{Endpoint, Timeouts};
+    use crate::common::protocol::messages::request::Connect;
+    use crate::OrientError;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::thread;
+    use std::time::Duration;
+
+    // A server that accepts the handshake and never answers
+    fn silent_server() -> Endpoint {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        thread::spawn(move || {
+            let (mut socket, _) = listener.accept().unwrap();
+            socket.write_all(&37i16.to_be_bytes()).unwrap();
+            let mut buf = [0; 1024];
+            while matches!(socket.read(&mut buf), Ok(read) if read > 0) {}
+        });
+        Endpoint::plain(address)
+    }
+
+    #[test]
+    fn check_closed_connection() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let endpoint = Endpoint::plain(listener.local_addr().unwrap());
+        let server = thread::spawn(move || {
+            let (mut socket, _) = listener.accept().unwrap();
+            socket.write_all(&37i16.to_be_bytes()).unwrap();
+            socket
+        });
+        let mut conn = Connection::connect_to(&endpoint).unwrap();
+        let socket = server.join().unwrap();
+        assert!(conn.check().is_ok());
+
+        drop(socket);
+        thread::sleep(Duration::from_millis(50));
+        assert!(conn.check().is_err());
+        assert!(conn.is_broken());
+    }
+
+    #[test]
+    fn undecodable_response_breaks_connection() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let endpoint = Endpoint::plain(listener.local_addr().unwrap());
+        thread::spawn(move || {
+            let (mut socket, _) = listener.accept().unwrap();
+            socket.write_all(&37i16.to_be_bytes()).unwrap();
+            let mut buf = [0; 1024];
+            let _ = socket.read(&mut buf).unwrap();
+            // a push of an unknown type
+            socket.write_all(&[3, 99]).unwrap();
+            while matches!(socket.read(&mut buf), Ok(read) if read > 0) {}
+        });
+        let mut conn = Connection::connect_to(&endpoint).unwrap();
+
+        let result = conn.send(Connect::new("root", "root").into());
+        assert!(matches!(result, Err(OrientError::Protocol(_))));
+        assert!(conn.is_broken());
+        assert!(conn.check().is_err());
+    }
+
+    #[test]
+    fn request_timeout() {
+        let mut endpoint = silent_server();
+        endpoint.timeouts = Timeouts {
+            request: Some(Duration::from_millis(100)),
+            ..Default::default()
+        };
+        let mut conn = Connection::connect_to(&endpoint).unwrap();
+
+        let result = conn.send(Connect::new("root", "root").into());
+        assert!(matches!(result, Err(OrientError::Timeout(_))));
+    }
+
+    #[test]
+    fn statement_timeout_overrides_io_timeout() {
+        let mut endpoint = silent_server();
+        endpoint.timeouts = Timeouts {
+            io: Some(Duration::from_secs(10)),
+            ..Default::default()
+        };
+        let mut conn = Connection::connect_to(&endpoint).unwrap();
+
+        let result = conn.send_with_timeout(
+            Connect::new("root", "root").into(),
+            Some(Duration::from_millis(100)),
+        );
+        match result {
+            Err(OrientError::Timeout(what)) => {
+                assert!(what.starts_with("waiting for the response"), "{}", what)
+            }
+            other => panic!("unexpected result {:?}", other.map(|_| ())),
+        }
     }
 }
@@ -0,0 +1,219 @@
+use crate::common::cluster::Endpoint;
+use crate::{OrientError, OrientResult};
+use std::io::{self, Read, Write};
+use std::net::{Shutdown, TcpStream};
+use std::time::Duration;
+
+#[cfg(feature = "tls")]
+mod tls_use {
+    pub(crate) use crate::common::tls::tls_error;
+    pub use rustls::ClientConnection;
+    pub use std::sync::{Arc, Mutex, MutexGuard};
+}
+#[cfg(feature = "tls")]
+use tls_use::*;
+
+/// The socket of a connection, plain or encrypted
+pub(crate) enum Stream {
+    Plain(TcpStream),
+    #[cfg(feature = "tls")]
+    Tls(TlsStream),
+}
+
+impl Stream {
+    pub(crate) fn connect(endpoint: &Endpoint) -> OrientResult<Stream> {
+        let timeouts = endpoint.timeouts;
+        let socket = match timeouts.connect {
+            Some(timeout) => {
+                TcpStream::connect_timeout(&endpoint.address, timeout).map_err(|e| {
+                    if e.kind() == io::ErrorKind::TimedOut {
+                        OrientError::timeout(
+                            &format!("connecting to {}", endpoint.address),
+                            timeout,
+                        )
+                    } else {
+                        OrientError::from(e)
+                    }
+                })?
+            }
+            None => TcpStream::connect(endpoint.address)?,
+        };
+        socket.set_read_timeout(timeouts.io)?;
+        socket.set_write_timeout(timeouts.io)?;
+        #[cfg(feature = "tls")]
+        if let Some((connector, name)) = &endpoint.tls {
+            return TlsStream::handshake(socket, connector.config.clone(), name.clone())
+                .map(Stream::Tls);
+        }
+        Ok(Stream::Plain(socket))
+    }
+
+    /// Another handle on the same socket
+    pub(crate) fn try_clone(&self) -> OrientResult<Stream> {
+        match self {
+            Stream::Plain(socket) => Ok(Stream::Plain(socket.try_clone()?)),
+            #[cfg(feature = "tls")]
+            Stream::Tls(stream) => Ok(Stream::Tls(stream.try_clone()?)),
+        }
+    }
+
+    fn socket(&self) -> &TcpStream {
+        match self {
+            Stream::Plain(socket) => socket,
+            #[cfg(feature = "tls")]
+            Stream::Tls(stream) => &stream.socket,
+        }
+    }
+
+    /// Bound the time spent by the next reads, `None` waits forever
+    pub(crate) fn set_read_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+        self.socket().set_read_timeout(timeout)
+    }
+
+    /// Whether the server closed the socket, looking at it without blocking
+    pub(crate) fn is_closed(&self) -> bool {
+        let socket = self.socket();
+        if socket.set_nonblocking(true).is_err() {
+            return true;
+        }
+        let closed = match socket.peek(&mut [0]) {
+            Ok(read) => read == 0,
+            Err(e) => e.kind() != io::ErrorKind::WouldBlock,
+        };
+        socket.set_nonblocking(false).is_err() || closed
+    }
+
+    pub(crate) fn shutdown(&self) -> OrientResult<()> {
+        match self {
+            Stream::Plain(socket) => socket.shutdown(Shutdown::Both)?,
+            #[cfg(feature = "tls")]
+            Stream::Tls(stream) => stream.shutdown()?,
+        }
+        Ok(())
+    }
+}
+
+impl Read for Stream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Stream::Plain(socket) => socket.read(buf),
+            #[cfg(feature = "tls")]
+            Stream::Tls(stream) => stream.read(buf),
+        }
+    }
+}
+
+impl Write for Stream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Stream::Plain(socket) => socket.write(buf),
+            #[cfg(feature = "tls")]
+            Stream::Tls(stream) => stream.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Stream::Plain(socket) => socket.flush(),
+            #[cfg(feature = "tls")]
+            Stream::Tls(stream) => stream.flush(),
+        }
+    }
+}
+
+/// A TLS session shared by the clones of a connection.
+///
+/// The socket is read without holding the session, so that a thread waiting
+/// for pushes does not block the requests written by another one.
+#[cfg(feature = "tls")]
+pub(crate) struct TlsStream {
+    session: Arc<Mutex<ClientConnection>>,
+    socket: TcpStream,
+}
+
+#[cfg(feature = "tls")]
+impl TlsStream {
+    fn handshake(
+        mut socket: TcpStream,
+        config: Arc<rustls::ClientConfig>,
+        name: rustls::pki_types::ServerName<'static>,
+    ) -> OrientResult<TlsStream> {
+        let mut session = ClientConnection::new(config, name).map_err(tls_error)?;
+        while session.is_handshaking() {
+            session.complete_io(&mut socket)?;
+        }
+        Ok(TlsStream {
+            session: Arc::new(Mutex::new(session)),
+            socket,
+        })
+    }
+
+    fn try_clone(&self) -> io::Result<TlsStream> {
+        Ok(TlsStream {
+            session: self.session.clone(),
+            socket: self.socket.try_clone()?,
+        })
+    }
+
+    fn lock(&self) -> MutexGuard<'_, ClientConnection> {
+        self.session.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn write_records(&self, session: &mut ClientConnection) -> io::Result<()> {
+        while session.wants_write() {
+            session.write_tls(&mut &self.socket)?;
+        }
+        Ok(())
+    }
+
+    fn shutdown(&self) -> io::Result<()> {
+        let mut session = self.lock();
+        session.send_close_notify();
+        let _ = self.write_records(&mut session);
+        self.socket.shutdown(Shutdown::Both)
+    }
+}
+
+#[cfg(feature = "tls")]
+impl Read for TlsStream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut records = [0; 4096];
+        loop {
+            match self.lock().reader().read(buf) {
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => {}
+                result => return result,
+            }
+            let read = self.socket.read(&mut records)?;
+            if read == 0 {
+                return Ok(0);
+            }
+            let mut session = self.lock();
+            let mut received = &records[..read];
+            while !received.is_empty() {
+                session.read_tls(&mut received)?;
+                if let Err(e) = session.process_new_packets() {
+                    // Let the server know why the session ended
+                    let _ = self.write_records(&mut session);
+                    return Err(io::Error::new(io::ErrorKind::InvalidData, e));
+                }
+            }
+            self.write_records(&mut session)?;
+        }
+    }
+}
+
+#[cfg(feature = "tls")]
+impl Write for TlsStream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut session = self.lock();
+        let written = session.writer().write(buf)?;
+        self.write_records(&mut session)?;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        let mut session = self.lock();
+        session.writer().flush()?;
+        self.write_records(&mut session)
+    }
+}
@@ -1,7 +1,11 @@
 use crate::common::protocol::messages::response::{
-    Connect, CreateDB, DropDB, ExistDB, Header, Open, Query, QueryClose, ServerQuery,
+    BeginTransaction, CommitTransaction, Connect, CreateDB, CreateRecord, DeleteRecord, DropDB,
+    ExistDB, Header, LiveQuery, LiveQueryResult, LoadRecord, Open, Query, QueryClose,
+    RollbackTransaction, SBTreeEntriesMajor, SBTreeFirstKey, ServerQuery, SubscribePush,
+    UnsubscribeLiveQuery, UpdateRecord,
 };
 use crate::common::types::error::RequestError;
+use crate::common::types::push::PushEvent;
 use crate::OrientResult;
 use std::io::Read;
 
@@ -22,5 +26,26 @@ pub trait VersionedDecoder {
         Ok(QueryClose {})
     }
 
+    fn decode_live_query<R: Read>(buf: &mut R) -> OrientResult<LiveQuery>;
+    fn decode_live_query_result<R: Read>(buf: &mut R) -> OrientResult<LiveQueryResult>;
+    fn decode_unsubscribe_live_query<R: Read>(_buf: &mut R) -> OrientResult<UnsubscribeLiveQuery> {
+        Ok(UnsubscribeLiveQuery {})
+    }
+    fn decode_subscribe_push<R: Read>(_buf: &mut R) -> OrientResult<SubscribePush> {
+        Ok(SubscribePush {})
+    }
+    fn decode_push_event<R: Read>(op: i8, buf: &mut R) -> OrientResult<PushEvent>;
+
     fn decode_server_query<R: Read>(buf: &mut R) -> OrientResult<ServerQuery>;
+    fn decode_load_record<R: Read>(buf: &mut R) -> OrientResult<LoadRecord>;
+    fn decode_sbtree_first_key<R: Read>(buf: &mut R) -> OrientResult<SBTreeFirstKey>;
+    fn decode_sbtree_entries_major<R: Read>(buf: &mut R) -> OrientResult<SBTreeEntriesMajor>;
+    fn decode_create_record<R: Read>(buf: &mut R) -> OrientResult<CreateRecord>;
+    fn decode_update_record<R: Read>(buf: &mut R) -> OrientResult<UpdateRecord>;
+    fn decode_delete_record<R: Read>(buf: &mut R) -> OrientResult<DeleteRecord>;
+    fn decode_begin_transaction<R: Read>(buf: &mut R) -> OrientResult<BeginTransaction>;
+    fn decode_commit_transaction<R: Read>(buf: &mut R) -> OrientResult<CommitTransaction>;
+    fn decode_rollback_transaction<R: Read>(_buf: &mut R) -> OrientResult<RollbackTransaction> {
+        Ok(RollbackTransaction {})
+    }
 }
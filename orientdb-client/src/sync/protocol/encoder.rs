@@ -1,7 +1,10 @@
 use crate::common::protocol::buffer::OBuffer;
 use crate::common::protocol::messages::request::{
-    Close as ReqClose, Connect, CreateDB, DropDB, ExistDB, HandShake, LiveQuery, Open as ReqOpen,
-    Query as ReqQuery, QueryClose, QueryNext, ServerQuery, UnsubscribeLiveQuery,
+    BeginTransaction, Close as ReqClose, CommitTransaction, Connect, CreateDB, CreateRecord,
+    DeleteRecord, DropDB, ExistDB, HandShake, LiveQuery, LoadRecord, Open as ReqOpen,
+    Query as ReqQuery, QueryClose, QueryNext, RollbackTransaction, SBTreeEntriesMajor,
+    SBTreeFirstKey, ServerQuery, SubscribeDistributedConfig, SubscribePush, UnsubscribeLiveQuery,
+    UpdateRecord,
 };
 
 use crate::OrientError;
@@ -16,6 +19,14 @@ pub trait VersionedEncoder {
         buf: &mut OBuffer,
         unsubscribe: UnsubscribeLiveQuery,
     ) -> Result<(), OrientError>;
+    fn encode_subscribe_distributed_config(
+        buf: &mut OBuffer,
+        subscribe: SubscribeDistributedConfig,
+    ) -> Result<(), OrientError>;
+    fn encode_subscribe_push(
+        buf: &mut OBuffer,
+        subscribe: SubscribePush,
+    ) -> Result<(), OrientError>;
     fn encode_query_next(buf: &mut OBuffer, next: QueryNext) -> Result<(), OrientError>;
     fn encode_query_close(buf: &mut OBuffer, close: QueryClose) -> Result<(), OrientError>;
     fn encode_connect(buf: &mut OBuffer, close: Connect) -> Result<(), OrientError>;
@@ -23,4 +34,28 @@ pub trait VersionedEncoder {
     fn encode_exist_db(buf: &mut OBuffer, close: ExistDB) -> Result<(), OrientError>;
     fn encode_drop_db(buf: &mut OBuffer, close: DropDB) -> Result<(), OrientError>;
     fn encode_server_query(buf: &mut OBuffer, query: ServerQuery) -> Result<(), OrientError>;
+    fn encode_load_record(buf: &mut OBuffer, load: LoadRecord) -> Result<(), OrientError>;
+    fn encode_sbtree_first_key(
+        buf: &mut OBuffer,
+        first_key: SBTreeFirstKey,
+    ) -> Result<(), OrientError>;
+    fn encode_sbtree_entries_major(
+        buf: &mut OBuffer,
+        entries: SBTreeEntriesMajor,
+    ) -> Result<(), OrientError>;
+    fn encode_create_record(buf: &mut OBuffer, create: CreateRecord) -> Result<(), OrientError>;
+    fn encode_update_record(buf: &mut OBuffer, update: UpdateRecord) -> Result<(), OrientError>;
+    fn encode_delete_record(buf: &mut OBuffer, delete: DeleteRecord) -> Result<(), OrientError>;
+    fn encode_begin_transaction(
+        buf: &mut OBuffer,
+        begin: BeginTransaction,
+    ) -> Result<(), OrientError>;
+    fn encode_commit_transaction(
+        buf: &mut OBuffer,
+        commit: CommitTransaction,
+    ) -> Result<(), OrientError>;
+    fn encode_rollback_transaction(
+        buf: &mut OBuffer,
+        rollback: RollbackTransaction,
+    ) -> Result<(), OrientError>;
 }
@@ -1,7 +1,7 @@
 pub mod reader {
 
     use crate::common::types::rid::ORecordID;
-    use crate::OrientResult;
+    use crate::{OrientError, OrientResult};
     use byteorder::{BigEndian, ReadBytesExt};
     use std::io::Read;
 
@@ -36,7 +36,12 @@ pub mod reader {
         let exhists = match e {
             0 => false,
             1 => true,
-            _ => panic!("Cannot convert value to bool"),
+            _ => {
+                return Err(OrientError::Decoder(format!(
+                    "Cannot convert value {} to bool",
+                    e
+                )))
+            }
         };
         Ok(exhists)
     }
@@ -71,4 +76,28 @@ pub mod reader {
         let res = String::from_utf8(bytes)?;
         Ok(res)
     }
+
+    /// Counts the bytes read, to tell where a response failed to decode
+    pub struct Counted<'a, R> {
+        inner: &'a mut R,
+        count: u64,
+    }
+
+    impl<'a, R: Read> Counted<'a, R> {
+        pub fn new(inner: &'a mut R) -> Counted<'a, R> {
+            Counted { inner, count: 0 }
+        }
+
+        pub fn count(&self) -> u64 {
+            self.count
+        }
+    }
+
+    impl<R: Read> Read for Counted<'_, R> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let read = self.inner.read(buf)?;
+            self.count += read as u64;
+            Ok(read)
+        }
+    }
 }
@@ -5,13 +5,15 @@ pub(crate) mod io;
 pub(crate) mod v37;
 use self::decoder::VersionedDecoder;
 use self::encoder::VersionedEncoder;
+use self::io::reader::Counted;
 use crate::common::protocol::buffer::OBuffer;
 
-use crate::common::protocol::messages::response::Status;
+use crate::common::protocol::messages::response::{Header, ResponseType, Status};
 use crate::common::protocol::messages::{Request, Response};
 
 use crate::{OrientError, OrientResult};
 
+#[allow(unused_imports)]
 pub mod messages {
 
     pub mod request {
@@ -66,14 +68,41 @@ impl WiredProtocol {
             Request::UnsubscribeLiveQuery(unsubscribe) => {
                 T::encode_unsubscribe_live_query(&mut buffer, unsubscribe)
             }
+            Request::SubscribeDistributedConfig(subscribe) => {
+                T::encode_subscribe_distributed_config(&mut buffer, subscribe)
+            }
+            Request::SubscribePush(subscribe) => T::encode_subscribe_push(&mut buffer, subscribe),
+            Request::LoadRecord(load) => T::encode_load_record(&mut buffer, load),
+            Request::SBTreeFirstKey(first_key) => {
+                T::encode_sbtree_first_key(&mut buffer, first_key)
+            }
+            Request::SBTreeEntriesMajor(entries) => {
+                T::encode_sbtree_entries_major(&mut buffer, entries)
+            }
+            Request::CreateRecord(create) => T::encode_create_record(&mut buffer, create),
+            Request::UpdateRecord(update) => T::encode_update_record(&mut buffer, update),
+            Request::DeleteRecord(delete) => T::encode_delete_record(&mut buffer, delete),
+            Request::BeginTransaction(begin) => T::encode_begin_transaction(&mut buffer, begin),
+            Request::CommitTransaction(commit) => T::encode_commit_transaction(&mut buffer, commit),
+            Request::RollbackTransaction(rollback) => {
+                T::encode_rollback_transaction(&mut buffer, rollback)
+            }
         }?;
 
         Ok(buffer)
     }
 
-    pub fn decode<R: Read>(&mut self, buf: &mut R) -> OrientResult<Response> {
+    /// Decode the next response or push.
+    ///
+    /// `subscription` is the kind of push subscribed by the pending request,
+    /// as the responses to the subscribe message have no other way to be told apart
+    pub fn decode<R: Read>(
+        &mut self,
+        buf: &mut R,
+        subscription: Option<i8>,
+    ) -> OrientResult<Response> {
         if self.version >= 37 {
-            return self.decode_with::<R, Protocol37>(buf);
+            return self.decode_with::<R, Protocol37>(buf, subscription);
         }
         Err(OrientError::Protocol(format!(
             "Protocol {} not supported",
@@ -84,24 +113,244 @@ impl WiredProtocol {
     pub fn decode_with<R: Read, T: VersionedDecoder>(
         &mut self,
         buf: &mut R,
+        subscription: Option<i8>,
     ) -> OrientResult<Response> {
         let header = T::decode_header(buf)?;
 
+        let mut counted = Counted::new(buf);
+        let payload = Self::decode_payload::<_, T>(&header, &mut counted, subscription).map_err(
+            |e| match e {
+                OrientError::Decoder(message) => OrientError::Decoder(format!(
+                    "Error decoding response after {} bytes: {}",
+                    counted.count(),
+                    message
+                )),
+                e => e,
+            },
+        )?;
+        Ok(Response::new(header, payload))
+    }
+
+    fn decode_payload<R: Read, T: VersionedDecoder>(
+        header: &Header,
+        buf: &mut R,
+        subscription: Option<i8>,
+    ) -> OrientResult<ResponseType> {
         let payload = match header.status {
-            Status::ERROR => return Err(OrientError::Request(T::decode_errors(buf)?)),
-            _ => match header.op {
+            Status::ERROR => return Err(T::decode_errors(buf)?.into()),
+            Status::PUSH => match header.op {
+                81 => T::decode_live_query_result(buf)?.into(),
+                82..=86 => T::decode_push_event(header.op, buf)?.into(),
+                _ => {
+                    return Err(OrientError::Protocol(format!(
+                        "Push {:?} not supported",
+                        header
+                    )))
+                }
+            },
+            Status::OK => match header.op {
                 2 => T::decode_connect(buf)?.into(),
                 3 => T::decode_open(buf)?.into(),
                 4 => T::decode_create_db(buf)?.into(),
                 6 => T::decode_exist(buf)?.into(),
                 7 => T::decode_drop_db(buf)?.into(),
+                30 => T::decode_load_record(buf)?.into(),
+                31 => T::decode_create_record(buf)?.into(),
+                32 => T::decode_update_record(buf)?.into(),
+                33 => T::decode_delete_record(buf)?.into(),
                 45 => T::decode_query(buf)?.into(),
                 46 => T::decode_query_close(buf)?.into(),
                 47 => T::decode_query(buf)?.into(),
                 50 => T::decode_server_query(buf)?.into(),
-                _ => panic!("Request {} not supported", header.op),
+                60 => T::decode_commit_transaction(buf)?.into(),
+                73 => T::decode_begin_transaction(buf)?.into(),
+                74 => T::decode_rollback_transaction(buf)?.into(),
+                112 => T::decode_sbtree_first_key(buf)?.into(),
+                113 => T::decode_sbtree_entries_major(buf)?.into(),
+                100 => match subscription {
+                    Some(2) | None => T::decode_live_query(buf)?.into(),
+                    Some(_) => T::decode_subscribe_push(buf)?.into(),
+                },
+                101 => T::decode_unsubscribe_live_query(buf)?.into(),
+                _ => {
+                    return Err(OrientError::Protocol(format!(
+                        "Request {:?} not supported",
+                        header
+                    )))
+                }
             },
         };
-        Ok(Response::new(header, payload))
+        Ok(payload)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::WiredProtocol;
+    use crate::common::protocol::messages::response::{
+        ExistDB, LiveQuery, LiveQueryResult, LoadRecord, Status, SubscribePush,
+    };
+    use crate::common::protocol::serializer::DocumentSerializer;
+    use crate::sync::protocol::v37::Protocol37;
+    use crate::types::{ODocument, PushEvent};
+    use crate::OrientError;
+
+    #[test]
+    fn decode_live_query_responses() {
+        let mut protocol = WiredProtocol::from_version(37).unwrap();
+
+        let mut buf = vec![0];
+        buf.extend_from_slice(&1i32.to_be_bytes());
+        buf.extend_from_slice(&(-1i32).to_be_bytes());
+        buf.push(100);
+        buf.extend_from_slice(&7i32.to_be_bytes());
+        // live query end push, without events
+        buf.extend_from_slice(&[3, 81]);
+        buf.extend_from_slice(&7i32.to_be_bytes());
+        buf.push(2);
+        buf.extend_from_slice(&0i32.to_be_bytes());
+        let mut input = buf.as_slice();
+
+        let live: LiveQuery = protocol.decode(&mut input, Some(2)).unwrap().payload();
+        assert_eq!(7, live.monitor_id);
+
+        let mut response = protocol.decode(&mut input, None).unwrap();
+        assert_eq!(Status::PUSH, response.header.status);
+        let result: LiveQueryResult = response.payload();
+        assert_eq!(7, result.monitor_id);
+        assert!(result.ended);
+        assert!(result.events.is_empty());
+        assert!(input.is_empty());
+    }
+
+    #[test]
+    fn decode_push_responses() {
+        let mut protocol = WiredProtocol::from_version(37).unwrap();
+
+        let mut schema = ODocument::empty();
+        schema.set("schemaVersion", 4);
+        let schema = Protocol37::encode_document(&schema).unwrap();
+        let schema = schema.as_slice();
+
+        let mut buf = vec![0];
+        buf.extend_from_slice(&1i32.to_be_bytes());
+        buf.extend_from_slice(&(-1i32).to_be_bytes());
+        buf.push(100);
+        buf.extend_from_slice(&[3, 83]);
+        buf.extend_from_slice(&(schema.len() as i32).to_be_bytes());
+        buf.extend_from_slice(schema);
+        buf.extend_from_slice(&[3, 85]);
+        let mut input = buf.as_slice();
+
+        let _: SubscribePush = protocol.decode(&mut input, Some(4)).unwrap().payload();
+
+        match protocol.decode(&mut input, None).unwrap().payload() {
+            PushEvent::Schema(schema) => assert_eq!(4, schema.get::<i32>("schemaVersion")),
+            event => panic!("unexpected push {:?}", event),
+        }
+        let event: PushEvent = protocol.decode(&mut input, None).unwrap().payload();
+        assert!(matches!(event, PushEvent::Functions));
+        assert!(input.is_empty());
+    }
+
+    fn ok_header(buf: &mut Vec<u8>, op: u8) {
+        buf.push(0);
+        buf.extend_from_slice(&1i32.to_be_bytes());
+        buf.extend_from_slice(&(-1i32).to_be_bytes());
+        buf.push(op);
+    }
+
+    #[test]
+    fn decode_load_record_responses() {
+        let mut protocol = WiredProtocol::from_version(37).unwrap();
+
+        let mut doc = ODocument::new("V");
+        doc.set("name", "John");
+        let content = Protocol37::encode_document(&doc).unwrap();
+        let content = content.as_slice();
+
+        let mut buf = vec![];
+        // a missing record, followed by the response to the next request
+        ok_header(&mut buf, 30);
+        buf.push(0);
+        ok_header(&mut buf, 6);
+        buf.push(1);
+        // a record with a prefetched null record, record id and record
+        ok_header(&mut buf, 30);
+        buf.extend_from_slice(&[1, 100]);
+        buf.extend_from_slice(&3i32.to_be_bytes());
+        buf.extend_from_slice(&(content.len() as i32).to_be_bytes());
+        buf.extend_from_slice(content);
+        buf.push(2);
+        buf.extend_from_slice(&(-2i16).to_be_bytes());
+        buf.push(2);
+        buf.extend_from_slice(&(-3i16).to_be_bytes());
+        buf.extend_from_slice(&10i16.to_be_bytes());
+        buf.extend_from_slice(&1i64.to_be_bytes());
+        buf.push(2);
+        buf.extend_from_slice(&0i16.to_be_bytes());
+        buf.push(100);
+        buf.extend_from_slice(&10i16.to_be_bytes());
+        buf.extend_from_slice(&2i64.to_be_bytes());
+        buf.extend_from_slice(&1i32.to_be_bytes());
+        buf.extend_from_slice(&(content.len() as i32).to_be_bytes());
+        buf.extend_from_slice(content);
+        buf.push(0);
+        ok_header(&mut buf, 6);
+        buf.push(0);
+        let mut input = buf.as_slice();
+
+        let missing: LoadRecord = protocol.decode(&mut input, None).unwrap().payload();
+        assert!(missing.record.is_none());
+        let exist: ExistDB = protocol.decode(&mut input, None).unwrap().payload();
+        assert!(exist.exist);
+
+        let load: LoadRecord = protocol.decode(&mut input, None).unwrap().payload();
+        let record = load.record.unwrap();
+        assert_eq!(3, record.version());
+        assert_eq!("John", record.get::<String>("name"));
+        let exist: ExistDB = protocol.decode(&mut input, None).unwrap().payload();
+        assert!(!exist.exist);
+        assert!(input.is_empty());
+    }
+
+    #[test]
+    fn decode_unsupported_result_type() {
+        let mut protocol = WiredProtocol::from_version(37).unwrap();
+
+        let mut buf = vec![];
+        ok_header(&mut buf, 45);
+        buf.extend_from_slice(&1i32.to_be_bytes());
+        buf.push(b'q');
+        // no changes and no execution plan
+        buf.extend_from_slice(&[0, 0]);
+        buf.extend_from_slice(&0i32.to_be_bytes());
+        buf.extend_from_slice(&1i32.to_be_bytes());
+        buf.push(9);
+        let mut input = buf.as_slice();
+
+        match protocol.decode(&mut input, None) {
+            Err(OrientError::Decoder(message)) => assert_eq!(
+                "Error decoding response after 16 bytes: Unsupported result type 9",
+                message
+            ),
+            result => panic!("Expected a decoder error, got {:?}", result.map(|_| ())),
+        }
+    }
+
+    #[test]
+    fn decode_unknown_response() {
+        let mut protocol = WiredProtocol::from_version(37).unwrap();
+
+        let mut buf = vec![0];
+        buf.extend_from_slice(&1i32.to_be_bytes());
+        buf.extend_from_slice(&(-1i32).to_be_bytes());
+        buf.push(99);
+        let mut input = buf.as_slice();
+
+        match protocol.decode(&mut input, None) {
+            Err(OrientError::Protocol(_)) => {}
+            result => panic!("Expected a protocol error, got {:?}", result.map(|_| ())),
+        }
     }
 }
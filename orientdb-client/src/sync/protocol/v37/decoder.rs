@@ -1,13 +1,18 @@
 use super::super::v37::Protocol37;
 use crate::common::protocol::deserializer::DocumentDeserializer;
 use crate::common::protocol::messages::response::{
-    Connect, ExistDB, Header, Open, Query, ServerQuery, Status,
+    BeginTransaction, CommitTransaction, Connect, CreateRecord, DeleteRecord, ExistDB, Header,
+    LiveQuery, LiveQueryResult, LoadRecord, Open, Query, SBTreeEntriesMajor, SBTreeFirstKey,
+    ServerQuery, Status, UpdateRecord,
 };
+use crate::common::protocol::v37::{decode_bag_entries, decode_bag_key};
 use crate::common::types::error::{OError, RequestError};
-use crate::common::types::OResult;
+use crate::common::types::push::{PushEvent, StorageConfiguration};
+use crate::common::types::{LiveResult, ODocument, OResult};
 use crate::sync::protocol::decoder::VersionedDecoder;
-use crate::OrientResult;
+use crate::{OrientError, OrientResult};
 use std::collections::{HashMap, VecDeque};
+use std::convert::TryFrom;
 use std::io::Read;
 
 use crate::sync::protocol::io::reader;
@@ -15,6 +20,17 @@ use crate::sync::protocol::io::reader;
 impl VersionedDecoder for Protocol37 {
     fn decode_header<R: Read>(buf: &mut R) -> OrientResult<Header> {
         let status = reader::read_i8(buf)?;
+        if status == 3 {
+            // pushes carry the push type in place of the request
+            let op = reader::read_i8(buf)?;
+            return Ok(Header {
+                status: Status::from(status),
+                client_id: None,
+                session_id: -1,
+                token: None,
+                op,
+            });
+        }
         let session_id = reader::read_i32(buf)?;
         let token = reader::read_optional_bytes(buf)?;
         let op = reader::read_i8(buf)?;
@@ -69,6 +85,57 @@ impl VersionedDecoder for Protocol37 {
         ))
     }
 
+    fn decode_live_query<R: Read>(buf: &mut R) -> OrientResult<LiveQuery> {
+        let monitor_id = reader::read_i32(buf)?;
+        Ok(LiveQuery { monitor_id })
+    }
+
+    fn decode_live_query_result<R: Read>(buf: &mut R) -> OrientResult<LiveQueryResult> {
+        let monitor_id = reader::read_i32(buf)?;
+        let status = reader::read_i8(buf)?;
+        let n_of_events = reader::read_i32(buf)?;
+
+        let mut events = vec![];
+        for _ in 0..n_of_events {
+            let e_type = reader::read_i8(buf)?;
+            let event = match e_type {
+                1 => LiveResult::Created(read_result(buf)?),
+                2 => {
+                    let result = read_result(buf)?;
+                    let before = read_result(buf)?;
+                    LiveResult::Updated((before, result))
+                }
+                3 => LiveResult::Deleted(read_result(buf)?),
+                _ => {
+                    return Err(OrientError::Decoder(format!(
+                        "Unsupported live query event {}",
+                        e_type
+                    )))
+                }
+            };
+            events.push(event);
+        }
+
+        Ok(LiveQueryResult::new(monitor_id, status == 2, events))
+    }
+
+    fn decode_push_event<R: Read>(op: i8, buf: &mut R) -> OrientResult<PushEvent> {
+        match op {
+            82 => Ok(PushEvent::StorageConfiguration(Box::new(
+                read_storage_configuration(buf)?,
+            ))),
+            83 => Ok(PushEvent::Schema(Protocol37::decode_document(
+                &reader::read_bytes(buf)?,
+            )?)),
+            84 => Ok(PushEvent::IndexManager(Protocol37::decode_document(
+                &reader::read_bytes(buf)?,
+            )?)),
+            85 => Ok(PushEvent::Functions),
+            86 => Ok(PushEvent::Sequences),
+            _ => Err(OrientError::Decoder(format!("Unsupported push {}", op))),
+        }
+    }
+
     fn decode_server_query<R: Read>(buf: &mut R) -> OrientResult<ServerQuery> {
         let query_id = reader::read_string(buf)?;
         let changes = reader::read_bool(buf)?;
@@ -96,6 +163,95 @@ impl VersionedDecoder for Protocol37 {
         ))
     }
 
+    fn decode_load_record<R: Read>(buf: &mut R) -> OrientResult<LoadRecord> {
+        // Nothing follows the status of a missing record
+        if reader::read_i8(buf)? == 0 {
+            return Ok(LoadRecord::new(None));
+        }
+        let r_type = reader::read_i8(buf)?;
+        let version = reader::read_i32(buf)?;
+        let content = reader::read_bytes(buf)?;
+        // records prefetched by the fetch plan are not cached, skip them
+        while reader::read_i8(buf)? == 2 {
+            match reader::read_i16(buf)? {
+                // null record
+                -2 => {}
+                // record id only
+                -3 => {
+                    let _identity = reader::read_identity(buf)?;
+                }
+                _ => {
+                    let _r_type = reader::read_i8(buf)?;
+                    let _identity = reader::read_identity(buf)?;
+                    let _version = reader::read_i32(buf)?;
+                    let _content = reader::read_bytes(buf)?;
+                }
+            }
+        }
+        Ok(LoadRecord::new(Some(read_document(
+            r_type, version, &content,
+        )?)))
+    }
+
+    fn decode_sbtree_first_key<R: Read>(buf: &mut R) -> OrientResult<SBTreeFirstKey> {
+        let stream = reader::read_bytes(buf)?;
+        Ok(SBTreeFirstKey::new(decode_bag_key(&stream)?))
+    }
+
+    fn decode_sbtree_entries_major<R: Read>(buf: &mut R) -> OrientResult<SBTreeEntriesMajor> {
+        let stream = reader::read_bytes(buf)?;
+        Ok(SBTreeEntriesMajor::new(decode_bag_entries(&stream)?))
+    }
+
+    fn decode_create_record<R: Read>(buf: &mut R) -> OrientResult<CreateRecord> {
+        let identity = reader::read_identity(buf)?;
+        let version = reader::read_i32(buf)?;
+        read_collection_changes(buf)?;
+        Ok(CreateRecord::new(identity, version))
+    }
+
+    fn decode_update_record<R: Read>(buf: &mut R) -> OrientResult<UpdateRecord> {
+        let version = reader::read_i32(buf)?;
+        read_collection_changes(buf)?;
+        Ok(UpdateRecord::new(version))
+    }
+
+    fn decode_delete_record<R: Read>(buf: &mut R) -> OrientResult<DeleteRecord> {
+        let deleted = reader::read_bool(buf)?;
+        Ok(DeleteRecord::new(deleted))
+    }
+
+    fn decode_begin_transaction<R: Read>(buf: &mut R) -> OrientResult<BeginTransaction> {
+        let tx_id = reader::read_i32(buf)?;
+        let size = reader::read_i32(buf)?;
+        let mut updated_ids = vec![];
+        for _ in 0..size {
+            let current = reader::read_identity(buf)?;
+            let updated = reader::read_identity(buf)?;
+            updated_ids.push((current, updated));
+        }
+        Ok(BeginTransaction::new(tx_id, updated_ids))
+    }
+
+    fn decode_commit_transaction<R: Read>(buf: &mut R) -> OrientResult<CommitTransaction> {
+        let size = reader::read_i32(buf)?;
+        let mut created = vec![];
+        for _ in 0..size {
+            let current = reader::read_identity(buf)?;
+            let created_rid = reader::read_identity(buf)?;
+            created.push((current, created_rid));
+        }
+        let size = reader::read_i32(buf)?;
+        let mut updated = vec![];
+        for _ in 0..size {
+            let rid = reader::read_identity(buf)?;
+            let version = reader::read_i32(buf)?;
+            updated.push((rid, version));
+        }
+        read_collection_changes(buf)?;
+        Ok(CommitTransaction::new(created, updated))
+    }
+
     fn decode_errors<R: Read>(buf: &mut R) -> OrientResult<RequestError> {
         let code = reader::read_i32(buf)?;
         let identifier = reader::read_i32(buf)?;
@@ -122,6 +278,63 @@ impl VersionedDecoder for Protocol37 {
     }
 }
 
+fn read_storage_configuration<R: Read>(buf: &mut R) -> OrientResult<StorageConfiguration> {
+    let date_format = reader::read_string(buf)?;
+    let date_time_format = reader::read_string(buf)?;
+    let name = reader::read_string(buf)?;
+    let version = reader::read_i32(buf)?;
+    let directory = reader::read_string(buf)?;
+    let mut properties = HashMap::new();
+    for _ in 0..reader::read_i32(buf)? {
+        let key = reader::read_string(buf)?;
+        let value = reader::read_string(buf)?;
+        properties.insert(key, value);
+    }
+    let schema_record_id = reader::read_identity(buf)?;
+    let index_manager_record_id = reader::read_identity(buf)?;
+    let cluster_selection = reader::read_string(buf)?;
+    let conflict_strategy = reader::read_string(buf)?;
+    let validation_enabled = reader::read_bool(buf)?;
+    let locale_language = reader::read_string(buf)?;
+    let minimum_clusters = reader::read_i32(buf)?;
+    let strict_sql = reader::read_bool(buf)?;
+    let charset = reader::read_string(buf)?;
+    let time_zone = reader::read_string(buf)?;
+    let locale_country = reader::read_string(buf)?;
+    let record_serializer = reader::read_string(buf)?;
+    let record_serializer_version = reader::read_i32(buf)?;
+    let binary_format_version = reader::read_i32(buf)?;
+    let mut clusters = vec![];
+    for _ in 0..reader::read_i32(buf)? {
+        let id = reader::read_i32(buf)?;
+        let name = reader::read_string(buf)?;
+        clusters.push((id, name));
+    }
+    Ok(StorageConfiguration {
+        date_format,
+        date_time_format,
+        name,
+        version,
+        directory,
+        properties,
+        schema_record_id,
+        index_manager_record_id,
+        cluster_selection,
+        conflict_strategy,
+        validation_enabled,
+        locale_language,
+        minimum_clusters,
+        strict_sql,
+        charset,
+        time_zone,
+        locale_country,
+        record_serializer,
+        record_serializer_version,
+        binary_format_version,
+        clusters,
+    })
+}
+
 fn read_result<R: Read>(buf: &mut R) -> OrientResult<OResult> {
     let r_type = reader::read_i8(buf)?;
     match r_type {
@@ -130,7 +343,7 @@ fn read_result<R: Read>(buf: &mut R) -> OrientResult<OResult> {
             let projection = Protocol37::decode_projection(&buffer)?;
             Ok(OResult::from(projection))
         }
-        1 | 2 | 3 => {
+        1..=3 => {
             let _val = reader::read_i16(buf)?;
             let _d_type = reader::read_i8(buf)?;
             let identity = reader::read_identity(buf)?;
@@ -142,10 +355,38 @@ fn read_result<R: Read>(buf: &mut R) -> OrientResult<OResult> {
             document.set_record_id(identity);
             document.set_version(version);
 
-            Ok(OResult::from((r_type, document)))
+            OResult::try_from((r_type, document))
         }
-        _ => panic!("Unsupported result type {}", r_type),
+        _ => Err(OrientError::Decoder(format!(
+            "Unsupported result type {}",
+            r_type
+        ))),
+    }
+}
+
+fn read_document(r_type: i8, version: i32, content: &[u8]) -> OrientResult<ODocument> {
+    if r_type != b'd' as i8 {
+        return Err(OrientError::Decoder(format!(
+            "Unsupported record type {}",
+            r_type
+        )));
+    }
+    let mut document = Protocol37::decode_document(content)?;
+    document.set_version(version);
+    Ok(document)
+}
+
+// Changes to tree ridbags are not tracked on the client, just consume them
+fn read_collection_changes<R: Read>(buf: &mut R) -> OrientResult<()> {
+    let size = reader::read_i32(buf)?;
+    for _ in 0..size {
+        let _uuid_most = reader::read_i64(buf)?;
+        let _uuid_least = reader::read_i64(buf)?;
+        let _file_id = reader::read_i64(buf)?;
+        let _page_index = reader::read_i64(buf)?;
+        let _page_offset = reader::read_i32(buf)?;
     }
+    Ok(())
 }
 
 fn read_result_set<R: Read>(buf: &mut R) -> OrientResult<VecDeque<OResult>> {
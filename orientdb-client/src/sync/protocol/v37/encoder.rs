@@ -1,14 +1,20 @@
 use super::super::v37::Protocol37;
 use crate::common::protocol::buffer::OBuffer;
 use crate::common::protocol::messages::request::{
-    Close, Connect, CreateDB, DropDB, ExistDB, HandShake, LiveQuery, Open, Query, QueryClose,
-    QueryNext, ServerQuery, UnsubscribeLiveQuery,
+    BeginTransaction, Close, CommitTransaction, Connect, CreateDB, CreateRecord, DeleteRecord,
+    DropDB, ExistDB, HandShake, LiveQuery, LoadRecord, Open, Query, QueryClose, QueryNext,
+    RecordOperation, RollbackTransaction, SBTreeEntriesMajor, SBTreeFirstKey, ServerQuery,
+    SubscribeDistributedConfig, SubscribePush, UnsubscribeLiveQuery, UpdateRecord,
 };
 use crate::common::protocol::serializer::DocumentSerializer;
+use crate::common::types::bag::BagPointer;
 use crate::common::types::document::ODocument;
 use crate::sync::protocol::encoder::VersionedEncoder;
 use crate::OrientResult;
 
+// Record type of documents
+const RECORD_TYPE_DOCUMENT: i8 = b'd' as i8;
+
 impl VersionedEncoder for Protocol37 {
     fn encode_handshake(buf: &mut OBuffer, handshake: HandShake) -> OrientResult<()> {
         buf.put_i8(20)?;
@@ -101,6 +107,31 @@ impl VersionedEncoder for Protocol37 {
         Ok(())
     }
 
+    fn encode_subscribe_distributed_config(
+        buf: &mut OBuffer,
+        subscribe: SubscribeDistributedConfig,
+    ) -> OrientResult<()> {
+        buf.put_i8(100)?;
+        buf.put_i32(subscribe.session_id)?;
+        if let Some(t) = subscribe.token {
+            buf.write_slice(&t)?;
+        }
+        buf.put_i8(1)?;
+
+        Ok(())
+    }
+
+    fn encode_subscribe_push(buf: &mut OBuffer, subscribe: SubscribePush) -> OrientResult<()> {
+        buf.put_i8(100)?;
+        buf.put_i32(subscribe.session_id)?;
+        if let Some(t) = subscribe.token {
+            buf.write_slice(&t)?;
+        }
+        buf.put_i8(subscribe.kind.code())?;
+
+        Ok(())
+    }
+
     fn encode_live_query(buf: &mut OBuffer, query: LiveQuery) -> OrientResult<()> {
         buf.put_i8(100)?;
         buf.put_i32(query.session_id)?;
@@ -180,4 +211,176 @@ impl VersionedEncoder for Protocol37 {
         buf.write_str(&next.query_id)?;
         Ok(())
     }
+
+    fn encode_load_record(buf: &mut OBuffer, load: LoadRecord) -> OrientResult<()> {
+        buf.put_i8(30)?;
+        buf.put_i32(load.session_id)?;
+        if let Some(t) = load.token {
+            buf.write_slice(&t)?;
+        }
+        buf.write_identity(&load.rid)?;
+        buf.write_str(&load.fetch_plan)?;
+        buf.write_bool(load.ignore_cache)?;
+        // load tombstones
+        buf.write_bool(false)?;
+        Ok(())
+    }
+
+    fn encode_sbtree_first_key(buf: &mut OBuffer, first_key: SBTreeFirstKey) -> OrientResult<()> {
+        buf.put_i8(112)?;
+        buf.put_i32(first_key.session_id)?;
+        if let Some(t) = first_key.token {
+            buf.write_slice(&t)?;
+        }
+        write_bag_pointer(buf, &first_key.pointer)?;
+        Ok(())
+    }
+
+    fn encode_sbtree_entries_major(
+        buf: &mut OBuffer,
+        entries: SBTreeEntriesMajor,
+    ) -> OrientResult<()> {
+        buf.put_i8(113)?;
+        buf.put_i32(entries.session_id)?;
+        if let Some(t) = entries.token {
+            buf.write_slice(&t)?;
+        }
+        write_bag_pointer(buf, &entries.pointer)?;
+        let mut key = OBuffer::new();
+        key.write_identity(&entries.key)?;
+        buf.write_slice(key.as_slice())?;
+        buf.write_bool(entries.inclusive)?;
+        buf.put_i32(entries.page_size)?;
+        Ok(())
+    }
+
+    fn encode_create_record(buf: &mut OBuffer, create: CreateRecord) -> OrientResult<()> {
+        buf.put_i8(31)?;
+        buf.put_i32(create.session_id)?;
+        if let Some(t) = create.token {
+            buf.write_slice(&t)?;
+        }
+        buf.put_i16(create.cluster_id)?;
+        let encoded = Protocol37::encode_document(&create.content)?;
+        buf.write_slice(encoded.as_slice())?;
+        buf.put_i8(RECORD_TYPE_DOCUMENT)?;
+        // sync mode
+        buf.put_i8(0)?;
+        Ok(())
+    }
+
+    fn encode_update_record(buf: &mut OBuffer, update: UpdateRecord) -> OrientResult<()> {
+        buf.put_i8(32)?;
+        buf.put_i32(update.session_id)?;
+        if let Some(t) = update.token {
+            buf.write_slice(&t)?;
+        }
+        buf.write_identity(&update.rid)?;
+        // update content
+        buf.write_bool(true)?;
+        let encoded = Protocol37::encode_document(&update.content)?;
+        buf.write_slice(encoded.as_slice())?;
+        buf.put_i32(update.version)?;
+        buf.put_i8(RECORD_TYPE_DOCUMENT)?;
+        // sync mode
+        buf.put_i8(0)?;
+        Ok(())
+    }
+
+    fn encode_delete_record(buf: &mut OBuffer, delete: DeleteRecord) -> OrientResult<()> {
+        buf.put_i8(33)?;
+        buf.put_i32(delete.session_id)?;
+        if let Some(t) = delete.token {
+            buf.write_slice(&t)?;
+        }
+        buf.write_identity(&delete.rid)?;
+        buf.put_i32(delete.version)?;
+        // sync mode
+        buf.put_i8(0)?;
+        Ok(())
+    }
+
+    fn encode_begin_transaction(buf: &mut OBuffer, begin: BeginTransaction) -> OrientResult<()> {
+        buf.put_i8(73)?;
+        buf.put_i32(begin.session_id)?;
+        if let Some(t) = begin.token {
+            buf.write_slice(&t)?;
+        }
+        buf.put_i32(begin.tx_id)?;
+        // has content
+        buf.write_bool(true)?;
+        // using log
+        buf.write_bool(true)?;
+        for operation in &begin.operations {
+            buf.put_i8(1)?;
+            encode_record_operation(buf, operation)?;
+        }
+        buf.put_i8(0)?;
+        // no index changes
+        buf.put_i32(0)?;
+        Ok(())
+    }
+
+    fn encode_commit_transaction(buf: &mut OBuffer, commit: CommitTransaction) -> OrientResult<()> {
+        buf.put_i8(60)?;
+        buf.put_i32(commit.session_id)?;
+        if let Some(t) = commit.token {
+            buf.write_slice(&t)?;
+        }
+        buf.put_i32(commit.tx_id)?;
+        // the content was already sent with the begin
+        buf.write_bool(false)?;
+        // using log
+        buf.write_bool(true)?;
+        Ok(())
+    }
+
+    fn encode_rollback_transaction(
+        buf: &mut OBuffer,
+        rollback: RollbackTransaction,
+    ) -> OrientResult<()> {
+        buf.put_i8(74)?;
+        buf.put_i32(rollback.session_id)?;
+        if let Some(t) = rollback.token {
+            buf.write_slice(&t)?;
+        }
+        buf.put_i32(rollback.tx_id)?;
+        Ok(())
+    }
+}
+
+fn encode_record_operation(buf: &mut OBuffer, operation: &RecordOperation) -> OrientResult<()> {
+    match operation {
+        RecordOperation::Create(doc) => {
+            buf.put_i8(3)?;
+            buf.write_identity(doc.record_id())?;
+            buf.put_i8(RECORD_TYPE_DOCUMENT)?;
+            let encoded = Protocol37::encode_document(doc)?;
+            buf.write_slice(encoded.as_slice())?;
+        }
+        RecordOperation::Update(doc) => {
+            buf.put_i8(1)?;
+            buf.write_identity(doc.record_id())?;
+            buf.put_i8(RECORD_TYPE_DOCUMENT)?;
+            buf.put_i32(doc.version())?;
+            let encoded = Protocol37::encode_document(doc)?;
+            buf.write_slice(encoded.as_slice())?;
+            // content changed
+            buf.write_bool(true)?;
+        }
+        RecordOperation::Delete(rid, version) => {
+            buf.put_i8(2)?;
+            buf.write_identity(rid)?;
+            buf.put_i8(RECORD_TYPE_DOCUMENT)?;
+            buf.put_i32(*version)?;
+        }
+    }
+    Ok(())
+}
+
+fn write_bag_pointer(buf: &mut OBuffer, pointer: &BagPointer) -> OrientResult<()> {
+    buf.put_i64(pointer.file_id)?;
+    buf.put_i64(pointer.page_index)?;
+    buf.put_i32(pointer.page_offset)?;
+    Ok(())
 }
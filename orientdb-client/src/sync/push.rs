@@ -0,0 +1,53 @@
+use super::network::conn::Connection;
+
+use crate::common::protocol::messages::response::Status;
+use crate::types::PushEvent;
+use crate::OrientResult;
+use std::sync::mpsc::{channel, Receiver};
+use std::thread;
+
+/// Changes pushed by the server after a subscription. Dropping the events
+/// closes their connection, which ends the subscription.
+pub struct PushEvents {
+    receiver: Receiver<OrientResult<PushEvent>>,
+    conn: Connection,
+}
+
+impl Iterator for PushEvents {
+    type Item = OrientResult<PushEvent>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.receiver.recv().ok()
+    }
+}
+
+impl Drop for PushEvents {
+    fn drop(&mut self) {
+        let _ = self.conn.close();
+    }
+}
+
+/// Read the pushes received by `conn` on a dedicated thread, until the
+/// connection fails or the events are dropped
+pub(crate) fn listen(conn: Connection) -> OrientResult<PushEvents> {
+    let (sender, receiver) = channel();
+    let mut reader = conn.try_clone()?;
+    reader.wait_pushes()?;
+    thread::Builder::new()
+        .name(String::from("orientdb-push"))
+        .spawn(move || loop {
+            match reader.receive() {
+                Ok(mut response) if response.header.status == Status::PUSH => {
+                    if sender.send(Ok(response.payload())).is_err() {
+                        break;
+                    }
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    let _ = sender.send(Err(e));
+                    break;
+                }
+            }
+        })?;
+    Ok(PushEvents { receiver, conn })
+}
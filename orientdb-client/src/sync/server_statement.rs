@@ -6,6 +6,7 @@ use crate::sync::types::resultset::ResultSet;
 use crate::types::result::FromResult;
 use crate::OrientResult;
 use std::collections::HashMap;
+use std::time::Duration;
 
 pub struct ServerStatement<'a> {
     session: &'a OrientDBClientInternal,
@@ -17,6 +18,7 @@ pub struct ServerStatement<'a> {
     page_size: i32,
     mode: i8,
     named: bool,
+    pub(crate) timeout: Option<Duration>,
 }
 
 impl<'a> ServerStatement<'a> {
@@ -36,13 +38,16 @@ impl<'a> ServerStatement<'a> {
             mode: 1,
             language: String::from("sql"),
             page_size: 150,
+            timeout: None,
         }
     }
+    #[allow(dead_code)]
     pub(crate) fn mode(mut self, mode: i8) -> Self {
         self.mode = mode;
         self
     }
 
+    #[allow(dead_code)]
     pub(crate) fn language(mut self, language: String) -> Self {
         self.language = language;
         self
@@ -60,7 +65,7 @@ impl<'a> ServerStatement<'a> {
     pub fn named(mut self, params: &[(&str, &dyn IntoOValue)]) -> Self {
         self.params = params
             .iter()
-            .map(|&(k, ref v)| (String::from(k), v.into_ovalue()))
+            .map(|(k, v)| (String::from(*k), v.into_ovalue()))
             .collect();
 
         self.named = true;
@@ -71,6 +76,13 @@ impl<'a> ServerStatement<'a> {
         self.page_size = page_size;
         self
     }
+
+    /// Wait at most `timeout` for each response of the statement,
+    /// instead of the request timeout of the connection options
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
     pub fn run(self) -> OrientResult<impl ResultSet> {
         self.session.run(self)
     }
@@ -1,21 +1,37 @@
 use super::network::cluster::{Cluster, Server};
 
 use super::client::OrientDBClientInternal;
+use super::live::{self, LiveResults, Unsubscriber};
+use super::live_statement::LiveStatement;
+use super::push::{self, PushEvents};
 use super::statement::Statement;
-use crate::common::protocol::messages::request::{Close, Query};
+use crate::common::live::LiveQueue;
+use crate::common::protocol::messages::request::Open;
+use crate::common::protocol::messages::request::{
+    BeginTransaction, Close, CommitTransaction, CreateRecord, DeleteRecord, LiveQuery, LoadRecord,
+    Query, RecordOperation, RollbackTransaction, SBTreeEntriesMajor, SBTreeFirstKey, SubscribePush,
+    UpdateRecord,
+};
 use crate::common::protocol::messages::response;
+use crate::common::protocol::messages::Response;
+use crate::common::session::{Credentials, Reopen, SessionCredentials};
+use crate::common::types::bag::{BagFetch, BagPage, BagSession, RidBag, BAG_PAGE_SIZE};
+use crate::common::types::rid::ORecordID;
+use crate::common::types::transaction;
+use crate::common::types::{IntoDocument, ODocument, PushKind, TransactionResult};
+use crate::sync::transaction::OTransaction;
+use crate::sync::types::bag::BagIter;
 use crate::sync::types::resultset::{PagedResultSet, ResultSet};
 use crate::{OrientError, OrientResult};
 use r2d2::{ManageConnection, Pool, PooledConnection};
+use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Duration;
 
 pub struct OSession {
     pub client_id: i32,
-    pub session_id: i32,
-    pub token: Option<Vec<u8>>,
-    // Unused for now. After can be used to switch server in case of failure
-    #[allow(unused_variables)]
-    #[allow(dead_code)]
+    credentials: Arc<SessionCredentials>,
+    reopen: Reopen,
     cluster: Arc<Cluster>,
     server: Arc<Server>,
     pooled: bool,
@@ -24,53 +40,324 @@ pub struct OSession {
 impl OSession {
     pub(crate) fn new(
         client_id: i32,
-        session_id: i32,
-        token: Option<Vec<u8>>,
+        credentials: Credentials,
+        reopen: Reopen,
         cluster: Arc<Cluster>,
         server: Arc<Server>,
         pooled: bool,
     ) -> OSession {
         OSession {
             client_id,
-            session_id,
-            token,
+            credentials: Arc::new(SessionCredentials::new(credentials)),
+            reopen,
             cluster,
             server,
             pooled,
         }
     }
 
-    pub fn query<T: Into<String>>(&self, query: T) -> Statement {
+    /// The id of the session, it changes when the session is opened again
+    pub fn session_id(&self) -> i32 {
+        self.credentials.get().session_id
+    }
+
+    /// The token of the session, it changes when the session is opened again
+    pub fn token(&self) -> Option<Vec<u8>> {
+        self.credentials.get().token
+    }
+
+    /// Run `f` with the credentials of the session. When the server reports that the
+    /// session expired, the session is opened again and `f` runs once more
+    fn with_credentials<T, F>(&self, f: F) -> OrientResult<T>
+    where
+        F: Fn(Credentials) -> OrientResult<T>,
+    {
+        let credentials = self.credentials.get();
+        match f(credentials.clone()) {
+            Err(e) if self.reopen.allows(&e) => f(self.reopen(&credentials)?),
+            result => result,
+        }
+    }
+
+    // Adopt the token renewed by the server in the response to a request sent as `session_id`
+    fn renewed(&self, session_id: i32, response: Response) -> Response {
+        self.credentials.renew(session_id, &response.header);
+        response
+    }
+
+    // Open the session again, unless another thread already did it
+    fn reopen(&self, expired: &Credentials) -> OrientResult<Credentials> {
+        let current = self.credentials.get();
+        if current != *expired {
+            return Ok(current);
+        }
+        let open = Open::new(&self.reopen.db, &self.reopen.user, &self.reopen.password);
+        let response: response::Open = self.server.connection()?.send(open.into())?.payload();
+        let fresh = Credentials::new(response.session_id, response.token);
+        Ok(self.credentials.replace(expired, fresh))
+    }
+
+    pub fn query<T: Into<String>>(&self, query: T) -> Statement<'_> {
         Statement::new(self, query.into())
     }
 
-    pub fn command<T: Into<String>>(&self, command: T) -> Statement {
+    pub fn command<T: Into<String>>(&self, command: T) -> Statement<'_> {
         Statement::new(self, command.into()).mode(0)
     }
 
-    pub fn script_sql<T: Into<String>>(&self, script: T) -> Statement {
+    pub fn script_sql<T: Into<String>>(&self, script: T) -> Statement<'_> {
         Statement::new(self, script.into())
             .mode(2)
             .language(String::from("SQL"))
     }
-    pub fn script<T: Into<String>, S: Into<String>>(&self, script: T, language: S) -> Statement {
+    pub fn script<T: Into<String>, S: Into<String>>(
+        &self,
+        script: T,
+        language: S,
+    ) -> Statement<'_> {
         Statement::new(self, script.into())
             .mode(2)
             .language(language.into())
     }
 
-    pub(crate) fn run(&self, query: Query) -> OrientResult<impl ResultSet> {
+    pub fn live_query<T: Into<String>>(&self, query: T) -> LiveStatement<'_> {
+        LiveStatement::new(self, query.into())
+    }
+
+    // The live query runs on its own connection, read by a dedicated thread
+    pub(crate) fn live_run(
+        &self,
+        live_query: LiveQuery,
+        queue: LiveQueue,
+    ) -> OrientResult<(Unsubscriber, LiveResults)> {
+        let (conn, q) = self.with_credentials(|Credentials { session_id, token }| {
+            let mut conn = self.server.dedicated_connection()?;
+            let mut live_query = live_query.clone();
+            live_query.session_id = session_id;
+            live_query.token = token;
+            let response = conn.send(live_query.into())?;
+            let q: response::LiveQuery = self.renewed(session_id, response).payload();
+            Ok((conn, q))
+        })?;
+
+        let queue = Arc::new(queue);
+
+        let subscription =
+            live::subscription(&conn, q.monitor_id, self.credentials.clone(), queue.clone())?;
+
+        live::listen(conn, q.monitor_id, queue)?;
+
+        Ok(subscription)
+    }
+
+    /// Subscribe to the changes of `kind`, pushed by the server on a dedicated connection
+    pub fn subscribe(&self, kind: PushKind) -> OrientResult<PushEvents> {
+        let conn = self.with_credentials(|Credentials { session_id, token }| {
+            let mut conn = self.server.dedicated_connection()?;
+            let subscribe = SubscribePush::new(session_id, token, kind);
+            let response = conn.send(subscribe.into())?;
+            let _: response::SubscribePush = self.renewed(session_id, response).payload();
+            Ok(conn)
+        })?;
+        push::listen(conn)
+    }
+
+    /// Load a record by id, returns `None` if the record does not exist
+    pub fn load(&self, rid: ORecordID) -> OrientResult<Option<ODocument>> {
+        let response: response::LoadRecord =
+            self.with_credentials(|Credentials { session_id, token }| {
+                let load = LoadRecord::new(session_id, token, rid.clone());
+                let (_, response) = self.cluster.send(&self.server, load.into())?;
+                Ok(self.renewed(session_id, response).payload())
+            })?;
+        Ok(response.record.map(|mut doc| {
+            doc.set_record_id(rid);
+            doc
+        }))
+    }
+
+    pub(crate) fn fetch_bag(&self, fetch: BagFetch) -> OrientResult<BagPage> {
+        self.with_credentials(|credentials| self.fetch_bag_with(fetch.clone(), credentials))
+    }
+
+    fn fetch_bag_with(&self, fetch: BagFetch, credentials: Credentials) -> OrientResult<BagPage> {
+        let Credentials { session_id, token } = credentials;
+        match fetch {
+            BagFetch::FirstKey(pointer) => {
+                let first_key = SBTreeFirstKey::new(session_id, token, pointer);
+                let (_, response) = self.cluster.send(&self.server, first_key.into())?;
+                let response: response::SBTreeFirstKey =
+                    self.renewed(session_id, response).payload();
+                Ok(BagPage::FirstKey(response.key))
+            }
+            BagFetch::EntriesMajor(pointer, key, inclusive) => {
+                let entries = SBTreeEntriesMajor::new(
+                    session_id,
+                    token,
+                    pointer,
+                    key,
+                    inclusive,
+                    BAG_PAGE_SIZE,
+                );
+                let (_, response) = self.cluster.send(&self.server, entries.into())?;
+                let response: response::SBTreeEntriesMajor =
+                    self.renewed(session_id, response).payload();
+                Ok(BagPage::Entries(response.entries))
+            }
+        }
+    }
+
+    /// Save a document, creating a new record if the document was never stored.
+    /// Returns the record id and the version of the stored record
+    pub fn save<T>(&self, doc: &T) -> OrientResult<(ORecordID, i32)>
+    where
+        T: IntoDocument + ?Sized,
+    {
+        self.save_document(&mut doc.into_document())
+    }
+
+    /// Save a document like [`save`](OSession::save),
+    /// the record id and the version of the stored record are also set on the document
+    pub fn save_document(&self, doc: &mut ODocument) -> OrientResult<(ORecordID, i32)> {
+        if !doc.record_id().is_new() {
+            return self.update(doc);
+        }
+        let response: response::CreateRecord =
+            self.with_credentials(|Credentials { session_id, token }| {
+                let create = CreateRecord::new(session_id, token, doc.clone());
+                let response = self.server.connection()?.send(create.into())?;
+                Ok(self.renewed(session_id, response).payload())
+            })?;
+        doc.set_record_id(response.rid.clone());
+        doc.set_version(response.version);
+        Ok((response.rid, response.version))
+    }
+
+    /// Update the record of a stored document.
+    /// The new version of the record is set on the document and returned with the record id
+    pub fn update(&self, doc: &mut ODocument) -> OrientResult<(ORecordID, i32)> {
+        let response: response::UpdateRecord =
+            self.with_credentials(|Credentials { session_id, token }| {
+                let update = UpdateRecord::new(session_id, token, doc.clone());
+                let response = self.server.connection()?.send(update.into())?;
+                Ok(self.renewed(session_id, response).payload())
+            })?;
+        doc.set_version(response.version);
+        Ok((doc.record_id().clone(), response.version))
+    }
+
+    /// Delete a record with the given version, returns `false` if nothing was deleted
+    pub fn delete(&self, rid: ORecordID, version: i32) -> OrientResult<bool> {
+        let response: response::DeleteRecord =
+            self.with_credentials(|Credentials { session_id, token }| {
+                let delete = DeleteRecord::new(session_id, token, rid.clone(), version);
+                let response = self.server.connection()?.send(delete.into())?;
+                Ok(self.renewed(session_id, response).payload())
+            })?;
+        Ok(response.deleted)
+    }
+
+    /// Begin a client side transaction
+    pub fn begin(&self) -> OTransaction<'_> {
+        OTransaction::new(self)
+    }
+
+    /// Run `f` retrying it up to `n` times when it fails with a concurrent modification
+    pub fn with_retry<T, F>(&self, mut n: u32, f: F) -> OrientResult<T>
+    where
+        F: Fn(&OSession) -> OrientResult<T>,
+    {
+        if n == 0 {
+            panic!("retry must be called with a number greater than 0")
+        };
+        loop {
+            match f(self) {
+                Err(OrientError::ConcurrentModification(_)) if n > 0 => n -= 1,
+                result => return result,
+            }
+        }
+    }
+
+    pub(crate) fn commit_transaction(
+        &self,
+        operations: Vec<RecordOperation>,
+    ) -> OrientResult<TransactionResult> {
+        if operations.is_empty() {
+            return Ok(TransactionResult::default());
+        }
+        self.with_credentials(|credentials| self.commit_with(operations.clone(), credentials))
+    }
+
+    fn commit_with(
+        &self,
+        operations: Vec<RecordOperation>,
+        credentials: Credentials,
+    ) -> OrientResult<TransactionResult> {
+        let Credentials { session_id, token } = credentials;
+        let tx_id = transaction::next_tx_id();
         let mut conn = self.server.connection()?;
+        let begin = BeginTransaction::new(session_id, token, tx_id, operations);
+        let result = conn.send(begin.into()).and_then(|reply| {
+            let begin: response::BeginTransaction = self.renewed(session_id, reply).payload();
+            // The reply may have renewed the token, the next requests send the current one
+            let Credentials { session_id, token } = self.credentials.get();
+            let commit = CommitTransaction::new(session_id, token, tx_id);
+            let reply = conn.send(commit.into())?;
+            let commit: response::CommitTransaction = self.renewed(session_id, reply).payload();
+            Ok(TransactionResult::new(begin, commit))
+        });
+        if result.is_err() {
+            let Credentials { session_id, token } = self.credentials.get();
+            let rollback = RollbackTransaction::new(session_id, token, tx_id);
+            let _ = conn.send(rollback.into());
+        }
+        result
+    }
+
+    pub(crate) fn run(
+        &self,
+        query: Query,
+        timeout: Option<Duration>,
+    ) -> OrientResult<impl ResultSet> {
         let page_size = query.page_size;
-        let q: response::Query = conn.send(query.into())?.payload();
-        Ok(PagedResultSet::new(
-            self.server.clone(),
-            q,
-            self.session_id,
-            self.token.clone(),
-            page_size,
-        ))
+        self.with_credentials(|Credentials { session_id, token }| {
+            let mut query = query.clone();
+            query.session_id = session_id;
+            query.token = token.clone();
+            // Next pages must be fetched from the server that runs the query
+            let (server, response) =
+                self.cluster
+                    .send_with_timeout(&self.server, query.into(), timeout)?;
+            let q: response::Query = self.renewed(session_id, response).payload();
+            Ok(PagedResultSet::new(
+                server,
+                q,
+                self.credentials.clone(),
+                page_size,
+                timeout,
+            ))
+        })
     }
+
+    /// Check that the server still knows the session, with a query that reads nothing
+    pub(crate) fn ping(&self) -> OrientResult<()> {
+        self.with_credentials(|Credentials { session_id, token }| {
+            let query = Query::new(
+                session_id,
+                token,
+                "SELECT 1",
+                HashMap::new(),
+                false,
+                "sql",
+                1,
+                10,
+            );
+            let response = self.server.connection()?.send(query.into())?;
+            let _: response::Query = self.renewed(session_id, response).payload();
+            Ok(())
+        })
+    }
+
     /// Close a session
     pub fn close(self) -> OrientResult<()> {
         if !self.pooled {
@@ -79,27 +366,33 @@ impl OSession {
         Ok(())
     }
 
-    fn force_close(mut self) -> OrientResult<()> {
+    fn force_close(self) -> OrientResult<()> {
         let mut conn = self.server.connection()?;
-        self.session_id = -1;
-        self.token = None;
-        conn.send_and_forget(Close::new(self.session_id, self.token).into())?;
+        self.credentials.set(Credentials::new(-1, None));
+        let Credentials { session_id, token } = self.credentials.get();
+        conn.send_and_forget(Close::new(session_id, token).into())?;
         Ok(())
     }
 }
 
+impl<'session> BagSession<'session> for OSession {
+    type Iter = BagIter<'session>;
+
+    fn bag_iter(&'session self, bag: &RidBag) -> BagIter<'session> {
+        BagIter::new(self, bag)
+    }
+}
+
 pub struct SessionPoolManager {
     db: String,
     user: String,
     password: String,
-    server: Arc<Server>,
     client: OrientDBClientInternal,
 }
 
 impl SessionPoolManager {
     pub(crate) fn new(
         client: OrientDBClientInternal,
-        server: Arc<Server>,
         db_name: &str,
         user: &str,
         password: &str,
@@ -108,13 +401,17 @@ impl SessionPoolManager {
             db: String::from(db_name),
             user: String::from(user),
             password: String::from(password),
-            server,
             client,
         }
     }
 
     pub(crate) fn managed(self, size: Option<u32>) -> OrientResult<SessionPool> {
-        let pool = Pool::builder().max_size(size.unwrap_or(20)).build(self)?;
+        // Sessions are not pinged on every checkout: an expired session is opened
+        // again by its next request, and the ones of a failed server are evicted
+        let pool = Pool::builder()
+            .max_size(size.unwrap_or(20))
+            .test_on_check_out(false)
+            .build(self)?;
 
         Ok(SessionPool(pool))
     }
@@ -125,21 +422,16 @@ impl ManageConnection for SessionPoolManager {
     type Error = OrientError;
 
     fn connect(&self) -> OrientResult<OSession> {
-        self.client._server_session(
-            self.server.clone(),
-            &self.db,
-            &self.user,
-            &self.password,
-            true,
-        )
+        self.client
+            ._session(&self.db, &self.user, &self.password, true)
     }
 
-    fn is_valid(&self, _conn: &mut OSession) -> OrientResult<()> {
-        Ok(())
+    fn is_valid(&self, conn: &mut OSession) -> OrientResult<()> {
+        conn.ping()
     }
 
-    fn has_broken(&self, _conn: &mut OSession) -> bool {
-        false
+    fn has_broken(&self, conn: &mut OSession) -> bool {
+        !conn.server.is_healthy()
     }
 }
 
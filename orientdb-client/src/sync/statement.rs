@@ -1,11 +1,13 @@
 use super::session::OSession;
 use crate::common::protocol::messages::request::Query;
+use crate::common::types::document::IntoDocument;
 use crate::common::types::value::{IntoOValue, OValue};
 use crate::sync::types::resultset::ResultSet;
 #[cfg(feature = "sugar")]
 use crate::types::result::FromResult;
 use crate::OrientResult;
 use std::collections::HashMap;
+use std::time::Duration;
 
 pub struct Statement<'a> {
     session: &'a OSession,
@@ -15,6 +17,7 @@ pub struct Statement<'a> {
     page_size: i32,
     mode: i8,
     named: bool,
+    timeout: Option<Duration>,
 }
 
 impl<'a> Statement<'a> {
@@ -27,6 +30,7 @@ impl<'a> Statement<'a> {
             mode: 1,
             language: String::from("sql"),
             page_size: 150,
+            timeout: None,
         }
     }
     pub(crate) fn mode(mut self, mode: i8) -> Self {
@@ -48,10 +52,16 @@ impl<'a> Statement<'a> {
         self.named = false;
         self
     }
-    pub fn named(mut self, params: &[(&str, &dyn IntoOValue)]) -> Self {
+    /// Bind named parameters, given as pairs of name and value
+    /// or as the fields of a document
+    pub fn named<T>(mut self, params: &T) -> Self
+    where
+        T: IntoDocument + ?Sized,
+    {
         self.params = params
+            .into_document()
             .iter()
-            .map(|&(k, ref v)| (String::from(k), v.into_ovalue()))
+            .map(|(k, v)| (k.clone(), v.clone()))
             .collect();
 
         self.named = true;
@@ -62,8 +72,17 @@ impl<'a> Statement<'a> {
         self.page_size = page_size;
         self
     }
+
+    /// Wait at most `timeout` for each response of the statement,
+    /// instead of the request timeout of the connection options
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
     pub fn run(self) -> OrientResult<impl ResultSet> {
-        self.session.run(self.into())
+        let session = self.session;
+        let timeout = self.timeout;
+        session.run(self.into(), timeout)
     }
 
     #[cfg(feature = "sugar")]
@@ -71,12 +90,7 @@ impl<'a> Statement<'a> {
     where
         T: FromResult,
     {
-        match self
-            .session
-            .run(self.into())?
-            .map(|r| r.and_then(T::from_result))
-            .next()
-        {
+        match self.run()?.map(|r| r.and_then(T::from_result)).next() {
             Some(s) => Ok(Some(s?)),
             None => Ok(None),
         }
@@ -87,10 +101,7 @@ impl<'a> Statement<'a> {
     where
         T: FromResult,
     {
-        self.session
-            .run(self.into())?
-            .map(|r| r.and_then(T::from_result))
-            .collect()
+        self.run()?.map(|r| r.and_then(T::from_result)).collect()
     }
 
     #[cfg(feature = "sugar")]
@@ -98,18 +109,15 @@ impl<'a> Statement<'a> {
     where
         T: FromResult,
     {
-        Ok(self
-            .session
-            .run(self.into())?
-            .map(|r| r.and_then(T::from_result)))
+        Ok(self.run()?.map(|r| r.and_then(T::from_result)))
     }
 }
 
 impl<'a> From<Statement<'a>> for Query {
     fn from(x: Statement) -> Query {
         Query {
-            session_id: x.session.session_id,
-            token: x.session.token.clone(),
+            session_id: x.session.session_id(),
+            token: x.session.token(),
             query: x.stm,
             language: x.language,
             named: x.named,
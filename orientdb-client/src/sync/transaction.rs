@@ -0,0 +1,50 @@
+use super::session::OSession;
+use crate::common::types::rid::ORecordID;
+use crate::common::types::transaction::TransactionLog;
+use crate::common::types::{IntoDocument, ODocument, TransactionResult};
+use crate::OrientResult;
+
+/// A client side optimistic transaction.
+///
+/// Record changes are buffered locally and sent to the server on commit.
+/// Dropping the transaction without committing discards the changes.
+pub struct OTransaction<'session> {
+    session: &'session OSession,
+    log: TransactionLog,
+}
+
+impl<'session> OTransaction<'session> {
+    pub(crate) fn new(session: &'session OSession) -> OTransaction<'session> {
+        OTransaction {
+            session,
+            log: TransactionLog::new(),
+        }
+    }
+
+    /// Load a record by id, changes buffered in the transaction are not visible
+    pub fn load(&self, rid: ORecordID) -> OrientResult<Option<ODocument>> {
+        self.session.load(rid)
+    }
+
+    /// Save a document in the transaction.
+    /// Returns the id of the record in the transaction, which is temporary for new records
+    pub fn save<T>(&mut self, doc: &T) -> ORecordID
+    where
+        T: IntoDocument + ?Sized,
+    {
+        self.log.save(&doc.into_document())
+    }
+
+    /// Delete a record with the given version in the transaction
+    pub fn delete(&mut self, rid: ORecordID, version: i32) {
+        self.log.delete(rid, version)
+    }
+
+    /// Commit the buffered changes, the server transaction is rolled back on error
+    pub fn commit(mut self) -> OrientResult<TransactionResult> {
+        self.session.commit_transaction(self.log.take())
+    }
+
+    /// Discard the buffered changes
+    pub fn rollback(self) {}
+}
@@ -0,0 +1,39 @@
+use crate::common::types::bag::{BagCursor, RidBag};
+use crate::common::types::rid::ORecordID;
+use crate::sync::session::OSession;
+use crate::OrientResult;
+
+/// Iterator over the links of a bag, tree bags are fetched one page at time
+pub struct BagIter<'session> {
+    session: &'session OSession,
+    cursor: BagCursor,
+}
+
+impl<'session> BagIter<'session> {
+    pub(crate) fn new(session: &'session OSession, bag: &RidBag) -> BagIter<'session> {
+        BagIter {
+            session,
+            cursor: BagCursor::new(bag),
+        }
+    }
+}
+
+impl<'session> Iterator for BagIter<'session> {
+    type Item = OrientResult<ORecordID>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(rid) = self.cursor.pop() {
+                return Some(Ok(rid));
+            }
+            let fetch = self.cursor.fetch()?;
+            match self.session.fetch_bag(fetch) {
+                Ok(page) => self.cursor.receive(page),
+                Err(e) => {
+                    self.cursor.fail();
+                    return Some(Err(e));
+                }
+            }
+        }
+    }
+}
@@ -1,56 +1,58 @@
-use crate::common::protocol::messages::request::{QueryClose, QueryNext};
+use crate::common::protocol::messages::request::{Close, QueryClose, QueryNext};
 use crate::common::protocol::messages::response::{Query, ServerQuery};
+use crate::common::session::{Credentials, SessionCredentials};
 use crate::common::types::result::OResult;
 use crate::sync::network::cluster::Server;
 use crate::OrientResult;
 use std::sync::Arc;
+use std::time::Duration;
 
 pub struct PagedResultSet {
     server: Arc<Server>,
     response: Query,
-    session_id: i32,
-    token: Option<Vec<u8>>,
+    credentials: Arc<SessionCredentials>,
     page_size: i32,
+    // Of the statement, to fetch the next pages
+    timeout: Option<Duration>,
 }
 
 impl PagedResultSet {
     pub(crate) fn new(
         server: Arc<Server>,
         response: Query,
-        session_id: i32,
-        token: Option<Vec<u8>>,
+        credentials: Arc<SessionCredentials>,
         page_size: i32,
+        timeout: Option<Duration>,
     ) -> PagedResultSet {
         PagedResultSet {
             server,
             response,
-            session_id,
-            token,
+            credentials,
             page_size,
+            timeout,
         }
     }
 
     fn fetch_next(&mut self) -> OrientResult<Query> {
         let mut conn = self.server.connection()?;
 
+        let Credentials { session_id, token } = self.credentials.get();
         let msg = QueryNext::new(
-            self.session_id,
-            self.token.clone(),
+            session_id,
+            token,
             self.response.query_id.clone(),
             self.page_size,
         );
-        let response: Query = conn.send(msg.into())?.payload();
-        Ok(response)
+        let mut response = conn.send_with_timeout(msg.into(), self.timeout)?;
+        self.credentials.renew(session_id, &response.header);
+        Ok(response.payload())
     }
 
     fn close_result(&mut self) -> OrientResult<()> {
         if self.response.has_next {
             if let Ok(mut conn) = self.server.connection() {
-                let msg = QueryClose::new(
-                    self.session_id,
-                    self.token.clone(),
-                    self.response.query_id.as_str(),
-                );
+                let Credentials { session_id, token } = self.credentials.get();
+                let msg = QueryClose::new(session_id, token, self.response.query_id.as_str());
                 conn.send(msg.into())?;
                 self.response.has_next = false;
             }
@@ -79,7 +81,11 @@ impl Iterator for PagedResultSet {
                     if self.response.has_next {
                         match self.fetch_next() {
                             Ok(result) => self.response = result,
-                            Err(e) => return Some(Err(e)),
+                            Err(e) => {
+                                // The cursor state is unknown after a failure, stop paging
+                                self.response.has_next = false;
+                                return Some(Err(e));
+                            }
                         }
                     } else {
                         return None;
@@ -102,18 +108,47 @@ impl Drop for PagedResultSet {
 }
 
 pub struct ServerResultSet {
-    response: ServerQuery,
+    inner: PagedResultSet,
+    open: bool,
 }
 
 impl ServerResultSet {
-    pub(crate) fn new(response: ServerQuery) -> ServerResultSet {
-        ServerResultSet { response }
+    pub(crate) fn new(
+        server: Arc<Server>,
+        response: ServerQuery,
+        session_id: i32,
+        token: Option<Vec<u8>>,
+        page_size: i32,
+        timeout: Option<Duration>,
+    ) -> ServerResultSet {
+        ServerResultSet {
+            inner: PagedResultSet::new(
+                server,
+                response.into(),
+                Arc::new(SessionCredentials::new(Credentials::new(session_id, token))),
+                page_size,
+                timeout,
+            ),
+            open: true,
+        }
+    }
+
+    // The admin session lives as long as the result set
+    fn close_session(&mut self) -> OrientResult<()> {
+        if self.open {
+            self.open = false;
+            self.inner.close_result()?;
+            let mut conn = self.inner.server.connection()?;
+            let Credentials { session_id, token } = self.inner.credentials.get();
+            conn.send_and_forget(Close::new(session_id, token).into())?;
+        }
+        Ok(())
     }
 }
 
 impl ResultSet for ServerResultSet {
-    fn close(self) -> OrientResult<()> {
-        Ok(())
+    fn close(mut self) -> OrientResult<()> {
+        self.close_session()
     }
 }
 
@@ -121,6 +156,13 @@ impl Iterator for ServerResultSet {
     type Item = OrientResult<OResult>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.response.records.pop_front().map(|x| Ok(x))
+        self.inner.next()
+    }
+}
+
+impl Drop for ServerResultSet {
+    #[allow(unused_must_use)]
+    fn drop(&mut self) {
+        self.close_session();
     }
 }
@@ -7,6 +7,7 @@ use std::env;
 
 #[derive(Debug)]
 pub struct OrientDBTest {
+    #[allow(dead_code)]
     pub address: String,
     pub host: String,
     pub port: u16,
@@ -53,7 +54,7 @@ impl OrientDBTest {
 }
 
 fn read_var(var: &str) -> String {
-    env::var(var).expect(&format!("Failed to read {} variable", var))
+    env::var(var).unwrap_or_else(|_| panic!("Failed to read {} variable", var))
 }
 
 pub fn config() -> OrientDBTest {
@@ -74,7 +75,7 @@ pub fn session(db: &str) -> OSession {
 
     create_database(db, &driver, &config);
 
-    let result = driver.session(&db, &config.username, &config.password);
+    let result = driver.session(db, &config.username, &config.password);
     assert!(result.is_ok());
     result.unwrap()
 }
@@ -87,7 +88,7 @@ pub fn create_database(db: &str, odb: &OrientDB, config: &OrientDBTest) {
             &config.r_password,
             DatabaseType::Memory,
         )
-        .expect(&format!("Cannot check if database with name {} exists", db));
+        .unwrap_or_else(|_| panic!("Cannot check if database with name {} exists", db));
 
     if exist {
         odb.drop_database(
@@ -96,18 +97,18 @@ pub fn create_database(db: &str, odb: &OrientDB, config: &OrientDBTest) {
             &config.r_password,
             DatabaseType::Memory,
         )
-        .expect(&format!("Cannot drop database with name {}", db));
+        .unwrap_or_else(|_| panic!("Cannot drop database with name {}", db));
     }
     odb.execute(
         &config.r_password,
         &config.r_password,
         &format!("create database {} memory users(admin identified by 'admin' role admin, reader identified by 'reader' role reader, writer identified by 'writer' role writer)", db) 
     )
-    .expect(&format!("Cannot create database with name {}", db)).
+    .unwrap_or_else(|_| panic!("Cannot create database with name {}", db)).
     run()
-    .expect(&format!("Cannot create database with name {}", db))
+    .unwrap_or_else(|_| panic!("Cannot create database with name {}", db))
     .close()
-    .expect(&format!("Cannot create database with name {}", db))
+    .unwrap_or_else(|_| panic!("Cannot create database with name {}", db))
     ;
 }
 
@@ -181,10 +182,8 @@ pub mod asynchronous {
 
         create_database(db, &driver, &config).await;
 
-        let result = driver
-            .session(&db, &config.username, &config.password)
-            .await;
-        assert!(result.is_ok(), result.err());
+        let result = driver.session(db, &config.username, &config.password).await;
+        assert!(result.is_ok(), "{:?}", result.err());
         result.unwrap()
     }
 
@@ -196,9 +195,9 @@ pub mod asynchronous {
         create_database(db, &driver, &config).await;
 
         let result = driver
-            .sessions(&db, &config.username, &config.password, None, None)
+            .sessions(db, &config.username, &config.password, None, None)
             .await;
-        assert!(result.is_ok(), result.err());
+        assert!(result.is_ok(), "{:?}", result.err());
         result.unwrap()
     }
 
@@ -211,7 +210,7 @@ pub mod asynchronous {
                 DatabaseType::Memory,
             )
             .await
-            .expect(&format!("Cannot check if database with name {} exists", db));
+            .unwrap_or_else(|_| panic!("Cannot check if database with name {} exists", db));
 
         if exist {
             odb.drop_database(
@@ -221,7 +220,7 @@ pub mod asynchronous {
                 DatabaseType::Memory,
             )
             .await
-            .expect(&format!("Cannot drop database with name {}", db));
+            .unwrap_or_else(|_| panic!("Cannot drop database with name {}", db));
         }
 
         let _ = odb.execute(
@@ -229,9 +228,9 @@ pub mod asynchronous {
         &config.r_password,
         &format!("create database {} memory users(admin identified by 'admin' role admin, reader identified by 'reader' role reader, writer identified by 'writer' role writer)", db) 
     ).await
-    .expect(&format!("Cannot create database with name {}", db)).
+    .unwrap_or_else(|_| panic!("Cannot create database with name {}", db)).
     run().await
-    .expect(&format!("Cannot create database with name {}", db))
+    .unwrap_or_else(|_| panic!("Cannot create database with name {}", db))
     ;
     }
 }
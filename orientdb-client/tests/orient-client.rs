@@ -28,7 +28,7 @@ fn test_client_create_exist_drop_db() {
 
     assert!(res.is_ok());
 
-    assert_eq!(true, res.unwrap());
+    assert!(res.unwrap());
 
     let res = client.drop_database(
         "test_client_create_exist_drop_db",
@@ -40,6 +40,66 @@ fn test_client_create_exist_drop_db() {
     assert!(res.is_ok());
 }
 
+#[test]
+fn test_client_execute_paged() {
+    let client = connect();
+
+    let results: Vec<_> = client
+        .execute(
+            "root",
+            "root",
+            "create database test_client_execute_paged memory",
+        )
+        .unwrap()
+        .page_size(1)
+        .run()
+        .unwrap()
+        .collect();
+
+    assert!(results.iter().all(|r| r.is_ok()), "{:?}", results);
+
+    let res = client.exist_database(
+        "test_client_execute_paged",
+        "root",
+        "root",
+        DatabaseType::Memory,
+    );
+    assert!(res.unwrap());
+
+    let res = client.drop_database(
+        "test_client_execute_paged",
+        "root",
+        "root",
+        DatabaseType::Memory,
+    );
+    assert!(res.is_ok());
+}
+
+#[test]
+fn test_client_connect_many_servers() {
+    use orientdb_client::common::cluster::RoundRobin;
+    use orientdb_client::{ConnectionOptions, OrientDB};
+
+    let config = config();
+    let options = ConnectionOptions::builder()
+        .host(config.host.as_str())
+        .port(config.port)
+        .add_server(config.host.as_str(), config.port)
+        .strategy(RoundRobin::default())
+        .build();
+    let client = OrientDB::connect(options).unwrap();
+
+    for _ in 0..2 {
+        let res = client.exist_database(
+            "test_client_connect_many_servers",
+            "root",
+            "root",
+            DatabaseType::Memory,
+        );
+        assert!(!res.unwrap());
+    }
+}
+
 #[cfg(feature = "async")]
 mod asynchronous {
     use super::common::config;
@@ -70,20 +130,83 @@ mod asynchronous {
             .create_database("test_async", "root", "root", DatabaseType::Memory)
             .await;
 
-        assert!(res.is_ok(), res.err());
+        assert!(res.is_ok(), "{:?}", res.err());
 
         let res = client
             .exist_database("test_async", "root", "root", DatabaseType::Memory)
             .await;
 
-        assert!(res.is_ok(), res.err());
+        assert!(res.is_ok(), "{:?}", res.err());
 
-        assert_eq!(true, res.unwrap());
+        assert!(res.unwrap());
 
         let res = client
             .drop_database("test_async", "root", "root", DatabaseType::Memory)
             .await;
 
-        assert!(res.is_ok(), res.err());
+        assert!(res.is_ok(), "{:?}", res.err());
+    }
+
+    #[cfg_attr(feature = "async-std-runtime", async_std::test)]
+    #[cfg_attr(feature = "tokio-runtime", tokio::test)]
+    async fn test_client_execute_paged() {
+        use futures::StreamExt;
+
+        let cfg = config();
+
+        let client = OrientDB::connect((cfg.host, cfg.port))
+            .await
+            .expect("Failed to connect");
+
+        let results: Vec<_> = client
+            .execute("root", "root", "create database test_async_execute memory")
+            .await
+            .unwrap()
+            .page_size(1)
+            .run()
+            .await
+            .unwrap()
+            .collect()
+            .await;
+
+        assert!(results.iter().all(|r| r.is_ok()), "{:?}", results);
+
+        let res = client
+            .exist_database("test_async_execute", "root", "root", DatabaseType::Memory)
+            .await;
+        assert!(res.unwrap());
+
+        let res = client
+            .drop_database("test_async_execute", "root", "root", DatabaseType::Memory)
+            .await;
+        assert!(res.is_ok(), "{:?}", res.err());
+    }
+
+    #[cfg_attr(feature = "async-std-runtime", async_std::test)]
+    #[cfg_attr(feature = "tokio-runtime", tokio::test)]
+    async fn test_client_connect_many_servers() {
+        use orientdb_client::common::cluster::RoundRobin;
+        use orientdb_client::ConnectionOptions;
+
+        let cfg = config();
+        let options = ConnectionOptions::builder()
+            .host(cfg.host.as_str())
+            .port(cfg.port)
+            .add_server(cfg.host.as_str(), cfg.port)
+            .strategy(RoundRobin::default())
+            .build();
+        let client = OrientDB::connect(options).await.expect("Failed to connect");
+
+        for _ in 0..2 {
+            let res = client
+                .exist_database(
+                    "test_async_connect_many_servers",
+                    "root",
+                    "root",
+                    DatabaseType::Memory,
+                )
+                .await;
+            assert!(!res.unwrap());
+        }
     }
 }
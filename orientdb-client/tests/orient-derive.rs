@@ -0,0 +1,327 @@
+#![cfg(feature = "sugar")]
+
+use orientdb_client::derive::{FromResult, IntoDocument};
+use orientdb_client::types::result::FromResult;
+use orientdb_client::types::rid::ORecordID;
+use orientdb_client::types::value::{FromOValue, IntoOValue, OValue};
+use orientdb_client::types::{IntoDocument, ODocument, OResult, Projection};
+use orientdb_client::OrientResult;
+use std::convert::TryFrom;
+
+fn result(fields: &[(&str, OValue)]) -> OResult {
+    let mut projection = Projection::default();
+    for (name, value) in fields {
+        projection.push(String::from(*name), value.clone());
+    }
+    OResult::from(projection)
+}
+
+fn upper(value: &OValue) -> OrientResult<String> {
+    match value {
+        OValue::String(s) => Ok(s.to_uppercase()),
+        _ => Ok(String::from("NONE")),
+    }
+}
+
+fn unknown() -> String {
+    String::from("unknown")
+}
+
+#[derive(FromResult, IntoDocument, Debug, PartialEq)]
+struct Address {
+    street: String,
+}
+
+#[derive(FromResult, IntoDocument, Debug, PartialEq)]
+#[orient(rename_all = "camelCase")]
+struct Person {
+    first_name: String,
+    #[orient(rename = "@class")]
+    class: String,
+    #[orient(default)]
+    age: i32,
+    #[orient(default = unknown)]
+    nick: String,
+    #[orient(skip)]
+    cached: Option<i32>,
+    #[orient(flatten)]
+    address: Address,
+    #[orient(with = upper)]
+    city: String,
+}
+
+#[test]
+fn derive_from_result_attributes() {
+    let result = result(&[
+        ("firstName", OValue::String(String::from("John"))),
+        ("@class", OValue::String(String::from("Person"))),
+        ("age", OValue::Null),
+        ("cached", OValue::I32(10)),
+        ("street", OValue::String(String::from("Main Street"))),
+        ("city", OValue::String(String::from("Rome"))),
+    ]);
+
+    assert_eq!(
+        Person {
+            first_name: String::from("John"),
+            class: String::from("Person"),
+            age: 0,
+            nick: String::from("unknown"),
+            cached: None,
+            address: Address {
+                street: String::from("Main Street")
+            },
+            city: String::from("ROME"),
+        },
+        Person::from_result(result).unwrap()
+    );
+}
+
+#[derive(IntoDocument)]
+#[orient(rename_all = "camelCase")]
+struct Update {
+    first_name: String,
+    #[orient(skip)]
+    _ignored: i32,
+    #[orient(flatten)]
+    address: Address,
+}
+
+#[test]
+fn derive_into_document_attributes() {
+    let doc = Update {
+        first_name: String::from("John"),
+        _ignored: 1,
+        address: Address {
+            street: String::from("Main Street"),
+        },
+    }
+    .into_document();
+
+    assert_eq!("Update", doc.class_name());
+    assert_eq!(2, doc.len());
+    assert_eq!("John", doc.get::<String>("firstName"));
+    assert_eq!("Main Street", doc.get::<String>("street"));
+}
+
+#[derive(IntoDocument)]
+struct Stored {
+    #[orient(rename = "@rid")]
+    rid: ORecordID,
+    #[orient(rename = "@version")]
+    version: Option<i32>,
+    #[orient(rename = "@class")]
+    class: String,
+    name: String,
+}
+
+#[test]
+fn derive_into_document_metadata() {
+    let doc = Stored {
+        rid: ORecordID::new(9, 1),
+        version: Some(3),
+        class: String::from("Person"),
+        name: String::from("John"),
+    }
+    .into_document();
+
+    assert_eq!(&ORecordID::new(9, 1), doc.record_id());
+    assert_eq!(3, doc.version());
+    assert_eq!("Person", doc.class_name());
+    assert_eq!(1, doc.len());
+    assert_eq!("John", doc.get::<String>("name"));
+}
+
+#[derive(FromResult, IntoDocument, Debug, PartialEq)]
+struct Tagged<T>
+where
+    T: FromOValue + IntoOValue,
+{
+    name: String,
+    value: T,
+}
+
+#[test]
+fn derive_generic_struct() {
+    let tagged = Tagged {
+        name: String::from("size"),
+        value: 10i64,
+    };
+    let doc = tagged.into_document();
+    assert_eq!("Tagged", doc.class_name());
+    assert_eq!(10, doc.get::<i64>("value"));
+    let read = result(&[("name", "size".into()), ("value", OValue::I64(10))]);
+    assert_eq!(tagged, Tagged::from_result(read).unwrap());
+}
+
+#[derive(FromResult, Debug, PartialEq)]
+struct Row(String, #[orient(default)] i32, Option<String>);
+
+#[test]
+fn derive_from_result_tuple_struct() {
+    let result = result(&[
+        ("name", OValue::String(String::from("John"))),
+        ("age", OValue::Null),
+        ("city", OValue::String(String::from("Rome"))),
+    ]);
+
+    assert_eq!(
+        Row(String::from("John"), 0, Some(String::from("Rome"))),
+        Row::from_result(result).unwrap()
+    );
+}
+
+#[test]
+fn derive_from_result_tuple_struct_short_projection() {
+    // a projection without the optional column
+    let result = result(&[("name", OValue::String(String::from("John")))]);
+    assert_eq!(
+        Row(String::from("John"), 0, None),
+        Row::from_result(result).unwrap()
+    );
+}
+
+#[test]
+fn from_result_tuple() {
+    let result = result(&[
+        ("name", OValue::String(String::from("John"))),
+        ("age", OValue::I32(30)),
+    ]);
+
+    let (name, age): (String, i32) = FromResult::from_result(result.clone()).unwrap();
+    assert_eq!((String::from("John"), 30), (name, age));
+
+    assert!(<(String, i32, String)>::from_result(result.clone()).is_err());
+    let (_, _, city): (String, i32, Option<String>) = FromResult::from_result(result).unwrap();
+    assert_eq!(None, city);
+}
+
+#[derive(FromResult, Debug, PartialEq)]
+enum Vertex {
+    Address(Address),
+    #[orient(rename = "Animal")]
+    Pet {
+        name: String,
+    },
+    Unknown,
+}
+
+#[test]
+fn derive_from_result_enum() {
+    let address = result(&[
+        ("@class", OValue::String(String::from("Address"))),
+        ("street", OValue::String(String::from("Main Street"))),
+    ]);
+    assert_eq!(
+        Vertex::Address(Address {
+            street: String::from("Main Street")
+        }),
+        Vertex::from_result(address).unwrap()
+    );
+
+    let pet = result(&[
+        ("@class", OValue::String(String::from("Animal"))),
+        ("name", OValue::String(String::from("Rex"))),
+    ]);
+    assert_eq!(
+        Vertex::Pet {
+            name: String::from("Rex")
+        },
+        Vertex::from_result(pet).unwrap()
+    );
+
+    let unknown = result(&[("@class", OValue::String(String::from("Unknown")))]);
+    assert_eq!(Vertex::Unknown, Vertex::from_result(unknown).unwrap());
+
+    let other = result(&[("@class", OValue::String(String::from("Person")))]);
+    assert!(Vertex::from_result(other).is_err());
+}
+
+#[derive(FromResult, Debug, PartialEq)]
+enum Place {
+    Address(Address),
+    #[orient(other)]
+    Other {
+        #[orient(rename = "@class")]
+        class: String,
+    },
+}
+
+#[test]
+fn derive_from_result_enum_other() {
+    let address = result(&[
+        ("@class", OValue::String(String::from("Address"))),
+        ("street", OValue::String(String::from("Main Street"))),
+    ]);
+    assert_eq!(
+        Place::Address(Address {
+            street: String::from("Main Street")
+        }),
+        Place::from_result(address).unwrap()
+    );
+
+    let home = result(&[
+        ("@class", OValue::String(String::from("HomeAddress"))),
+        ("street", OValue::String(String::from("Main Street"))),
+    ]);
+    assert_eq!(
+        Place::Other {
+            class: String::from("HomeAddress")
+        },
+        Place::from_result(home).unwrap()
+    );
+}
+
+#[derive(FromResult, Debug, PartialEq)]
+struct Versioned {
+    #[orient(rename = "@rid")]
+    id: ORecordID,
+    #[orient(rename = "@class")]
+    class: String,
+    #[orient(rename = "@version")]
+    version: i32,
+    street: String,
+}
+
+#[test]
+fn from_result_metadata() {
+    let mut doc = ODocument::new("Address");
+    doc.set_record_id(ORecordID::new(10, 1));
+    doc.set_version(3);
+    doc.set("street", "Main Street");
+
+    let result = OResult::try_from((1, doc)).unwrap();
+    assert_eq!(Some(&ORecordID::new(10, 1)), result.rid());
+    assert_eq!(Some("Address"), result.class_name());
+    assert_eq!(Some(3), result.version());
+    assert_eq!(
+        Versioned {
+            id: ORecordID::new(10, 1),
+            class: String::from("Address"),
+            version: 3,
+            street: String::from("Main Street"),
+        },
+        Versioned::from_result(result).unwrap()
+    );
+
+    let mut projection = Projection::default();
+    projection.push(
+        String::from("street"),
+        OValue::String(String::from("Main Street")),
+    );
+    projection.set_metadata(String::from("@rid"), OValue::Link(ORecordID::new(10, 2)));
+    projection.set_metadata(
+        String::from("@class"),
+        OValue::String(String::from("Address")),
+    );
+    projection.set_metadata(String::from("@version"), OValue::I32(1));
+
+    let result = OResult::from(projection);
+    assert_eq!(Some(&ORecordID::new(10, 2)), result.rid());
+    assert_eq!(3, result.metadata().len());
+    let versioned = Versioned::from_result(result).unwrap();
+    assert_eq!(ORecordID::new(10, 2), versioned.id);
+    assert_eq!(1, versioned.version);
+
+    assert_eq!(None, OResult::empty().rid());
+}
@@ -1,8 +1,8 @@
 use orientdb_client::sync::types::resultset::ResultSet;
 
-use orientdb_client::types::value::{EmbeddedMap, OValue};
+use orientdb_client::types::value::{EmbeddedMap, IntoOValue, OValue};
 use orientdb_client::types::ODocument;
-use orientdb_client::OSession;
+use orientdb_client::{OSession, OrientError};
 
 mod common;
 
@@ -11,10 +11,10 @@ use common::{run_with_session, session};
 #[test]
 fn test_open_session_close() {
     let session = session("test_open_session_close");
-    assert!(session.session_id > 0);
-    match session.token {
-        Some(ref t) => assert!(t.len() > 0),
-        None => assert!(false),
+    assert!(session.session_id() > 0);
+    match session.token() {
+        Some(ref t) => assert!(!t.is_empty()),
+        None => panic!("Session token not found"),
     }
     let result = session.close();
     assert!(result.is_ok());
@@ -74,7 +74,7 @@ fn session_query_with_more_named_params() {
     run_with_session("session_query_with_more_named_params", |session| {
         let result: Vec<_> = session
             .query("select from OUser where name = :name and void =:void")
-            .named(&[("name", &"admin"), ("void", &1)])
+            .named(&[("name", &"admin" as &dyn IntoOValue), ("void", &1)])
             .run()
             .unwrap()
             .collect();
@@ -197,19 +197,93 @@ fn session_query_iter() {
     });
 }
 
+#[cfg(feature = "sugar")]
+#[test]
+fn session_save_into_document() {
+    use orientdb_client::derive::{FromResult, IntoDocument};
+
+    run_with_session("session_save_into_document", |session| {
+        #[derive(IntoDocument, FromResult, Debug, PartialEq)]
+        struct V {
+            name: String,
+            age: i32,
+        }
+
+        let person = V {
+            name: String::from("John"),
+            age: 30,
+        };
+        let (rid, _) = session.save(&person).unwrap();
+        let doc = session.load(rid).unwrap().unwrap();
+        assert_eq!("V", doc.class_name());
+        assert_eq!("John", doc.get::<String>("name"));
+
+        let result: Option<V> = session
+            .query("select from V where name = :name and age = :age")
+            .named(&person)
+            .fetch_one()
+            .unwrap();
+
+        assert_eq!(Some(person), result);
+    });
+}
+
+#[test]
+fn session_live_query() {
+    run_with_session("session_live_query", |session| {
+        let (unsubscriber, results) = session.live_query("live select from V").run().unwrap();
+
+        let _: Vec<_> = session
+            .command("insert into V set id = 1")
+            .run()
+            .unwrap()
+            .collect();
+        let _: Vec<_> = session
+            .command("update V set id = 2 where id = 1")
+            .run()
+            .unwrap()
+            .collect();
+        let _: Vec<_> = session
+            .command("delete vertex from V where id = 2")
+            .run()
+            .unwrap()
+            .collect();
+
+        unsubscriber.unsubscribe().unwrap();
+
+        let events: Vec<_> = results.map(|event| event.unwrap()).collect();
+        assert_eq!(3, events.len());
+    });
+}
+
+#[test]
+fn session_subscribe_schema() {
+    use orientdb_client::types::{PushEvent, PushKind};
+
+    run_with_session("session_subscribe_schema", |session| {
+        let mut events = session.subscribe(PushKind::Schema).unwrap();
+
+        let _: Vec<_> = session.command("create class Foo").run().unwrap().collect();
+
+        assert!(matches!(events.next(), Some(Ok(PushEvent::Schema(_)))));
+    });
+}
+
 #[cfg(feature = "uuid")]
 #[test]
 fn session_query_with_uuid() {
     use uuid::Uuid;
 
     run_with_session("session_query_with_uuid", |session| {
-        crate_schema(&session);
+        crate_schema(session);
 
         let uuid = Uuid::new_v4();
         session
             .command("create vertex Person set gid = ?")
             .positional(&[&uuid])
             .run()
+            .unwrap()
+            .close()
             .unwrap();
 
         let mut iter = session
@@ -252,11 +326,13 @@ fn session_query_with_projection() {
 #[test]
 fn session_query_with_complex_document() {
     run_with_session("session_query_with_complex_document", |session| {
-        crate_schema(&session);
+        crate_schema(session);
 
         session
             .command("create vertex Person content  { name : 'Jonh', address : { street : 'test street'}}")
             .run()
+            .unwrap()
+            .close()
             .unwrap();
 
         let result = session
@@ -283,14 +359,14 @@ fn session_query_with_complex_document() {
 #[test]
 fn session_query_projected_with_complex_document() {
     run_with_session("session_query_projected_with_complex_document", |session| {
-        crate_schema(&session);
+        crate_schema(session);
 
         let script = r#"
             let v1 = create vertex Person content  { name : 'Foo', address : { street : 'test street'}};
             let v2 = create vertex Person content  { name : 'Jonh', address : { street : 'test street'}};
             let e  = create edge HasFriend from $v1 to $v2;
         "#;
-        session.script_sql(script).run().unwrap();
+        session.script_sql(script).run().unwrap().close().unwrap();
 
         let result = session
             .query("select name,address from Person where name = :name")
@@ -316,31 +392,161 @@ fn session_query_projected_with_complex_document() {
     });
 }
 
+#[test]
+fn session_record_crud() {
+    run_with_session("session_record_crud", |session| {
+        let mut doc = ODocument::builder()
+            .set_class_name("V")
+            .set("name", "John")
+            .build();
+
+        let (rid, version) = session.save_document(&mut doc).unwrap();
+        assert!(!rid.is_new());
+        assert_eq!(&rid, doc.record_id());
+        assert_eq!(version, doc.version());
+
+        // Saving the refreshed document again updates the same record
+        let (same, version) = session.save_document(&mut doc).unwrap();
+        assert_eq!(rid, same);
+        assert_eq!(version, doc.version());
+
+        let mut loaded = session.load(rid.clone()).unwrap().unwrap();
+        assert_eq!(version, loaded.version());
+        assert_eq!(&rid, loaded.record_id());
+        assert_eq!("John", loaded.get::<String>("name"));
+
+        loaded.set("name", "Jack");
+        let (_, version) = session.save_document(&mut loaded).unwrap();
+
+        assert_eq!(version, loaded.version());
+        let loaded = session.load(rid.clone()).unwrap().unwrap();
+        assert_eq!(version, loaded.version());
+        assert_eq!("Jack", loaded.get::<String>("name"));
+
+        assert!(session.delete(rid.clone(), version).unwrap());
+        assert_eq!(None, session.load(rid).unwrap());
+
+        // The connection is still in sync after loading a missing record
+        assert_eq!(1, session.query("select 1 as one").run().unwrap().count());
+    });
+}
+
+#[test]
+fn session_transaction_commit() {
+    run_with_session("session_transaction_commit", |session| {
+        let mut tx = session.begin();
+        let temp = tx.save(
+            &ODocument::builder()
+                .set_class_name("V")
+                .set("name", "John")
+                .build(),
+        );
+        assert!(temp.is_new());
+
+        let result = tx.commit().unwrap();
+        let rid = result.created_rid(&temp).unwrap().clone();
+        let doc = session.load(rid).unwrap().unwrap();
+
+        assert_eq!(Some(doc.version()), result.version(&temp));
+        assert_eq!("John", doc.get::<String>("name"));
+    });
+}
+
+#[test]
+fn session_transaction_concurrent_modification() {
+    run_with_session("session_transaction_concurrent_modification", |session| {
+        let doc = ODocument::builder()
+            .set_class_name("V")
+            .set("name", "John")
+            .build();
+        let (rid, version) = session.save(&doc).unwrap();
+
+        let mut doc = session.load(rid).unwrap().unwrap();
+        doc.set("name", "Jack");
+        let mut tx = session.begin();
+        tx.save(&doc);
+        assert_eq!(
+            Some(version + 1),
+            tx.commit().unwrap().version(doc.record_id())
+        );
+
+        let mut tx = session.begin();
+        tx.save(&doc);
+        match tx.commit() {
+            Err(OrientError::ConcurrentModification(_)) => {}
+            result => panic!("Expected a concurrent modification, got {:?}", result),
+        }
+    });
+}
+
+#[test]
+fn session_tree_ridbag_iter() {
+    run_with_session("session_tree_ridbag_iter", |session| {
+        let hub = ODocument::builder()
+            .set_class_name("V")
+            .set("name", "hub")
+            .build();
+        let (rid, _) = session.save(&hub).unwrap();
+        for _ in 0..100 {
+            let leaf = ODocument::builder()
+                .set_class_name("V")
+                .set("name", "leaf")
+                .build();
+            session.save(&leaf).unwrap();
+        }
+        session
+            .command(format!(
+                "create edge E from #{}:{} to (select from V where name = 'leaf')",
+                rid.cluster, rid.position
+            ))
+            .run()
+            .unwrap()
+            .close()
+            .unwrap();
+
+        let hub = session.load(rid).unwrap().unwrap();
+        let edges = match hub.get_raw("out_E") {
+            Some(OValue::RidBag(bag)) => bag.iter(session).collect::<Result<Vec<_>, _>>().unwrap(),
+            value => panic!("Expected a ridbag, got {:?}", value),
+        };
+        assert_eq!(100, edges.len());
+    });
+}
+
 fn crate_schema(session: &OSession) {
     session
         .command("create class Person extends V")
         .run()
+        .unwrap()
+        .close()
         .unwrap();
 
     session
         .command("create class HasFriend extends E")
         .run()
+        .unwrap()
+        .close()
         .unwrap();
 
     session
         .command("create class Address abstract")
         .run()
+        .unwrap()
+        .close()
         .unwrap();
 
     session
         .command("create property Person.address EMBEDDED Address")
         .run()
+        .unwrap()
+        .close()
         .unwrap();
 }
 
 #[cfg(feature = "async")]
 mod asynchronous {
     use super::common::asynchronous::{session, sessions};
+    use orientdb_client::types::value::IntoOValue;
 
     use futures::StreamExt;
 
@@ -348,10 +554,10 @@ mod asynchronous {
     #[cfg_attr(feature = "tokio-runtime", tokio::test)]
     async fn test_open_session_close() {
         let session = session("test_async_open_session_close").await;
-        assert!(session.session_id > 0);
-        match session.token {
-            Some(ref t) => assert!(t.len() > 0),
-            None => assert!(false),
+        assert!(session.session_id() > 0);
+        match session.token() {
+            Some(ref t) => assert!(!t.is_empty()),
+            None => panic!("Session token not found"),
         }
         let result = session.close().await;
         assert!(result.is_ok());
@@ -424,7 +630,7 @@ mod asynchronous {
         let session = session("async_session_query_with_more_named_params").await;
         let result: Vec<_> = session
             .query("select from OUser where name = :name and void =:void")
-            .named(&[("name", &"admin"), ("void", &1)])
+            .named(&[("name", &"admin" as &dyn IntoOValue), ("void", &1)])
             .run()
             .await
             .unwrap()
@@ -496,11 +702,8 @@ mod asynchronous {
                         s.command("create edge from (select from v where id = '1') to (select from v where id = '2')").run().await
                     }).await;
 
-                    match inner_resut {
-                        Ok(_e) => {
-                            new_counter.fetch_add(1,Ordering::SeqCst);
-                        },
-                        _=> {}
+                    if let Ok(_e) = inner_resut {
+                        new_counter.fetch_add(1,Ordering::SeqCst);
                     };
                 })
             }).collect();
@@ -590,11 +793,8 @@ mod asynchronous {
                         s.command("create edge from (select from v where id = '1') to (select from v where id = '2')").run().await
                     }).await;
 
-                    match inner_resut {
-                        Ok(_e) => {
-                            new_counter.fetch_add(1,Ordering::SeqCst);
-                        },
-                        _=> {}
+                    if let Ok(_e) = inner_resut {
+                        new_counter.fetch_add(1,Ordering::SeqCst);
                     };
                 })
             }).collect();
@@ -663,6 +863,145 @@ mod asynchronous {
         assert_eq!(3, counter);
     }
 
+    #[cfg_attr(feature = "async-std-runtime", async_std::test)]
+    #[cfg_attr(feature = "tokio-runtime", tokio::test)]
+    async fn live_query_resilient_test() {
+        use orientdb_client::types::OResult;
+
+        let pool = sessions("live_query_resilient_test").await;
+
+        let session = pool.get().await.unwrap();
+
+        let (unsubscriber, stream) = session
+            .live_query("live select from V")
+            .resilient(true)
+            .run()
+            .await
+            .unwrap();
+
+        let _result: Vec<Result<OResult, _>> = session
+            .command("insert into v set id = 1")
+            .run()
+            .await
+            .unwrap()
+            .collect()
+            .await;
+
+        unsubscriber.unsubscribe().await.unwrap();
+
+        let events: Vec<_> = stream.collect().await;
+        assert_eq!(1, events.len());
+    }
+
+    #[cfg_attr(feature = "async-std-runtime", async_std::test)]
+    #[cfg_attr(feature = "tokio-runtime", tokio::test)]
+    async fn session_record_crud() {
+        use orientdb_client::types::ODocument;
+
+        let session = session("async_session_record_crud").await;
+
+        let mut doc = ODocument::builder()
+            .set_class_name("V")
+            .set("name", "John")
+            .build();
+
+        let (rid, version) = session.save_document(&mut doc).await.unwrap();
+        assert!(!rid.is_new());
+        assert_eq!(&rid, doc.record_id());
+        assert_eq!(version, doc.version());
+
+        // Saving the refreshed document again updates the same record
+        let (same, version) = session.save_document(&mut doc).await.unwrap();
+        assert_eq!(rid, same);
+        assert_eq!(version, doc.version());
+
+        let mut loaded = session.load(rid.clone()).await.unwrap().unwrap();
+        assert_eq!(version, loaded.version());
+        assert_eq!("John", loaded.get::<String>("name"));
+
+        loaded.set("name", "Jack");
+        let (_, version) = session.save_document(&mut loaded).await.unwrap();
+
+        assert_eq!(version, loaded.version());
+        let loaded = session.load(rid.clone()).await.unwrap().unwrap();
+        assert_eq!(version, loaded.version());
+        assert_eq!("Jack", loaded.get::<String>("name"));
+
+        assert!(session.delete(rid.clone(), version).await.unwrap());
+        assert_eq!(None, session.load(rid).await.unwrap());
+
+        // The connection is still in sync after loading a missing record
+        let rows: Vec<_> = session
+            .query("select 1 as one")
+            .run()
+            .await
+            .unwrap()
+            .collect()
+            .await;
+        assert_eq!(1, rows.len());
+    }
+
+    #[cfg_attr(feature = "async-std-runtime", async_std::test)]
+    #[cfg_attr(feature = "tokio-runtime", tokio::test)]
+    async fn session_transaction_commit() {
+        use orientdb_client::types::ODocument;
+
+        let session = session("async_session_transaction_commit").await;
+
+        let mut tx = session.begin();
+        let temp = tx.save(
+            &ODocument::builder()
+                .set_class_name("V")
+                .set("name", "John")
+                .build(),
+        );
+        let result = tx.commit().await.unwrap();
+        let rid = result.created_rid(&temp).unwrap().clone();
+        let doc = session.load(rid).await.unwrap().unwrap();
+
+        assert_eq!(Some(doc.version()), result.version(&temp));
+        assert_eq!("John", doc.get::<String>("name"));
+    }
+
+    #[cfg_attr(feature = "async-std-runtime", async_std::test)]
+    #[cfg_attr(feature = "tokio-runtime", tokio::test)]
+    async fn session_transaction_with_retry() {
+        use orientdb_client::types::ODocument;
+        use orientdb_client::OrientError;
+        use std::sync::atomic::{AtomicI32, Ordering};
+
+        let session = session("async_session_transaction_with_retry").await;
+
+        let doc = ODocument::builder()
+            .set_class_name("V")
+            .set("name", "John")
+            .build();
+        let (rid, version) = session.save(&doc).await.unwrap();
+
+        let mut stale = session.load(rid.clone()).await.unwrap().unwrap();
+        stale.set_version(version - 1);
+
+        let attempts = AtomicI32::new(0);
+        let result = session
+            .with_retry(2, |s| {
+                let stale = stale.clone();
+                let attempts = &attempts;
+                async move {
+                    attempts.fetch_add(1, Ordering::SeqCst);
+                    let mut tx = s.begin();
+                    tx.save(&stale);
+                    tx.commit().await
+                }
+            })
+            .await;
+
+        match result {
+            Err(OrientError::ConcurrentModification(_)) => {}
+            result => panic!("Expected a concurrent modification, got {:?}", result),
+        }
+        assert_eq!(3, attempts.load(Ordering::SeqCst));
+    }
+
     #[cfg_attr(feature = "async-std-runtime", async_std::test)]
     #[cfg_attr(feature = "tokio-runtime", tokio::test)]
     #[cfg(feature = "sugar")]
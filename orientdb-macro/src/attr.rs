@@ -0,0 +1,191 @@
+use syn::{Attribute, Expr, ExprLit, Field, Lit, LitStr, Path, Variant};
+
+/// Options of the `#[orient(...)]` attribute on a struct
+#[derive(Default)]
+pub struct Container {
+    pub rename_all: Option<RenameRule>,
+}
+
+impl Container {
+    pub fn from_attrs(attrs: &[Attribute]) -> syn::Result<Container> {
+        let mut container = Container::default();
+        for attr in attrs.iter().filter(|a| a.path().is_ident("orient")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("rename_all") {
+                    let rule: LitStr = meta.value()?.parse()?;
+                    container.rename_all = Some(RenameRule::from_lit(&rule)?);
+                    Ok(())
+                } else {
+                    Err(meta.error("unsupported orient container attribute"))
+                }
+            })?;
+        }
+        Ok(container)
+    }
+}
+
+pub enum DefaultValue {
+    Trait,
+    Path(Path),
+}
+
+/// Options of the `#[orient(...)]` attribute on a field
+pub struct FieldAttr {
+    pub rename: Option<String>,
+    pub default: Option<DefaultValue>,
+    pub skip: bool,
+    pub flatten: bool,
+    pub with: Option<Path>,
+}
+
+impl FieldAttr {
+    pub fn from_field(field: &Field) -> syn::Result<FieldAttr> {
+        let mut attr = FieldAttr {
+            rename: None,
+            default: None,
+            skip: false,
+            flatten: false,
+            with: None,
+        };
+        for a in field.attrs.iter().filter(|a| a.path().is_ident("orient")) {
+            a.parse_nested_meta(|meta| {
+                if meta.path.is_ident("rename") {
+                    let name: LitStr = meta.value()?.parse()?;
+                    attr.rename = Some(name.value());
+                } else if meta.path.is_ident("default") {
+                    attr.default = if meta.input.peek(syn::Token![=]) {
+                        Some(DefaultValue::Path(parse_path(meta.value()?.parse()?)?))
+                    } else {
+                        Some(DefaultValue::Trait)
+                    };
+                } else if meta.path.is_ident("skip") {
+                    attr.skip = true;
+                } else if meta.path.is_ident("flatten") {
+                    attr.flatten = true;
+                } else if meta.path.is_ident("with") {
+                    attr.with = Some(parse_path(meta.value()?.parse()?)?);
+                } else {
+                    return Err(meta.error("unsupported orient field attribute"));
+                }
+                Ok(())
+            })?;
+        }
+        if attr.flatten && (attr.rename.is_some() || attr.with.is_some()) {
+            return Err(syn::Error::new_spanned(
+                field,
+                "flatten cannot be combined with rename or with",
+            ));
+        }
+        Ok(attr)
+    }
+
+    /// Name of the property read or written for the field
+    pub fn name(&self, field: &str, container: &Container) -> String {
+        match (&self.rename, &container.rename_all) {
+            (Some(name), _) => name.clone(),
+            (None, Some(rule)) => rule.apply(field),
+            (None, None) => String::from(field),
+        }
+    }
+}
+
+/// Options of the `#[orient(...)]` attribute on an enum variant
+pub struct VariantAttr {
+    pub rename: Option<String>,
+    pub other: bool,
+}
+
+impl VariantAttr {
+    pub fn from_variant(variant: &Variant) -> syn::Result<VariantAttr> {
+        let mut attr = VariantAttr {
+            rename: None,
+            other: false,
+        };
+        for a in variant.attrs.iter().filter(|a| a.path().is_ident("orient")) {
+            a.parse_nested_meta(|meta| {
+                if meta.path.is_ident("rename") {
+                    let name: LitStr = meta.value()?.parse()?;
+                    attr.rename = Some(name.value());
+                    Ok(())
+                } else if meta.path.is_ident("other") {
+                    attr.other = true;
+                    Ok(())
+                } else {
+                    Err(meta.error("unsupported orient variant attribute"))
+                }
+            })?;
+        }
+        if attr.other && attr.rename.is_some() {
+            return Err(syn::Error::new_spanned(
+                variant,
+                "other cannot be combined with rename",
+            ));
+        }
+        Ok(attr)
+    }
+
+    /// Class matched by the variant
+    pub fn name(&self, variant: &str) -> String {
+        self.rename.clone().unwrap_or_else(|| String::from(variant))
+    }
+}
+
+// Accept both `with = path` and `with = "path"`
+fn parse_path(expr: Expr) -> syn::Result<Path> {
+    match expr {
+        Expr::Path(path) => Ok(path.path),
+        Expr::Lit(ExprLit {
+            lit: Lit::Str(lit), ..
+        }) => lit.parse(),
+        expr => Err(syn::Error::new_spanned(expr, "expected a path")),
+    }
+}
+
+pub enum RenameRule {
+    Lower,
+    Upper,
+    Pascal,
+    Camel,
+    Snake,
+    ScreamingSnake,
+    Kebab,
+}
+
+impl RenameRule {
+    fn from_lit(lit: &LitStr) -> syn::Result<RenameRule> {
+        match lit.value().as_str() {
+            "lowercase" => Ok(RenameRule::Lower),
+            "UPPERCASE" => Ok(RenameRule::Upper),
+            "PascalCase" => Ok(RenameRule::Pascal),
+            "camelCase" => Ok(RenameRule::Camel),
+            "snake_case" => Ok(RenameRule::Snake),
+            "SCREAMING_SNAKE_CASE" => Ok(RenameRule::ScreamingSnake),
+            "kebab-case" => Ok(RenameRule::Kebab),
+            _ => Err(syn::Error::new_spanned(lit, "unsupported rename_all rule")),
+        }
+    }
+
+    // Field names are snake_case
+    pub fn apply(&self, field: &str) -> String {
+        match self {
+            RenameRule::Lower | RenameRule::Snake => field.to_lowercase(),
+            RenameRule::Upper | RenameRule::ScreamingSnake => field.to_uppercase(),
+            RenameRule::Kebab => field.replace('_', "-"),
+            RenameRule::Pascal | RenameRule::Camel => {
+                let mut name = String::new();
+                let mut upper = matches!(self, RenameRule::Pascal);
+                for c in field.chars() {
+                    if c == '_' {
+                        upper = true;
+                    } else if upper {
+                        name.extend(c.to_uppercase());
+                        upper = false;
+                    } else {
+                        name.push(c);
+                    }
+                }
+                name
+            }
+        }
+    }
+}
@@ -0,0 +1,102 @@
+use crate::attr::{Container, FieldAttr};
+use quote::quote;
+use syn::{parse_quote, Data, DataStruct, DeriveInput, Fields, FieldsNamed, Stmt};
+
+pub fn derive(input: &DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    match &input.data {
+        Data::Struct(DataStruct {
+            fields: Fields::Named(FieldsNamed { named, .. }),
+            ..
+        }) => {
+            let ident = &input.ident;
+            let class_name = ident.to_string();
+            let container = Container::from_attrs(&input.attrs)?;
+
+            let mut class = quote!(::std::string::String::from(#class_name));
+            let mut writes: Vec<Stmt> = Vec::new();
+            for field in named.iter() {
+                let id = field.ident.as_ref().expect("named field");
+                let attr = FieldAttr::from_field(field)?;
+                // default and with only apply when reading a result
+                if attr.skip {
+                    continue;
+                }
+                if attr.flatten {
+                    writes.push(parse_quote!(
+                        for (name, value) in orientdb_client::types::IntoDocument::into_document(&self.#id).iter() {
+                            doc.set_raw(name.clone(), value.clone());
+                        }
+                    ));
+                    continue;
+                }
+                let name = attr.name(&id.to_string(), &container);
+                // record metadata is not a field of the document
+                match name.as_str() {
+                    "@rid" => writes.push(parse_quote!(
+                        if let orientdb_client::types::value::OValue::Link(rid) =
+                            orientdb_client::types::value::IntoOValue::into_ovalue(&self.#id)
+                        {
+                            doc.set_record_id(rid);
+                        }
+                    )),
+                    "@version" => writes.push(parse_quote!(
+                        if let orientdb_client::types::value::OValue::I32(version) =
+                            orientdb_client::types::value::IntoOValue::into_ovalue(&self.#id)
+                        {
+                            doc.set_version(version);
+                        }
+                    )),
+                    "@class" => {
+                        class = quote!(
+                            match orientdb_client::types::value::IntoOValue::into_ovalue(&self.#id) {
+                                orientdb_client::types::value::OValue::String(class) => class,
+                                _ => ::std::string::String::from(#class_name),
+                            }
+                        )
+                    }
+                    _ if name.starts_with('@') => {
+                        return Err(syn::Error::new_spanned(
+                            field,
+                            format!(
+                                "`{}` cannot be written to a document, only `@rid`, `@class` and `@version` are supported",
+                                name
+                            ),
+                        ))
+                    }
+                    _ => writes.push(parse_quote!(
+                        doc.set_raw(#name, orientdb_client::types::value::IntoOValue::into_ovalue(&self.#id));
+                    )),
+                }
+            }
+
+            let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+            Ok(quote! {
+
+                impl #impl_generics orientdb_client::types::IntoDocument for #ident #ty_generics #where_clause {
+
+                    fn into_document(&self) -> orientdb_client::types::ODocument {
+                        let mut doc = orientdb_client::types::ODocument::new(#class);
+
+                        #(#writes)*
+
+                        doc
+                    }
+                }
+
+                impl #impl_generics orientdb_client::types::value::IntoOValue for #ident #ty_generics #where_clause {
+
+                    fn into_ovalue(&self) -> orientdb_client::types::value::OValue {
+                        orientdb_client::types::value::OValue::Document(
+                            orientdb_client::types::IntoDocument::into_document(self),
+                        )
+                    }
+                }
+            })
+        }
+        _ => Err(syn::Error::new_spanned(
+            input,
+            "Only structs with named fields are supported for IntoDocument derive",
+        )),
+    }
+}
@@ -1,8 +1,10 @@
 use proc_macro::TokenStream;
 
+mod attr;
+mod document;
 mod result;
 
-#[proc_macro_derive(FromResult)]
+#[proc_macro_derive(FromResult, attributes(orient))]
 pub fn derive_from_result(input: TokenStream) -> TokenStream {
     let input = syn::parse_macro_input!(input as syn::DeriveInput);
 
@@ -11,3 +13,13 @@ pub fn derive_from_result(input: TokenStream) -> TokenStream {
         Err(e) => e.to_compile_error().into(),
     }
 }
+
+#[proc_macro_derive(IntoDocument, attributes(orient))]
+pub fn derive_into_document(input: TokenStream) -> TokenStream {
+    let input = syn::parse_macro_input!(input as syn::DeriveInput);
+
+    match document::derive(&input) {
+        Ok(ts) => ts.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
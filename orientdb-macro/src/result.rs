@@ -1,44 +1,200 @@
+use crate::attr::{Container, DefaultValue, FieldAttr, VariantAttr};
 use quote::quote;
-use syn::{parse_quote, Data, DataStruct, DeriveInput, Fields, FieldsNamed, Stmt};
+use syn::{
+    parse_quote, Arm, Data, DataEnum, DataStruct, DeriveInput, Expr, Field, Fields, FieldsNamed,
+    FieldsUnnamed, Index, Path, Stmt,
+};
 
 pub fn derive(input: &DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
-    match &input.data {
-        Data::Struct(DataStruct {
-            fields: Fields::Named(FieldsNamed { named, .. }),
-            ..
-        }) => {
-            let ident = &input.ident;
-
-            let reads = named.iter().filter_map(|field| -> Option<Stmt> {
-                let id = &field.ident.as_ref()?;
-                let id_s = id.to_string();
-                let ty = &field.ty;
-
-                Some(parse_quote!(
-                    let #id: #ty = result.get_or_null(#id_s)?;
-                ))
-            });
+    let ident = &input.ident;
+    let container = Container::from_attrs(&input.attrs)?;
 
-            let names = named.iter().map(|field| &field.ident);
+    let body = match &input.data {
+        Data::Struct(DataStruct { fields, .. }) => {
+            read_fields(&parse_quote!(#ident), fields, &container)?
+        }
+        Data::Enum(DataEnum { variants, .. }) => {
+            let mut fallback = None;
+            let mut arms = variants
+                .iter()
+                .map(|variant| -> syn::Result<Option<Arm>> {
+                    let id = &variant.ident;
+                    let attr = VariantAttr::from_variant(variant)?;
+                    let read = match &variant.fields {
+                        // A newtype variant wraps the struct mapped on the class
+                        Fields::Unnamed(FieldsUnnamed { unnamed, .. }) if unnamed.len() == 1 => {
+                            parse_quote!(Ok(#ident::#id(
+                                orientdb_client::types::result::FromResult::from_result(result)?
+                            )))
+                        }
+                        fields => read_fields(&parse_quote!(#ident::#id), fields, &container)?,
+                    };
+                    if attr.other {
+                        // The catch-all variant reads any class without a variant,
+                        // e.g. a subclass of the mapped classes
+                        if fallback.is_some() {
+                            return Err(syn::Error::new_spanned(
+                                variant,
+                                "only one variant can be marked as other",
+                            ));
+                        }
+                        fallback = Some(parse_quote!(_ => { #read }));
+                        return Ok(None);
+                    }
+                    let class = attr.name(&id.to_string());
+                    Ok(Some(parse_quote!(Some(#class) => { #read })))
+                })
+                .filter_map(Result::transpose)
+                .collect::<syn::Result<Vec<Arm>>>()?;
+
+            let name = ident.to_string();
+
+            match fallback {
+                Some(arm) => arms.push(arm),
+                None => arms.extend(vec![
+                    parse_quote!(Some(class) => Err(orientdb_client::OrientError::Conversion(
+                        format!("Unexpected class {} for {}", class, #name)
+                    )),),
+                    parse_quote!(None => Err(orientdb_client::OrientError::Conversion(
+                        format!("Missing @class for {}", #name)
+                    )),),
+                ]),
+            }
+
+            parse_quote!({
+                let class = result.class_name().map(String::from);
+                match class.as_deref() {
+                    #(#arms)*
+                }
+            })
+        }
+        Data::Union(_) => {
+            return Err(syn::Error::new_spanned(
+                input,
+                "Only structs and enums are supported for FromResult derive",
+            ))
+        }
+    };
+
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
 
-            Ok(quote! {
+        impl #impl_generics orientdb_client::types::result::FromResult for #ident #ty_generics #where_clause {
 
-                impl orientdb_client::types::result::FromResult for #ident {
+            fn from_result(result : orientdb_client::types::OResult) -> orientdb_client::OrientResult<Self> where Self: Sized {
+                #body
+            }
+        }
+    })
+}
 
-                    fn from_result(result : orientdb_client::types::OResult) -> orientdb_client::OrientResult<Self> where Self: Sized {
+// Build `path` from the result, by name for named fields and by position
+// in the projection for tuple fields.
+fn read_fields(path: &Path, fields: &Fields, container: &Container) -> syn::Result<Expr> {
+    match fields {
+        Fields::Named(FieldsNamed { named, .. }) => {
+            let reads = named
+                .iter()
+                .map(|field| {
+                    let id = field.ident.as_ref().expect("named field");
+                    let attr = FieldAttr::from_field(field)?;
+                    let name = attr.name(&id.to_string(), container);
+                    read_field(field, &attr, Column::Name(name), parse_quote!(#id))
+                })
+                .collect::<syn::Result<Vec<Stmt>>>()?;
+
+            let names = named.iter().map(|field| &field.ident);
 
-                        #(#reads)*
+            Ok(parse_quote!({
+                #(#reads)*
 
-                        Ok(#ident {
-                            #(#names),*
-                        })
+                Ok(#path {
+                    #(#names),*
+                })
+            }))
+        }
+        Fields::Unnamed(FieldsUnnamed { unnamed, .. }) => {
+            let reads = unnamed
+                .iter()
+                .enumerate()
+                .map(|(idx, field)| {
+                    let attr = FieldAttr::from_field(field)?;
+                    if attr.rename.is_some() || attr.flatten {
+                        return Err(syn::Error::new_spanned(
+                            field,
+                            "rename and flatten are not supported on tuple fields",
+                        ));
                     }
-                }
-            })
+                    let id = quote::format_ident!("field_{}", idx);
+                    read_field(field, &attr, Column::Index(Index::from(idx)), id)
+                })
+                .collect::<syn::Result<Vec<Stmt>>>()?;
+
+            let names = (0..unnamed.len()).map(|idx| quote::format_ident!("field_{}", idx));
+
+            Ok(parse_quote!({
+                #(#reads)*
+
+                Ok(#path(#(#names),*))
+            }))
         }
-        _ => Err(syn::Error::new_spanned(
-            input,
-            "Only structs are supported for Repo derive",
-        )),
+        Fields::Unit => Ok(parse_quote!(Ok(#path))),
     }
 }
+
+enum Column {
+    Name(String),
+    Index(Index),
+}
+
+fn read_field(
+    field: &Field,
+    attr: &FieldAttr,
+    column: Column,
+    id: syn::Ident,
+) -> syn::Result<Stmt> {
+    let ty = &field.ty;
+
+    let default: Expr = match &attr.default {
+        Some(DefaultValue::Path(path)) => parse_quote!(#path()),
+        _ => parse_quote!(::std::default::Default::default()),
+    };
+
+    let convert: Expr = match &attr.with {
+        Some(path) => parse_quote!(#path(value)?),
+        None => parse_quote!(orientdb_client::types::value::FromOValue::from_value(
+            value
+        )?),
+    };
+
+    let raw: Expr = match &column {
+        Column::Name(name) => parse_quote!(result.get_raw(#name)),
+        Column::Index(idx) => parse_quote!(result.get_raw_at(#idx)),
+    };
+
+    let read: Expr = if attr.skip {
+        default
+    } else if attr.flatten {
+        parse_quote!(orientdb_client::types::result::FromResult::from_result(
+            result.clone()
+        )?)
+    } else if attr.default.is_some() {
+        parse_quote!(match #raw {
+            None | Some(orientdb_client::types::value::OValue::Null) => #default,
+            Some(value) => #convert,
+        })
+    } else if let (Column::Name(name), None) = (&column, &attr.with) {
+        parse_quote!(result.get_or_null(#name)?)
+    } else {
+        // a missing column reads as null like a missing field, so `Option` columns are `None`
+        parse_quote!({
+            let value = #raw.unwrap_or(&orientdb_client::types::value::OValue::Null);
+            #convert
+        })
+    };
+
+    Ok(parse_quote!(
+        let #id: #ty = #read;
+    ))
+}